@@ -0,0 +1,3 @@
+DROP TABLE IF EXISTS employees;
+DROP TABLE IF EXISTS salary_grades;
+DROP TABLE IF EXISTS departments;
@@ -0,0 +1,31 @@
+-- Login accounts and API keys
+
+CREATE TABLE users (
+  id INT AUTO_INCREMENT PRIMARY KEY,
+  employee_id CHAR(36) NULL UNIQUE,
+  name VARCHAR(255) NOT NULL,
+  email VARCHAR(255) NOT NULL UNIQUE,
+  password_hash VARCHAR(255) NULL,
+  failed_login_attempts INT UNSIGNED NOT NULL DEFAULT 0,
+  locked_until TIMESTAMP NULL,
+  reset_token_hash CHAR(64) NULL UNIQUE,
+  reset_token_expires_at TIMESTAMP NULL,
+  created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+  updated_at TIMESTAMP NULL ON UPDATE CURRENT_TIMESTAMP,
+  CONSTRAINT fk_user_employee FOREIGN KEY (employee_id) REFERENCES employees(id) ON DELETE CASCADE
+) ENGINE=InnoDB;
+
+-- Create api_keys table (non-interactive clients, only the SHA-256 of a key is stored)
+CREATE TABLE api_keys (
+  id CHAR(36) PRIMARY KEY,
+  name VARCHAR(255) NOT NULL,
+  key_prefix CHAR(12) NOT NULL,
+  key_hash CHAR(64) NOT NULL UNIQUE,
+  scopes VARCHAR(500) NOT NULL,
+  created_by CHAR(36) NULL,
+  created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+  last_used_at TIMESTAMP NULL,
+  revoked_at TIMESTAMP NULL,
+  CONSTRAINT fk_api_key_creator FOREIGN KEY (created_by) REFERENCES employees(id) ON DELETE SET NULL
+) ENGINE=InnoDB;
+
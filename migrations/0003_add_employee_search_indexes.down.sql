@@ -0,0 +1,5 @@
+DROP INDEX idx_emp_first_name ON employees;
+DROP INDEX idx_emp_last_name ON employees;
+ALTER TABLE employees
+  MODIFY first_name VARCHAR(100) NOT NULL,
+  MODIFY last_name VARCHAR(100) NOT NULL;
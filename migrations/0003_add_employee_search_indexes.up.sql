@@ -0,0 +1,10 @@
+-- Employee search: accent and case insensitive names with indexes for prefix matching
+
+-- Pin the collation so the search doesn't depend on the server default
+ALTER TABLE employees
+  MODIFY first_name VARCHAR(100) CHARACTER SET utf8mb4 COLLATE utf8mb4_0900_ai_ci NOT NULL,
+  MODIFY last_name VARCHAR(100) CHARACTER SET utf8mb4 COLLATE utf8mb4_0900_ai_ci NOT NULL;
+
+-- Create indexes, email is already covered by its unique key
+CREATE INDEX idx_emp_last_name ON employees(last_name);
+CREATE INDEX idx_emp_first_name ON employees(first_name);
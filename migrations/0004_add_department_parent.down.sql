@@ -0,0 +1,3 @@
+ALTER TABLE departments DROP FOREIGN KEY fk_dept_parent;
+DROP INDEX idx_dept_parent ON departments;
+ALTER TABLE departments DROP COLUMN parent_id;
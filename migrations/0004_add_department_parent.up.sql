@@ -0,0 +1,8 @@
+-- Department hierarchy: divisions contain departments, departments contain teams
+
+ALTER TABLE departments
+  ADD COLUMN parent_id CHAR(36) NULL AFTER head_id,
+  ADD CONSTRAINT fk_dept_parent FOREIGN KEY (parent_id) REFERENCES departments(id) ON DELETE SET NULL;
+
+-- Create indexes
+CREATE INDEX idx_dept_parent ON departments(parent_id);
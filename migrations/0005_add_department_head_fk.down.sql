@@ -0,0 +1,2 @@
+ALTER TABLE departments DROP FOREIGN KEY fk_dept_head;
+DROP INDEX idx_dept_head ON departments;
@@ -0,0 +1,12 @@
+-- Department heads must be existing employees, heads that point nowhere are cleared first
+
+UPDATE departments d
+  LEFT JOIN employees e ON e.id = d.head_id
+  SET d.head_id = NULL
+  WHERE d.head_id IS NOT NULL AND e.id IS NULL;
+
+-- Create indexes
+CREATE INDEX idx_dept_head ON departments(head_id);
+
+ALTER TABLE departments
+  ADD CONSTRAINT fk_dept_head FOREIGN KEY (head_id) REFERENCES employees(id) ON DELETE SET NULL;
@@ -0,0 +1,2 @@
+ALTER TABLE departments DROP COLUMN deleted_at;
+ALTER TABLE salary_grades DROP COLUMN deleted_at;
@@ -0,0 +1,7 @@
+-- Deleted departments and salary grades are kept for payroll audits, deleted_at marks them
+
+ALTER TABLE departments
+  ADD COLUMN deleted_at TIMESTAMP NULL AFTER parent_id;
+
+ALTER TABLE salary_grades
+  ADD COLUMN deleted_at TIMESTAMP NULL AFTER description;
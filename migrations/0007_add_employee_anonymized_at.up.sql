@@ -0,0 +1,5 @@
+-- Anonymized employees keep their row for reports and manager_id references, anonymized_at
+-- marks that name and email were replaced for good
+
+ALTER TABLE employees
+  ADD COLUMN anonymized_at TIMESTAMP NULL AFTER deleted_at;
@@ -0,0 +1,10 @@
+-- Removes the sample rows only, data created through the API is kept
+DELETE FROM users WHERE employee_id = '750e8400-e29b-41d4-a716-446655440001';
+
+UPDATE departments SET head_id = NULL WHERE id LIKE '650e8400-e29b-41d4-a716-4466554400__';
+
+DELETE FROM employees WHERE id LIKE '750e8400-e29b-41d4-a716-4466554400__';
+
+DELETE FROM departments WHERE id LIKE '650e8400-e29b-41d4-a716-4466554400__';
+
+DELETE FROM salary_grades WHERE id LIKE '550e8400-e29b-41d4-a716-4466554400__';
@@ -1,51 +1,5 @@
--- Drop existing tables if they exist (for clean restart)
-DROP TABLE IF EXISTS employees;
-DROP TABLE IF EXISTS salary_grades;
-DROP TABLE IF EXISTS departments;
-
--- Create departments table
-CREATE TABLE departments (
-  id CHAR(36) PRIMARY KEY,
-  name VARCHAR(255) NOT NULL UNIQUE,
-  head_id CHAR(36) NULL,
-  created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
-  updated_at TIMESTAMP NULL ON UPDATE CURRENT_TIMESTAMP
-) ENGINE=InnoDB;
-
--- Create salary_grades table
-CREATE TABLE salary_grades (
-  id CHAR(36) PRIMARY KEY,
-  code VARCHAR(50) NOT NULL UNIQUE,
-  base_salary DECIMAL(12,2) NOT NULL,
-  description TEXT NULL,
-  created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
-) ENGINE=InnoDB;
-
--- Create employees table
-CREATE TABLE employees (
-  id CHAR(36) PRIMARY KEY,
-  first_name VARCHAR(100) NOT NULL,
-  last_name VARCHAR(100) NOT NULL,
-  email VARCHAR(255) NOT NULL UNIQUE,
-  department_id CHAR(36) NULL,
-  salary_grade_id CHAR(36) NULL,
-  manager_id CHAR(36) NULL,
-  role ENUM('Admin','DepartmentHead','DeputyHead','Employee') NOT NULL DEFAULT 'Employee',
-  hire_date DATE NULL,
-  active BOOLEAN NOT NULL DEFAULT TRUE,
-  deleted_at TIMESTAMP NULL,
-  created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
-  updated_at TIMESTAMP NULL ON UPDATE CURRENT_TIMESTAMP,
-  CONSTRAINT fk_emp_department FOREIGN KEY (department_id) REFERENCES departments(id) ON DELETE SET NULL,
-  CONSTRAINT fk_emp_salary FOREIGN KEY (salary_grade_id) REFERENCES salary_grades(id) ON DELETE SET NULL,
-  CONSTRAINT fk_emp_manager FOREIGN KEY (manager_id) REFERENCES employees(id) ON DELETE SET NULL
-) ENGINE=InnoDB;
-
--- Create indexes
-CREATE INDEX idx_emp_department ON employees(department_id);
-CREATE INDEX idx_emp_manager ON employees(manager_id);
-CREATE INDEX idx_emp_salary ON employees(salary_grade_id);
-CREATE INDEX idx_emp_active ON employees(active);
+-- Sample data for development and demos, loaded by `backend seed` and removed by `backend seed remove`.
+-- The admin login for Alice Johnson is created by the command with a random password.
 
 -- Insert sample salary grades
 INSERT INTO salary_grades (id, code, base_salary, description) VALUES
@@ -0,0 +1,39 @@
+// Account security settings - login lockout, password reset tokens and the secret token helpers
+// shared with API keys
+
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use sha2::{Digest, Sha256};
+
+pub const DEFAULT_MAX_FAILED_LOGINS: u32 = 5;
+pub const DEFAULT_LOCKOUT_SECONDS: i64 = 15 * 60;
+pub const DEFAULT_RESET_TOKEN_TTL_SECONDS: i64 = 60 * 60;
+
+#[derive(Debug, Clone)]
+pub struct AccountPolicy {
+    /// Failed logins in a row after which the account gets locked
+    pub max_failed_logins: u32,
+    pub lockout_seconds: i64,
+    pub reset_token_ttl_seconds: i64,
+}
+
+impl Default for AccountPolicy {
+    fn default() -> Self {
+        AccountPolicy {
+            max_failed_logins: DEFAULT_MAX_FAILED_LOGINS,
+            lockout_seconds: DEFAULT_LOCKOUT_SECONDS,
+            reset_token_ttl_seconds: DEFAULT_RESET_TOKEN_TTL_SECONDS,
+        }
+    }
+}
+
+/// Random 256 bit token as hex, handed out once and only stored as hash
+pub fn generate_secret_token() -> String {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    hex::encode(bytes)
+}
+
+/// Secret tokens are long random values, a fast unsalted hash is enough and keeps them searchable
+pub fn hash_secret_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
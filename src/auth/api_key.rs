@@ -0,0 +1,136 @@
+// API keys for non-interactive clients - key format, scope mapping and lookup
+
+use super::AuthenticatedUser;
+use super::account::{generate_secret_token, hash_secret_token};
+use crate::db::DbPool;
+use crate::error::ApiError;
+use crate::models::{ApiScope, Role};
+use actix_web::http::Method;
+use mysql::prelude::*;
+
+pub const API_KEY_HEADER: &str = "X-Api-Key";
+
+// Makes keys recognisable in logs and secret scanners
+const KEY_MARKER: &str = "pmk_";
+const KEY_PREFIX_LEN: usize = 12;
+
+pub fn generate_api_key() -> String {
+    format!("{}{}", KEY_MARKER, generate_secret_token())
+}
+
+/// Part of the key that is stored in plain text and shown in listings
+pub fn key_prefix(key: &str) -> String {
+    key.chars().take(KEY_PREFIX_LEN).collect()
+}
+
+pub fn scopes_to_column(scopes: &[ApiScope]) -> String {
+    scopes
+        .iter()
+        .map(ApiScope::as_str)
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+// Scopes that no longer exist are dropped instead of failing the request
+pub fn scopes_from_column(column: &str) -> Vec<ApiScope> {
+    column
+        .split(',')
+        .filter_map(|scope| scope.trim().parse().ok())
+        .collect()
+}
+
+fn is_under(path: &str, prefix: &str) -> bool {
+    path == prefix
+        || path
+            .strip_prefix(prefix)
+            .is_some_and(|rest| rest.starts_with('/'))
+}
+
+/// Scope an API key needs for a request, `None` for endpoints API keys may never use
+pub fn required_scope(method: &Method, path: &str) -> Option<ApiScope> {
+    let read = matches!(*method, Method::GET | Method::HEAD);
+
+    // Personal data exports and anonymization are for people, never for batch clients
+    if is_under(path, "/api/employees")
+        && (path.ends_with("/personal-data") || path.ends_with("/anonymize"))
+    {
+        return None;
+    }
+
+    // /api/departments/{id}/employees lists employees, not departments
+    let employees = is_under(path, "/api/employees")
+        || is_under(path, "/api/org-chart")
+        || (is_under(path, "/api/departments") && path.ends_with("/employees"));
+
+    let scope = if employees {
+        if read {
+            ApiScope::EmployeesRead
+        } else {
+            ApiScope::EmployeesWrite
+        }
+    } else if is_under(path, "/api/departments") {
+        if read {
+            ApiScope::DepartmentsRead
+        } else {
+            ApiScope::DepartmentsWrite
+        }
+    } else if is_under(path, "/api/salary-grades") {
+        if read {
+            ApiScope::SalaryGradesRead
+        } else {
+            ApiScope::SalaryGradesWrite
+        }
+    } else {
+        return None;
+    };
+    Some(scope)
+}
+
+/// Identity handed to the handlers for an API key. The key acts with admin rights, the
+/// middleware has already limited it to the resources its scopes cover.
+pub fn service_user(key_id: &str) -> AuthenticatedUser {
+    AuthenticatedUser {
+        employee_id: format!("api-key:{}", key_id),
+        role: Role::Admin,
+        department_id: None,
+    }
+}
+
+/// Looks up an active key, checks its scope for the request and records the use
+pub fn authenticate_api_key(
+    pool: &DbPool,
+    key: &str,
+    method: &Method,
+    path: &str,
+) -> Result<AuthenticatedUser, ApiError> {
+    let mut conn = pool.get_conn()?;
+
+    let found: Option<(String, String)> = conn.exec_first(
+        "SELECT id, scopes FROM api_keys WHERE key_hash = ? AND revoked_at IS NULL",
+        (hash_secret_token(key),),
+    )?;
+    let Some((id, scopes)) = found else {
+        return Err(ApiError::Unauthorized(
+            "Invalid or revoked API key".to_string(),
+        ));
+    };
+
+    let Some(required) = required_scope(method, path) else {
+        return Err(ApiError::Forbidden(
+            "API keys cannot access this endpoint".to_string(),
+        ));
+    };
+    if !scopes_from_column(&scopes).contains(&required) {
+        return Err(ApiError::Forbidden(format!(
+            "API key lacks the {} scope",
+            required
+        )));
+    }
+
+    conn.exec_drop(
+        "UPDATE api_keys SET last_used_at = NOW() WHERE id = ?",
+        (&id,),
+    )?;
+
+    Ok(service_user(&id))
+}
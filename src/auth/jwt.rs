@@ -0,0 +1,94 @@
+// Signing and validating HS256 access and refresh tokens
+
+use super::AuthenticatedUser;
+use crate::error::ApiError;
+use crate::models::Role;
+use chrono::Utc;
+use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use serde::{Deserialize, Serialize};
+
+pub const DEFAULT_ACCESS_TTL_SECONDS: i64 = 15 * 60;
+pub const DEFAULT_REFRESH_TTL_SECONDS: i64 = 7 * 24 * 60 * 60;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum TokenType {
+    Access,
+    Refresh,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Claims {
+    /// Employee UUID
+    pub sub: String,
+    pub role: Role,
+    /// Department at the time the token was issued, used for department scoped permissions
+    pub department_id: Option<String>,
+    #[serde(rename = "typ")]
+    pub token_type: TokenType,
+    pub iat: i64,
+    pub exp: i64,
+}
+
+#[derive(Clone)]
+pub struct JwtConfig {
+    encoding_key: EncodingKey,
+    decoding_key: DecodingKey,
+    pub access_ttl_seconds: i64,
+    pub refresh_ttl_seconds: i64,
+}
+
+impl JwtConfig {
+    pub fn new(secret: &str) -> Self {
+        JwtConfig {
+            encoding_key: EncodingKey::from_secret(secret.as_bytes()),
+            decoding_key: DecodingKey::from_secret(secret.as_bytes()),
+            access_ttl_seconds: DEFAULT_ACCESS_TTL_SECONDS,
+            refresh_ttl_seconds: DEFAULT_REFRESH_TTL_SECONDS,
+        }
+    }
+
+    pub fn with_ttls(mut self, access_ttl_seconds: i64, refresh_ttl_seconds: i64) -> Self {
+        self.access_ttl_seconds = access_ttl_seconds;
+        self.refresh_ttl_seconds = refresh_ttl_seconds;
+        self
+    }
+
+    pub fn issue(
+        &self,
+        user: &AuthenticatedUser,
+        token_type: TokenType,
+    ) -> Result<String, ApiError> {
+        let now = Utc::now().timestamp();
+        let ttl = match token_type {
+            TokenType::Access => self.access_ttl_seconds,
+            TokenType::Refresh => self.refresh_ttl_seconds,
+        };
+        let claims = Claims {
+            sub: user.employee_id.clone(),
+            role: user.role,
+            department_id: user.department_id.clone(),
+            token_type,
+            iat: now,
+            exp: now + ttl,
+        };
+
+        jsonwebtoken::encode(&Header::new(Algorithm::HS256), &claims, &self.encoding_key)
+            .map_err(|e| ApiError::Internal(format!("token signing failed: {}", e)))
+    }
+
+    /// Checks signature, expiry and that the token is of the expected type
+    pub fn verify(&self, token: &str, expected: TokenType) -> Result<Claims, ApiError> {
+        let mut validation = Validation::new(Algorithm::HS256);
+        validation.leeway = 0;
+
+        let claims = jsonwebtoken::decode::<Claims>(token, &self.decoding_key, &validation)
+            .map_err(|_| ApiError::Unauthorized("Invalid or expired token".to_string()))?
+            .claims;
+
+        if claims.token_type != expected {
+            return Err(ApiError::Unauthorized("Wrong token type".to_string()));
+        }
+        Ok(claims)
+    }
+}
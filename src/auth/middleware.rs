@@ -0,0 +1,68 @@
+// Middleware validating the bearer token or API key of every /api/* request
+
+use super::api_key::{API_KEY_HEADER, authenticate_api_key};
+use super::{AuthenticatedUser, JwtConfig, TokenType};
+use crate::db::{DbPool, blocking};
+use crate::error::ApiError;
+use actix_web::body::{BoxBody, EitherBody, MessageBody};
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::http::header;
+use actix_web::middleware::Next;
+use actix_web::{HttpMessage, web};
+
+// Routes under /api that must stay reachable without a token
+const PUBLIC_PATHS: &[&str] = &[
+    "/api/auth/login",
+    "/api/auth/refresh",
+    "/api/auth/reset-password",
+];
+
+pub async fn require_auth(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<EitherBody<impl MessageBody, BoxBody>>, actix_web::Error> {
+    let path = req.path();
+    if !path.starts_with("/api/") || PUBLIC_PATHS.contains(&path) {
+        return next
+            .call(req)
+            .await
+            .map(ServiceResponse::map_into_left_body);
+    }
+
+    match authenticate(&req).await {
+        Ok(user) => {
+            req.extensions_mut().insert(user);
+            next.call(req)
+                .await
+                .map(ServiceResponse::map_into_left_body)
+        }
+        // Answer here instead of failing the service so outer middleware still sees the ApiError
+        Err(err) => Ok(req.error_response(err).map_into_right_body()),
+    }
+}
+
+async fn authenticate(req: &ServiceRequest) -> Result<AuthenticatedUser, ApiError> {
+    if let Some(key) = req.headers().get(API_KEY_HEADER) {
+        let key = key
+            .to_str()
+            .map_err(|_| ApiError::Unauthorized("Invalid or revoked API key".to_string()))?
+            .to_string();
+        let pool = DbPool::from_app_data(req.app_data())?;
+        let (method, path) = (req.method().clone(), req.path().to_string());
+        return blocking(move || authenticate_api_key(&pool, &key, &method, &path)).await;
+    }
+
+    let jwt = req
+        .app_data::<web::Data<JwtConfig>>()
+        .ok_or_else(|| ApiError::Internal("JwtConfig is not registered".to_string()))?;
+
+    let token = req
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .ok_or_else(|| ApiError::Unauthorized("Missing bearer token or API key".to_string()))?;
+
+    jwt.verify(token, TokenType::Access)
+        .map(AuthenticatedUser::from)
+}
@@ -0,0 +1,52 @@
+// Authentication module - password hashing, account lockout, JWT handling, API keys and the
+// middleware protecting /api/*
+
+pub mod account;
+pub mod api_key;
+pub mod jwt;
+pub mod middleware;
+pub mod password;
+pub mod policy;
+
+use crate::error::ApiError;
+use crate::models::Role;
+use actix_web::dev::Payload;
+use actix_web::{FromRequest, HttpMessage, HttpRequest};
+use std::future::{Ready, ready};
+
+pub use account::AccountPolicy;
+pub use jwt::{Claims, JwtConfig, TokenType};
+pub use middleware::require_auth;
+pub use policy::{AdminOnly, DepartmentManager, Policy, RequireRole};
+
+/// Identity of the caller, put into the request extensions by the auth middleware
+#[derive(Debug, Clone, PartialEq)]
+pub struct AuthenticatedUser {
+    pub employee_id: String,
+    pub role: Role,
+    pub department_id: Option<String>,
+}
+
+impl From<Claims> for AuthenticatedUser {
+    fn from(claims: Claims) -> Self {
+        AuthenticatedUser {
+            employee_id: claims.sub,
+            role: claims.role,
+            department_id: claims.department_id,
+        }
+    }
+}
+
+impl FromRequest for AuthenticatedUser {
+    type Error = ApiError;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        ready(
+            req.extensions()
+                .get::<AuthenticatedUser>()
+                .cloned()
+                .ok_or_else(|| ApiError::Unauthorized("Authentication required".to_string())),
+        )
+    }
+}
@@ -0,0 +1,154 @@
+// Role based authorization - policy extractor and department scope checks
+
+use super::AuthenticatedUser;
+use crate::error::ApiError;
+use crate::models::{Employee, Role};
+use actix_web::dev::Payload;
+use actix_web::{FromRequest, HttpRequest};
+use std::future::{Ready, ready};
+use std::marker::PhantomData;
+use std::ops::Deref;
+
+/// Set of roles allowed to call a handler
+pub trait Policy {
+    const ROLES: &'static [Role];
+    /// Reason returned with the 403 when the caller's role is not in `ROLES`
+    const REASON: &'static str;
+}
+
+/// Only admins, e.g. department and salary grade management
+pub struct AdminOnly;
+
+impl Policy for AdminOnly {
+    const ROLES: &'static [Role] = &[Role::Admin];
+    const REASON: &'static str = "Only admins may perform this action";
+}
+
+/// Admins plus department heads and their deputies, who are further limited to their own department
+pub struct DepartmentManager;
+
+impl Policy for DepartmentManager {
+    const ROLES: &'static [Role] = &[Role::Admin, Role::DepartmentHead, Role::DeputyHead];
+    const REASON: &'static str =
+        "Only admins, department heads and deputy heads may perform this action";
+}
+
+/// Extractor that only lets callers through whose role is allowed by `P`
+///
+/// ```ignore
+/// pub async fn delete_department(caller: RequireRole<AdminOnly>, ...) -> Result<HttpResponse, ApiError>
+/// ```
+pub struct RequireRole<P: Policy> {
+    pub user: AuthenticatedUser,
+    policy: PhantomData<P>,
+}
+
+impl<P: Policy> Deref for RequireRole<P> {
+    type Target = AuthenticatedUser;
+
+    fn deref(&self) -> &Self::Target {
+        &self.user
+    }
+}
+
+impl<P: Policy> FromRequest for RequireRole<P> {
+    type Error = ApiError;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let result = AuthenticatedUser::from_request(req, payload)
+            .into_inner()
+            .and_then(|user| {
+                if P::ROLES.contains(&user.role) {
+                    Ok(RequireRole {
+                        user,
+                        policy: PhantomData,
+                    })
+                } else {
+                    Err(ApiError::Forbidden(P::REASON.to_string()))
+                }
+            });
+        ready(result)
+    }
+}
+
+// Higher roles may change lower ones, never the other way round
+fn rank(role: Role) -> u8 {
+    match role {
+        Role::Admin => 3,
+        Role::DepartmentHead => 2,
+        Role::DeputyHead => 1,
+        Role::Employee => 0,
+    }
+}
+
+impl AuthenticatedUser {
+    pub fn is_admin(&self) -> bool {
+        self.role == Role::Admin
+    }
+
+    /// Admins may manage every department, heads and deputies only the one they belong to
+    pub fn ensure_department(&self, department_id: Option<&str>) -> Result<(), ApiError> {
+        if self.is_admin() {
+            return Ok(());
+        }
+
+        let manages = matches!(self.role, Role::DepartmentHead | Role::DeputyHead)
+            && self.department_id.is_some()
+            && self.department_id.as_deref() == department_id;
+
+        if manages {
+            Ok(())
+        } else {
+            Err(ApiError::Forbidden(
+                "You can only manage employees of your own department".to_string(),
+            ))
+        }
+    }
+
+    /// Plain employees may only look at their own record
+    pub fn ensure_can_read_employee(&self, employee_id: &str) -> Result<(), ApiError> {
+        if self.role == Role::Employee && self.employee_id != employee_id {
+            return Err(ApiError::Forbidden(
+                "Employees can only view their own record".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Heads and deputies may only change employees below their own role, so a deputy can't
+    /// touch the head of their department. Admins may change anyone.
+    pub fn ensure_outranks(&self, role: Role) -> Result<(), ApiError> {
+        if !self.is_admin() && rank(role) >= rank(self.role) {
+            return Err(ApiError::Forbidden(format!(
+                "You can only change employees below your own role, not a {}",
+                role
+            )));
+        }
+        Ok(())
+    }
+
+    /// Heads and deputies may hand out the Employee and DeputyHead roles, everything above is up to admins
+    pub fn ensure_can_grant(&self, role: Role) -> Result<(), ApiError> {
+        if !self.is_admin() && matches!(role, Role::Admin | Role::DepartmentHead) {
+            return Err(ApiError::Forbidden(format!(
+                "Only admins can grant the {} role",
+                role
+            )));
+        }
+        Ok(())
+    }
+
+    /// Heads and deputies may only change employees of their own department below their own
+    /// role. The repositories check this on the locked row, a concurrent move or promotion can't
+    /// slip in between.
+    pub fn ensure_can_change(&self, employee: &Employee) -> Result<(), ApiError> {
+        self.ensure_outranks(employee.role)?;
+        self.ensure_department(employee.department_id.as_deref())
+    }
+
+    /// Heads and deputies may only put employees under a manager of their own department
+    pub fn ensure_can_assign_manager(&self, manager: &Employee) -> Result<(), ApiError> {
+        self.ensure_department(manager.department_id.as_deref())
+    }
+}
@@ -1,10 +1,108 @@
 // Handeling DB Connection
 
+use crate::error::ApiError;
+use actix_web::{FromRequest, HttpRequest, dev::Payload, web};
 use mysql::*;
+use std::future::{Ready, ready};
+use std::time::Duration;
 
-pub type DbPool = Pool;
+/// Pool size and timeouts, read from the `DB_*` env vars in main.rs
+#[derive(Debug, Clone)]
+pub struct PoolConfig {
+    pub min_connections: usize,
+    pub max_connections: usize,
+    /// How long a request waits for a free connection before giving up with 503
+    pub acquire_timeout: Duration,
+    /// Upper bound for a single statement, `None` leaves it to the server
+    pub query_timeout: Option<Duration>,
+}
+
+impl Default for PoolConfig {
+    fn default() -> Self {
+        PoolConfig {
+            min_connections: 10,
+            max_connections: 100,
+            acquire_timeout: Duration::from_secs(5),
+            query_timeout: Some(Duration::from_secs(30)),
+        }
+    }
+}
+
+/// Connection pool that never waits longer than the configured acquire timeout
+#[derive(Clone, Debug)]
+pub struct DbPool {
+    pool: Pool,
+    acquire_timeout: Duration,
+}
+
+impl DbPool {
+    pub fn get_conn(&self) -> Result<PooledConn, mysql::Error> {
+        self.pool.try_get_conn(self.acquire_timeout)
+    }
+
+    /// The pool registered as app data. With in-memory storage there is none, accounts and API
+    /// keys always live in MySQL, so their routes answer 503 instead of failing with a plain 500.
+    pub fn from_app_data(data: Option<&web::Data<DbPool>>) -> Result<DbPool, ApiError> {
+        data.map(|pool| pool.get_ref().clone()).ok_or_else(|| {
+            ApiError::Unavailable(
+                "User accounts and API keys need MySQL storage, the server runs with STORAGE=memory"
+                    .to_string(),
+            )
+        })
+    }
+}
+
+impl FromRequest for DbPool {
+    type Error = ApiError;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        ready(DbPool::from_app_data(req.app_data()))
+    }
+}
 
 pub fn create_pool(database_url: &str) -> Result<DbPool, mysql::Error> {
-    let opts = Opts::from_url(database_url)?;
-    Pool::new(opts)
+    create_pool_with(database_url, &PoolConfig::default())
+}
+
+pub fn create_pool_with(database_url: &str, config: &PoolConfig) -> Result<DbPool, mysql::Error> {
+    let constraints = PoolConstraints::new(config.min_connections, config.max_connections).ok_or(
+        UrlError::InvalidPoolConstraints {
+            min: config.min_connections,
+            max: config.max_connections,
+        },
+    )?;
+
+    // NOW() and the TIMESTAMP columns in UTC, whatever the server's zone is. The dates are
+    // formatted with a `Z` and the retention cutoff is computed from `Utc::now()`.
+    let mut init = vec!["SET SESSION time_zone = '+00:00'".to_string()];
+    if let Some(timeout) = config.query_timeout {
+        // Lets the server abort long SELECTs itself instead of only dropping the socket
+        init.push(format!(
+            "SET SESSION max_execution_time = {}",
+            timeout.as_millis()
+        ));
+    }
+    let builder = OptsBuilder::from_opts(Opts::from_url(database_url)?)
+        .pool_opts(PoolOpts::default().with_constraints(constraints))
+        .read_timeout(config.query_timeout)
+        .write_timeout(config.query_timeout)
+        .init(init);
+
+    Ok(DbPool {
+        pool: Pool::new(builder)?,
+        acquire_timeout: config.acquire_timeout,
+    })
+}
+
+/// Runs synchronous storage work on actix's blocking thread pool, so a slow query only
+/// occupies a blocking thread and the worker keeps answering other requests
+pub async fn blocking<F, T>(work: F) -> Result<T, ApiError>
+where
+    F: FnOnce() -> Result<T, ApiError> + Send + 'static,
+    T: Send + 'static,
+{
+    web::block(work)
+        .await
+        .map_err(|err| ApiError::Internal(format!("blocking task failed: {}", err)))?
 }
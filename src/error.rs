@@ -0,0 +1,369 @@
+// Crate wide error type returned by all handlers, turns failures into RFC 7807 problem+json responses
+
+use crate::models::{FieldError, ProblemDetails};
+use actix_web::body::{BoxBody, EitherBody, MessageBody};
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::error::{JsonPayloadError, PathError, QueryPayloadError};
+use actix_web::http::{StatusCode, header};
+use actix_web::middleware::Next;
+use actix_web::{HttpRequest, HttpResponse, ResponseError};
+use std::fmt;
+
+pub const PROBLEM_JSON: &str = "application/problem+json";
+
+// MySQL server error codes we translate into client errors
+const ER_DUP_ENTRY: u16 = 1062;
+const ER_ROW_IS_REFERENCED_2: u16 = 1451;
+const ER_NO_REFERENCED_ROW_2: u16 = 1452;
+const ER_TRUNCATED_WRONG_VALUE: u16 = 1292;
+const WARN_DATA_TRUNCATED: u16 = 1265;
+const ER_QUERY_TIMEOUT: u16 = 3024;
+const ER_LOCK_WAIT_TIMEOUT: u16 = 1205;
+const ER_LOCK_DEADLOCK: u16 = 1213;
+
+// Unique keys from the migrations and the message a client gets when one of them is violated
+const UNIQUE_KEY_MESSAGES: &[(&str, &str)] = &[
+    (
+        "employees.email",
+        "An employee with this email already exists",
+    ),
+    (
+        "departments.name",
+        "A department with this name already exists",
+    ),
+    (
+        "salary_grades.code",
+        "A salary grade with this code already exists",
+    ),
+    ("users.email", "A user with this email already exists"),
+    (
+        "users.employee_id",
+        "This employee already has a user account",
+    ),
+];
+
+// Foreign keys from the migrations and the message a client gets when one of them points nowhere
+const FOREIGN_KEY_MESSAGES: &[(&str, &str)] = &[
+    (
+        "fk_emp_department",
+        "department_id does not reference an existing department",
+    ),
+    (
+        "fk_emp_salary",
+        "salary_grade_id does not reference an existing salary grade",
+    ),
+    (
+        "fk_emp_manager",
+        "manager_id does not reference an existing employee",
+    ),
+    (
+        "fk_dept_head",
+        "head_id does not reference an existing employee",
+    ),
+    (
+        "fk_dept_parent",
+        "parent_id does not reference an existing department",
+    ),
+    (
+        "fk_user_employee",
+        "employee_id does not reference an existing employee",
+    ),
+];
+
+const DUPLICATE_FALLBACK: &str = "Resource already exists";
+const MISSING_REFERENCE_FALLBACK: &str = "Referenced resource does not exist";
+
+#[derive(Debug)]
+pub enum ApiError {
+    BadRequest(String),
+    Unauthorized(String),
+    Forbidden(String),
+    NotFound(String),
+    Conflict(String),
+    Validation(Vec<FieldError>),
+    Unprocessable(String),
+    /// Pool exhausted or query timed out, the client may retry later
+    Unavailable(String),
+    Database(mysql::Error),
+    Internal(String),
+}
+
+impl ApiError {
+    /// Machine readable error code, stable across releases
+    pub fn code(&self) -> &'static str {
+        match self {
+            ApiError::BadRequest(_) => "bad_request",
+            ApiError::Unauthorized(_) => "unauthorized",
+            ApiError::Forbidden(_) => "forbidden",
+            ApiError::NotFound(_) => "not_found",
+            ApiError::Conflict(_) => "conflict",
+            ApiError::Validation(_) => "validation_failed",
+            ApiError::Unprocessable(_) => "unprocessable_entity",
+            ApiError::Unavailable(_) => "service_unavailable",
+            ApiError::Database(_) => "database_error",
+            ApiError::Internal(_) => "internal_error",
+        }
+    }
+
+    /// Short human readable summary of the problem type
+    pub fn title(&self) -> &'static str {
+        match self {
+            ApiError::BadRequest(_) => "Bad Request",
+            ApiError::Unauthorized(_) => "Unauthorized",
+            ApiError::Forbidden(_) => "Forbidden",
+            ApiError::NotFound(_) => "Not Found",
+            ApiError::Conflict(_) => "Conflict",
+            ApiError::Validation(_) => "Validation Failed",
+            ApiError::Unprocessable(_) => "Unprocessable Entity",
+            ApiError::Unavailable(_) => "Service Unavailable",
+            ApiError::Database(_) => "Database Error",
+            ApiError::Internal(_) => "Internal Server Error",
+        }
+    }
+
+    /// Message shown to the client, never contains raw database errors
+    pub fn message(&self) -> String {
+        match self {
+            ApiError::BadRequest(msg)
+            | ApiError::Unauthorized(msg)
+            | ApiError::Forbidden(msg)
+            | ApiError::NotFound(msg)
+            | ApiError::Conflict(msg)
+            | ApiError::Unprocessable(msg)
+            | ApiError::Unavailable(msg) => msg.clone(),
+            ApiError::Validation(errors) => match errors.as_slice() {
+                [single] => format!("{}: {}", single.field, single.message),
+                _ => format!("{} fields failed validation", errors.len()),
+            },
+            ApiError::Database(_) => "A database error occurred".to_string(),
+            ApiError::Internal(_) => "An internal error occurred".to_string(),
+        }
+    }
+
+    /// Problem details body for this error, `instance` is the request path if known
+    pub fn problem_details(&self, instance: Option<String>) -> ProblemDetails {
+        ProblemDetails {
+            problem_type: format!("/problems/{}", self.code().replace('_', "-")),
+            title: self.title().to_string(),
+            status: self.status_code().as_u16(),
+            detail: self.message(),
+            instance,
+            code: self.code().to_string(),
+            errors: match self {
+                ApiError::Validation(errors) => Some(errors.clone()),
+                _ => None,
+            },
+        }
+    }
+
+    pub fn problem_response(&self, instance: Option<String>) -> HttpResponse {
+        let mut builder = HttpResponse::build(self.status_code());
+        builder.content_type(PROBLEM_JSON);
+        if let ApiError::Unauthorized(_) = self {
+            builder.insert_header((header::WWW_AUTHENTICATE, "Bearer"));
+        }
+        builder.json(self.problem_details(instance))
+    }
+
+    /// Conflict for a violated unique key, named like in `UNIQUE_KEY_MESSAGES` (`table.column`)
+    pub fn duplicate_key(key: &str) -> Self {
+        ApiError::Conflict(
+            exact_key_message(key, UNIQUE_KEY_MESSAGES)
+                .unwrap_or(DUPLICATE_FALLBACK)
+                .to_string(),
+        )
+    }
+
+    /// Unprocessable for a foreign key that points nowhere, named like in `FOREIGN_KEY_MESSAGES`
+    pub fn missing_reference(constraint: &str) -> Self {
+        ApiError::Unprocessable(
+            exact_key_message(constraint, FOREIGN_KEY_MESSAGES)
+                .unwrap_or(MISSING_REFERENCE_FALLBACK)
+                .to_string(),
+        )
+    }
+
+    /// Shorthand for a validation error on a single field
+    pub fn invalid_field(field: &str, message: &str) -> Self {
+        ApiError::Validation(vec![FieldError {
+            field: field.to_string(),
+            message: message.to_string(),
+        }])
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ApiError::Database(e) => write!(f, "database error: {}", e),
+            ApiError::Internal(msg) => write!(f, "internal error: {}", msg),
+            other => write!(f, "{}: {}", other.code(), other.message()),
+        }
+    }
+}
+
+impl std::error::Error for ApiError {}
+
+impl ResponseError for ApiError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            ApiError::BadRequest(_) => StatusCode::BAD_REQUEST,
+            ApiError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            ApiError::Forbidden(_) => StatusCode::FORBIDDEN,
+            ApiError::NotFound(_) => StatusCode::NOT_FOUND,
+            ApiError::Conflict(_) => StatusCode::CONFLICT,
+            ApiError::Validation(_) | ApiError::Unprocessable(_) => {
+                StatusCode::UNPROCESSABLE_ENTITY
+            }
+            ApiError::Unavailable(_) => StatusCode::SERVICE_UNAVAILABLE,
+            ApiError::Database(_) | ApiError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        // Details of server side failures only go to the log
+        if matches!(
+            self,
+            ApiError::Unavailable(_) | ApiError::Database(_) | ApiError::Internal(_)
+        ) {
+            eprintln!("{}", self);
+        }
+
+        self.problem_response(None)
+    }
+}
+
+impl From<mysql::Error> for ApiError {
+    fn from(err: mysql::Error) -> Self {
+        if is_timeout(&err) {
+            return ApiError::Unavailable(
+                "The database is busy, please try again later".to_string(),
+            );
+        }
+        let mysql::Error::MySqlError(ref server_err) = err else {
+            return ApiError::Database(err);
+        };
+
+        match server_err.code {
+            ER_DUP_ENTRY => ApiError::Conflict(
+                lookup_key_message(&server_err.message, UNIQUE_KEY_MESSAGES)
+                    .unwrap_or(DUPLICATE_FALLBACK)
+                    .to_string(),
+            ),
+            ER_NO_REFERENCED_ROW_2 => ApiError::Unprocessable(
+                lookup_key_message(&server_err.message, FOREIGN_KEY_MESSAGES)
+                    .unwrap_or(MISSING_REFERENCE_FALLBACK)
+                    .to_string(),
+            ),
+            ER_ROW_IS_REFERENCED_2 => {
+                ApiError::Unprocessable("Resource is still referenced by other records".to_string())
+            }
+            // Two transactions waited on each others row locks, InnoDB rolled this one back
+            ER_LOCK_DEADLOCK | ER_LOCK_WAIT_TIMEOUT => {
+                ApiError::Conflict("The record was changed concurrently, please retry".to_string())
+            }
+            ER_TRUNCATED_WRONG_VALUE | WARN_DATA_TRUNCATED => ApiError::invalid_field(
+                column_name(&server_err.message).unwrap_or("unknown"),
+                "Invalid value",
+            ),
+            _ => ApiError::Database(err),
+        }
+    }
+}
+
+// Waiting for a pooled connection, the socket read/write timeout and MySQL's max_execution_time
+fn is_timeout(err: &mysql::Error) -> bool {
+    match err {
+        mysql::Error::DriverError(mysql::DriverError::Timeout) => true,
+        mysql::Error::IoError(io) => matches!(
+            io.kind(),
+            std::io::ErrorKind::TimedOut | std::io::ErrorKind::WouldBlock
+        ),
+        mysql::Error::MySqlError(server_err) => server_err.code == ER_QUERY_TIMEOUT,
+        _ => false,
+    }
+}
+
+impl From<validator::ValidationErrors> for ApiError {
+    fn from(errors: validator::ValidationErrors) -> Self {
+        let mut field_errors: Vec<FieldError> = errors
+            .field_errors()
+            .into_iter()
+            .flat_map(|(field, errors)| {
+                errors.iter().map(move |err| FieldError {
+                    field: field.to_string(),
+                    message: err
+                        .message
+                        .as_ref()
+                        .map(|msg| msg.to_string())
+                        .unwrap_or_else(|| format!("failed {} check", err.code)),
+                })
+            })
+            .collect();
+
+        // HashMap order is random, keep responses deterministic
+        field_errors.sort_by(|a, b| a.field.cmp(&b.field));
+        ApiError::Validation(field_errors)
+    }
+}
+
+// Finds the first known key or constraint name mentioned in a MySQL error message
+fn lookup_key_message(message: &str, table: &[(&str, &'static str)]) -> Option<&'static str> {
+    table
+        .iter()
+        .find(|(key, _)| {
+            message.contains(&format!("'{}'", key)) || message.contains(&format!("`{}`", key))
+        })
+        .map(|(_, msg)| *msg)
+}
+
+fn exact_key_message(key: &str, table: &[(&str, &'static str)]) -> Option<&'static str> {
+    table
+        .iter()
+        .find(|(known, _)| *known == key)
+        .map(|(_, msg)| *msg)
+}
+
+// Extracts the column from messages like "Data truncated for column 'role' at row 1"
+fn column_name(message: &str) -> Option<&str> {
+    let start = message.find("column '")? + "column '".len();
+    let end = message[start..].find('\'')?;
+    Some(&message[start..start + end])
+}
+
+/// Middleware that fills `instance` of problem responses with the path of the failed request
+pub async fn problem_instance(
+    req: ServiceRequest,
+    next: Next<impl MessageBody + 'static>,
+) -> Result<ServiceResponse<EitherBody<impl MessageBody, BoxBody>>, actix_web::Error> {
+    let res = next.call(req).await?;
+
+    let problem = res
+        .response()
+        .error()
+        .and_then(|err| err.as_error::<ApiError>())
+        .map(|err| err.problem_response(Some(res.request().path().to_string())));
+
+    match problem {
+        Some(problem) => Ok(res.into_response(problem).map_into_right_body()),
+        None => Ok(res.map_into_left_body()),
+    }
+}
+
+// Extractor error handlers so malformed requests get problem+json bodies too
+pub fn json_error_handler(err: JsonPayloadError, _req: &HttpRequest) -> actix_web::Error {
+    ApiError::BadRequest(format!("Invalid JSON body: {}", err)).into()
+}
+
+pub fn query_error_handler(err: QueryPayloadError, _req: &HttpRequest) -> actix_web::Error {
+    ApiError::BadRequest(format!("Invalid query string: {}", err)).into()
+}
+
+pub fn path_error_handler(err: PathError, _req: &HttpRequest) -> actix_web::Error {
+    ApiError::BadRequest(format!("Invalid path parameter: {}", err)).into()
+}
+
+/// Fallback for requests that match no route
+pub async fn route_not_found() -> Result<HttpResponse, ApiError> {
+    Err(ApiError::NotFound("No route matches this path".to_string()))
+}
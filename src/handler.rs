@@ -1,9 +1,16 @@
 // Api Routes with their respective function like post or get, and the logic behind it
 
-use crate::db::DbPool;
-use crate::models::{CreateUserRequest, HealthResponse, User};
+use crate::auth::account::{generate_secret_token, hash_secret_token};
+use crate::auth::password::{hash_password, verify_password};
+use crate::auth::{AccountPolicy, AdminOnly, AuthenticatedUser, RequireRole};
+use crate::db::{DbPool, blocking};
+use crate::error::ApiError;
+use crate::models::{
+    ChangePasswordRequest, CreateUserRequest, HealthResponse, ResetTokenResponse, User,
+};
 use actix_web::{HttpResponse, Responder, get, post, web};
 use mysql::prelude::*;
+use validator::Validate;
 
 // Health check endpoint
 #[utoipa::path(
@@ -12,6 +19,7 @@ use mysql::prelude::*;
     responses(
         (status = 200, description = "Server health status", body = HealthResponse)
     ),
+    security(()),
     tag = "Health"
 )]
 #[get("/health")]
@@ -23,38 +31,45 @@ pub async fn health() -> impl Responder {
     HttpResponse::Ok().json(response)
 }
 
+// Columns of the public user representation, locked_until is only reported while the lock lasts
+pub(crate) const USER_COLUMNS: &str = "id, name, email, employee_id, failed_login_attempts, \
+    IF(locked_until > NOW(), DATE_FORMAT(locked_until, '%Y-%m-%dT%H:%i:%sZ'), NULL) AS locked_until";
+
+pub(crate) fn user_from_row(mut row: mysql::Row) -> User {
+    User {
+        id: row.take("id").unwrap(),
+        name: row.take("name").unwrap(),
+        email: row.take("email").unwrap(),
+        employee_id: row.take("employee_id").unwrap(),
+        failed_login_attempts: row.take("failed_login_attempts").unwrap(),
+        locked_until: row.take("locked_until").unwrap(),
+    }
+}
+
 // Database endpoint - Get all users
 #[utoipa::path(
     get,
     path = "/api/users",
     responses(
         (status = 200, description = "List of all users", body = Vec<User>),
-        (status = 500, description = "Internal server error")
+        (status = 403, description = "Caller is not an admin", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Internal server error", body = ProblemDetails, content_type = "application/problem+json")
     ),
     tag = "Users"
 )]
 #[get("/api/users")]
-pub async fn get_users(pool: web::Data<DbPool>) -> impl Responder {
-    let mut conn = match pool.get_conn() {
-        Ok(conn) => conn,
-        Err(e) => {
-            return HttpResponse::InternalServerError().json(serde_json::json!({
-                "error": format!("Database connection error: {}", e)
-            }));
-        }
-    };
+pub async fn get_users(
+    _caller: RequireRole<AdminOnly>,
+    pool: DbPool,
+) -> Result<HttpResponse, ApiError> {
+    let users: Vec<User> = blocking(move || {
+        let mut conn = pool.get_conn()?;
 
-    let result: Result<Vec<User>, mysql::Error> = conn
-        .query_map("SELECT id, name, email FROM users", |(id, name, email)| {
-            User { id, name, email }
-        });
-
-    match result {
-        Ok(users) => HttpResponse::Ok().json(users),
-        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({
-            "error": format!("Database error: {}", e)
-        })),
-    }
+        let rows: Vec<mysql::Row> = conn.query(format!("SELECT {} FROM users", USER_COLUMNS))?;
+        Ok(rows.into_iter().map(user_from_row).collect())
+    })
+    .await?;
+    Ok(HttpResponse::Ok().json(users))
 }
 
 // Database endpoint - Get user by ID
@@ -66,36 +81,32 @@ pub async fn get_users(pool: web::Data<DbPool>) -> impl Responder {
     ),
     responses(
         (status = 200, description = "User found", body = User),
-        (status = 404, description = "User not found"),
-        (status = 500, description = "Internal server error")
+        (status = 403, description = "Caller is not an admin", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "User not found", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Internal server error", body = ProblemDetails, content_type = "application/problem+json")
     ),
     tag = "Users"
 )]
 #[get("/api/users/{id}")]
-pub async fn get_user_by_id(pool: web::Data<DbPool>, id: web::Path<i32>) -> impl Responder {
-    let mut conn = match pool.get_conn() {
-        Ok(conn) => conn,
-        Err(e) => {
-            return HttpResponse::InternalServerError().json(serde_json::json!({
-                "error": format!("Database connection error: {}", e)
-            }));
-        }
-    };
+pub async fn get_user_by_id(
+    _caller: RequireRole<AdminOnly>,
+    pool: DbPool,
+    id: web::Path<i32>,
+) -> Result<HttpResponse, ApiError> {
+    let id = id.into_inner();
+    let row: Option<mysql::Row> = blocking(move || {
+        let mut conn = pool.get_conn()?;
+
+        Ok(conn.exec_first(
+            format!("SELECT {} FROM users WHERE id = ?", USER_COLUMNS),
+            (id,),
+        )?)
+    })
+    .await?;
 
-    let result: Result<Option<User>, mysql::Error> = conn
-        .exec_first("SELECT id, name, email FROM users WHERE id = ?", (*id,))
-        .map(|row: Option<(i32, String, String)>| {
-            row.map(|(id, name, email)| User { id, name, email })
-        });
-
-    match result {
-        Ok(Some(user)) => HttpResponse::Ok().json(user),
-        Ok(None) => HttpResponse::NotFound().json(serde_json::json!({
-            "error": "User not found"
-        })),
-        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({
-            "error": format!("Database error: {}", e)
-        })),
+    match row {
+        Some(row) => Ok(HttpResponse::Ok().json(user_from_row(row))),
+        None => Err(ApiError::NotFound("User not found".to_string())),
     }
 }
 
@@ -105,41 +116,156 @@ pub async fn get_user_by_id(pool: web::Data<DbPool>, id: web::Path<i32>) -> impl
     path = "/api/users",
     request_body = CreateUserRequest,
     responses(
-        (status = 201, description = "User created successfully"),
-        (status = 500, description = "Internal server error")
+        (status = 201, description = "User created successfully", body = User),
+        (status = 403, description = "Caller is not an admin", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 409, description = "Email or employee already has an account", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 422, description = "Validation failed or unknown employee", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Internal server error", body = ProblemDetails, content_type = "application/problem+json")
     ),
     tag = "Users"
 )]
 #[post("/api/users")]
 pub async fn create_user(
-    pool: web::Data<DbPool>,
+    _caller: RequireRole<AdminOnly>,
+    pool: DbPool,
     user: web::Json<CreateUserRequest>,
-) -> impl Responder {
-    let mut conn = match pool.get_conn() {
-        Ok(conn) => conn,
-        Err(e) => {
-            return HttpResponse::InternalServerError().json(serde_json::json!({
-                "error": format!("Database connection error: {}", e)
-            }));
+) -> Result<HttpResponse, ApiError> {
+    user.validate()?;
+
+    let user = user.into_inner();
+    let (user_id, user) = blocking(move || {
+        // Hashing is deliberately slow, so it runs off the worker as well
+        let password_hash = user.password.as_deref().map(hash_password).transpose()?;
+
+        let mut conn = pool.get_conn()?;
+
+        conn.exec_drop(
+            "INSERT INTO users (name, email, employee_id, password_hash) VALUES (?, ?, ?, ?)",
+            (&user.name, &user.email, &user.employee_id, &password_hash),
+        )?;
+
+        Ok((conn.last_insert_id() as i32, user))
+    })
+    .await?;
+    Ok(HttpResponse::Created().json(User {
+        id: user_id,
+        name: user.name,
+        email: user.email,
+        employee_id: user.employee_id,
+        failed_login_attempts: 0,
+        locked_until: None,
+    }))
+}
+
+// Change own password
+#[utoipa::path(
+    post,
+    path = "/api/users/{id}/password",
+    params(
+        ("id" = i32, Path, description = "User ID")
+    ),
+    request_body = ChangePasswordRequest,
+    responses(
+        (status = 200, description = "Password changed"),
+        (status = 403, description = "Account belongs to someone else or current password is wrong", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "User not found", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 422, description = "Validation failed", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Internal server error", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    tag = "Users"
+)]
+#[post("/api/users/{id}/password")]
+pub async fn change_password(
+    caller: AuthenticatedUser,
+    pool: DbPool,
+    id: web::Path<i32>,
+    req: web::Json<ChangePasswordRequest>,
+) -> Result<HttpResponse, ApiError> {
+    req.validate()?;
+
+    let id = id.into_inner();
+    blocking(move || {
+        let mut conn = pool.get_conn()?;
+
+        let account: Option<(Option<String>, Option<String>)> = conn.exec_first(
+            "SELECT employee_id, password_hash FROM users WHERE id = ?",
+            (id,),
+        )?;
+        let Some((employee_id, password_hash)) = account else {
+            return Err(ApiError::NotFound("User not found".to_string()));
+        };
+
+        // Admins reset other people's passwords through reset tokens instead
+        if employee_id.as_deref() != Some(caller.employee_id.as_str()) {
+            return Err(ApiError::Forbidden(
+                "You can only change your own password".to_string(),
+            ));
         }
-    };
+        let current_ok = password_hash
+            .as_deref()
+            .is_some_and(|hash| verify_password(&req.current_password, hash));
+        if !current_ok {
+            return Err(ApiError::Forbidden(
+                "Current password is incorrect".to_string(),
+            ));
+        }
+
+        conn.exec_drop(
+            "UPDATE users SET password_hash = ? WHERE id = ?",
+            (hash_password(&req.new_password)?, id),
+        )?;
+        Ok(())
+    })
+    .await?;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "message": "Password changed successfully"
+    })))
+}
 
-    let result = conn.exec_drop(
-        "INSERT INTO users (name, email) VALUES (?, ?)",
-        (&user.name, &user.email),
-    );
-
-    match result {
-        Ok(_) => {
-            let user_id = conn.last_insert_id() as i32;
-            HttpResponse::Created().json(serde_json::json!({
-                "id": user_id,
-                "name": user.name,
-                "email": user.email
-            }))
+// Issue a password reset token (admin only)
+#[utoipa::path(
+    post,
+    path = "/api/users/{id}/reset-token",
+    params(
+        ("id" = i32, Path, description = "User ID")
+    ),
+    responses(
+        (status = 201, description = "Reset token issued, replaces any earlier token", body = ResetTokenResponse),
+        (status = 403, description = "Caller is not an admin", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "User not found", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Internal server error", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    tag = "Users"
+)]
+#[post("/api/users/{id}/reset-token")]
+pub async fn create_reset_token(
+    _caller: RequireRole<AdminOnly>,
+    pool: DbPool,
+    policy: web::Data<AccountPolicy>,
+    id: web::Path<i32>,
+) -> Result<HttpResponse, ApiError> {
+    let token = generate_secret_token();
+    let token_hash = hash_secret_token(&token);
+    let ttl_seconds = policy.reset_token_ttl_seconds;
+    let id = id.into_inner();
+    blocking(move || {
+        let mut conn = pool.get_conn()?;
+
+        conn.exec_drop(
+            "UPDATE users SET reset_token_hash = ?, reset_token_expires_at = NOW() + INTERVAL ? SECOND WHERE id = ?",
+            (token_hash, ttl_seconds, id),
+        )?;
+
+        if conn.affected_rows() == 0 {
+            return Err(ApiError::NotFound("User not found".to_string()));
         }
-        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({
-            "error": format!("Database error: {}", e)
-        })),
-    }
+        Ok(())
+    })
+    .await?;
+
+    Ok(HttpResponse::Created().json(ResetTokenResponse {
+        reset_token: token,
+        expires_in: policy.reset_token_ttl_seconds,
+    }))
 }
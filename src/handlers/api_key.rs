@@ -0,0 +1,164 @@
+// API key management handlers (admin only)
+
+use crate::auth::account::hash_secret_token;
+use crate::auth::api_key::{generate_api_key, key_prefix, scopes_from_column, scopes_to_column};
+use crate::auth::{AdminOnly, RequireRole};
+use crate::db::{DbPool, blocking};
+use crate::error::ApiError;
+use crate::models::*;
+use actix_web::{HttpResponse, delete, get, post, web};
+use mysql::prelude::*;
+use uuid::Uuid;
+use validator::Validate;
+
+pub(crate) const API_KEY_COLUMNS: &str = "id, name, key_prefix, scopes, created_by, \
+    DATE_FORMAT(created_at, '%Y-%m-%dT%H:%i:%sZ') AS created_at, \
+    DATE_FORMAT(last_used_at, '%Y-%m-%dT%H:%i:%sZ') AS last_used_at, \
+    DATE_FORMAT(revoked_at, '%Y-%m-%dT%H:%i:%sZ') AS revoked_at";
+
+pub(crate) fn api_key_from_row(mut row: mysql::Row) -> ApiKey {
+    ApiKey {
+        id: row.take("id").unwrap(),
+        name: row.take("name").unwrap(),
+        key_prefix: row.take("key_prefix").unwrap(),
+        scopes: scopes_from_column(&row.take::<String, _>("scopes").unwrap()),
+        created_by: row.take("created_by").unwrap(),
+        created_at: row.take("created_at").unwrap(),
+        last_used_at: row.take("last_used_at").unwrap(),
+        revoked_at: row.take("revoked_at").unwrap(),
+    }
+}
+
+/// List all API keys, revoked ones included
+#[utoipa::path(
+    get,
+    path = "/api/api-keys",
+    responses(
+        (status = 200, description = "List of API keys", body = Vec<ApiKey>),
+        (status = 403, description = "Caller is not an admin", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Internal server error", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    tag = "API Keys"
+)]
+#[get("/api/api-keys")]
+pub async fn get_api_keys(
+    _caller: RequireRole<AdminOnly>,
+    pool: DbPool,
+) -> Result<HttpResponse, ApiError> {
+    let rows: Vec<mysql::Row> = blocking(move || {
+        let mut conn = pool.get_conn()?;
+
+        Ok(conn.query(format!(
+            "SELECT {} FROM api_keys ORDER BY created_at",
+            API_KEY_COLUMNS
+        ))?)
+    })
+    .await?;
+
+    let keys: Vec<ApiKey> = rows.into_iter().map(api_key_from_row).collect();
+    Ok(HttpResponse::Ok().json(keys))
+}
+
+/// Create a new API key, the key is only returned in this response
+#[utoipa::path(
+    post,
+    path = "/api/api-keys",
+    request_body = CreateApiKeyRequest,
+    responses(
+        (status = 201, description = "API key created", body = CreateApiKeyResponse),
+        (status = 403, description = "Caller is not an admin", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 422, description = "Validation failed", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Internal server error", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    tag = "API Keys"
+)]
+#[post("/api/api-keys")]
+pub async fn create_api_key(
+    caller: RequireRole<AdminOnly>,
+    pool: DbPool,
+    req: web::Json<CreateApiKeyRequest>,
+) -> Result<HttpResponse, ApiError> {
+    req.validate()?;
+
+    let mut scopes: Vec<ApiScope> = Vec::new();
+    for scope in &req.scopes {
+        if !scopes.contains(scope) {
+            scopes.push(*scope);
+        }
+    }
+
+    let id = Uuid::new_v4().to_string();
+    let key = generate_api_key();
+    let prefix = key_prefix(&key);
+
+    let params = (
+        id.clone(),
+        req.name.clone(),
+        prefix.clone(),
+        hash_secret_token(&key),
+        scopes_to_column(&scopes),
+        caller.employee_id.clone(),
+    );
+    blocking(move || {
+        let mut conn = pool.get_conn()?;
+
+        conn.exec_drop(
+            "INSERT INTO api_keys (id, name, key_prefix, key_hash, scopes, created_by) VALUES (?, ?, ?, ?, ?, ?)",
+            params,
+        )?;
+        Ok(())
+    })
+    .await?;
+
+    Ok(HttpResponse::Created().json(CreateApiKeyResponse {
+        id,
+        name: req.name.clone(),
+        key_prefix: prefix,
+        scopes,
+        key,
+    }))
+}
+
+/// Revoke an API key, it stops working immediately
+#[utoipa::path(
+    delete,
+    path = "/api/api-keys/{id}",
+    params(
+        ("id" = String, Path, description = "API key UUID")
+    ),
+    responses(
+        (status = 200, description = "API key revoked"),
+        (status = 403, description = "Caller is not an admin", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "API key not found or already revoked", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Internal server error", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    tag = "API Keys"
+)]
+#[delete("/api/api-keys/{id}")]
+pub async fn revoke_api_key(
+    _caller: RequireRole<AdminOnly>,
+    pool: DbPool,
+    id: web::Path<String>,
+) -> Result<HttpResponse, ApiError> {
+    blocking(move || {
+        let mut conn = pool.get_conn()?;
+
+        // Revoked keys are kept so listings still show who used what and when
+        conn.exec_drop(
+            "UPDATE api_keys SET revoked_at = NOW() WHERE id = ? AND revoked_at IS NULL",
+            (id.as_str(),),
+        )?;
+
+        if conn.affected_rows() == 0 {
+            return Err(ApiError::NotFound(
+                "API key not found or already revoked".to_string(),
+            ));
+        }
+        Ok(())
+    })
+    .await?;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "message": "API key revoked successfully"
+    })))
+}
@@ -0,0 +1,208 @@
+// Authentication handlers - login, token refresh and password reset
+
+use crate::auth::account::hash_secret_token;
+use crate::auth::password::{hash_password, verify_password_or_dummy};
+use crate::auth::{AccountPolicy, AuthenticatedUser, JwtConfig, TokenType};
+use crate::db::{DbPool, blocking};
+use crate::error::ApiError;
+use crate::models::*;
+use actix_web::{HttpResponse, post, web};
+use mysql::prelude::*;
+use validator::Validate;
+
+fn token_pair(jwt: &JwtConfig, user: &AuthenticatedUser) -> Result<TokenResponse, ApiError> {
+    Ok(TokenResponse {
+        access_token: jwt.issue(user, TokenType::Access)?,
+        refresh_token: jwt.issue(user, TokenType::Refresh)?,
+        token_type: "Bearer".to_string(),
+        expires_in: jwt.access_ttl_seconds,
+    })
+}
+
+/// Log in with user email and password
+#[utoipa::path(
+    post,
+    path = "/api/auth/login",
+    request_body = LoginRequest,
+    responses(
+        (status = 200, description = "Login successful", body = TokenResponse),
+        (status = 401, description = "Invalid email or password, or account locked (only reported for the right password)", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 422, description = "Validation failed", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Internal server error", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(()),
+    tag = "Auth"
+)]
+#[post("/api/auth/login")]
+pub async fn login(
+    pool: DbPool,
+    jwt: web::Data<JwtConfig>,
+    policy: web::Data<AccountPolicy>,
+    credentials: web::Json<LoginRequest>,
+) -> Result<HttpResponse, ApiError> {
+    credentials.validate()?;
+
+    let user = blocking(move || {
+        let mut conn = pool.get_conn()?;
+
+        let mut rows: Vec<mysql::Row> = conn.exec(
+            "SELECT u.id, u.password_hash, COALESCE(u.locked_until > NOW(), FALSE) AS locked, e.id AS employee_id, e.role, e.department_id, e.active \
+             FROM users u JOIN employees e ON e.id = u.employee_id WHERE u.email = ?",
+            (&credentials.email,),
+        )?;
+
+        // Unknown email, wrong password and inactive employee all get the same answer, and an
+        // unknown email still pays for a password check so the timing doesn't tell either
+        let invalid = || ApiError::Unauthorized("Invalid email or password".to_string());
+        let Some(mut row) = rows.pop() else {
+            verify_password_or_dummy(&credentials.password, None);
+            return Err(invalid());
+        };
+
+        let user_id: i32 = row.take("id").unwrap();
+        let password_hash: Option<String> = row.take("password_hash").unwrap();
+        let locked: bool = row.take("locked").unwrap();
+        let active: bool = row.take("active").unwrap();
+
+        let password_ok = verify_password_or_dummy(&credentials.password, password_hash.as_deref());
+        // Only someone who knows the password learns about the lock, for everyone else a locked
+        // account looks like any other failed login
+        if locked && password_ok {
+            return Err(ApiError::Unauthorized(
+                "Account is locked after too many failed logins, try again later".to_string(),
+            ));
+        }
+        if locked {
+            return Err(invalid());
+        }
+        if !password_ok {
+            // Lock the account once the limit is reached and start counting from zero afterwards,
+            // MySQL applies the assignments left to right so both read the old counter
+            conn.exec_drop(
+                "UPDATE users SET \
+                 locked_until = IF(failed_login_attempts + 1 >= ?, NOW() + INTERVAL ? SECOND, locked_until), \
+                 failed_login_attempts = IF(failed_login_attempts + 1 >= ?, 0, failed_login_attempts + 1) \
+                 WHERE id = ?",
+                (
+                    policy.max_failed_logins,
+                    policy.lockout_seconds,
+                    policy.max_failed_logins,
+                    user_id,
+                ),
+            )?;
+            return Err(invalid());
+        }
+        if !active {
+            return Err(invalid());
+        }
+
+        conn.exec_drop(
+            "UPDATE users SET failed_login_attempts = 0, locked_until = NULL WHERE id = ?",
+            (user_id,),
+        )?;
+
+        Ok(AuthenticatedUser {
+            employee_id: row.take("employee_id").unwrap(),
+            role: row.take("role").unwrap(),
+            department_id: row.take("department_id").unwrap(),
+        })
+    })
+    .await?;
+
+    Ok(HttpResponse::Ok().json(token_pair(&jwt, &user)?))
+}
+
+/// Exchange a refresh token for a new token pair
+#[utoipa::path(
+    post,
+    path = "/api/auth/refresh",
+    request_body = RefreshRequest,
+    responses(
+        (status = 200, description = "Tokens refreshed", body = TokenResponse),
+        (status = 401, description = "Invalid refresh token or inactive employee", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Internal server error", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(()),
+    tag = "Auth"
+)]
+#[post("/api/auth/refresh")]
+pub async fn refresh(
+    pool: DbPool,
+    jwt: web::Data<JwtConfig>,
+    req: web::Json<RefreshRequest>,
+) -> Result<HttpResponse, ApiError> {
+    let claims = jwt.verify(&req.refresh_token, TokenType::Refresh)?;
+
+    // Role and department are read again so changes apply from the next refresh on
+    let employee_id = claims.sub.clone();
+    let employee: Option<(Role, Option<String>, bool)> = blocking(move || {
+        let mut conn = pool.get_conn()?;
+
+        Ok(conn.exec_first(
+            "SELECT role, department_id, active FROM employees WHERE id = ?",
+            (employee_id,),
+        )?)
+    })
+    .await?;
+
+    match employee {
+        Some((role, department_id, true)) => {
+            let user = AuthenticatedUser {
+                employee_id: claims.sub,
+                role,
+                department_id,
+            };
+            Ok(HttpResponse::Ok().json(token_pair(&jwt, &user)?))
+        }
+        _ => Err(ApiError::Unauthorized(
+            "Employee no longer exists or is inactive".to_string(),
+        )),
+    }
+}
+
+/// Set a new password with a reset token issued by an admin
+#[utoipa::path(
+    post,
+    path = "/api/auth/reset-password",
+    request_body = ResetPasswordRequest,
+    responses(
+        (status = 200, description = "Password changed"),
+        (status = 401, description = "Invalid or expired reset token", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 422, description = "Validation failed", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Internal server error", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(()),
+    tag = "Auth"
+)]
+#[post("/api/auth/reset-password")]
+pub async fn reset_password(
+    pool: DbPool,
+    req: web::Json<ResetPasswordRequest>,
+) -> Result<HttpResponse, ApiError> {
+    req.validate()?;
+
+    blocking(move || {
+        let password_hash = hash_password(&req.new_password)?;
+
+        let mut conn = pool.get_conn()?;
+
+        // Token is single use, a successful reset also lifts a lockout
+        conn.exec_drop(
+            "UPDATE users SET password_hash = ?, reset_token_hash = NULL, reset_token_expires_at = NULL, failed_login_attempts = 0, locked_until = NULL \
+             WHERE reset_token_hash = ? AND reset_token_expires_at > NOW()",
+            (&password_hash, hash_secret_token(&req.reset_token)),
+        )?;
+
+        if conn.affected_rows() == 0 {
+            return Err(ApiError::Unauthorized(
+                "Invalid or expired reset token".to_string(),
+            ));
+        }
+        Ok(())
+    })
+    .await?;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "message": "Password changed successfully"
+    })))
+}
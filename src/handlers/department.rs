@@ -1,58 +1,33 @@
 // Department management handlers
 
-use crate::db::DbPool;
+use crate::auth::{AdminOnly, RequireRole};
+use crate::db::blocking;
+use crate::error::ApiError;
+use crate::hierarchy::build_department_tree;
 use crate::models::*;
-use actix_web::{HttpResponse, Responder, delete, get, post, put, web};
-use mysql::prelude::*;
-use uuid::Uuid;
-
-// Type alias for department database row
-type DepartmentRow = (
-    String,
-    String,
-    Option<String>,
-    Option<String>,
-    Option<String>,
-);
+use crate::repository::{DepartmentEmployees, DepartmentRepository};
+use actix_web::{HttpResponse, delete, get, post, put, web};
+use validator::Validate;
 
 /// Get all departments
 #[utoipa::path(
     get,
     path = "/api/departments",
+    params(IncludeDeletedQuery),
     responses(
-        (status = 200, description = "List of all departments", body = Vec<Department>),
-        (status = 500, description = "Internal server error")
+        (status = 200, description = "List of all departments, without deleted ones unless include_deleted is set", body = Vec<Department>),
+        (status = 500, description = "Internal server error", body = ProblemDetails, content_type = "application/problem+json")
     ),
     tag = "Departments"
 )]
 #[get("/api/departments")]
-pub async fn get_departments(pool: web::Data<DbPool>) -> impl Responder {
-    let mut conn = match pool.get_conn() {
-        Ok(conn) => conn,
-        Err(e) => {
-            return HttpResponse::InternalServerError().json(serde_json::json!({
-                "error": format!("Database connection error: {}", e)
-            }));
-        }
-    };
-
-    let result: Result<Vec<Department>, mysql::Error> = conn.query_map(
-        "SELECT id, name, head_id, created_at, updated_at FROM departments",
-        |(id, name, head_id, created_at, updated_at)| Department {
-            id,
-            name,
-            head_id,
-            created_at,
-            updated_at,
-        },
-    );
-
-    match result {
-        Ok(departments) => HttpResponse::Ok().json(departments),
-        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({
-            "error": format!("Database error: {}", e)
-        })),
-    }
+pub async fn get_departments(
+    departments: web::Data<dyn DepartmentRepository>,
+    query: web::Query<IncludeDeletedQuery>,
+) -> Result<HttpResponse, ApiError> {
+    let include_deleted = query.include_deleted.unwrap_or(false);
+
+    Ok(HttpResponse::Ok().json(blocking(move || departments.list(include_deleted)).await?))
 }
 
 /// Get department by ID
@@ -60,52 +35,81 @@ pub async fn get_departments(pool: web::Data<DbPool>) -> impl Responder {
     get,
     path = "/api/departments/{id}",
     params(
-        ("id" = String, Path, description = "Department UUID")
+        ("id" = String, Path, description = "Department UUID"),
+        IncludeDeletedQuery
     ),
     responses(
         (status = 200, description = "Department found", body = Department),
-        (status = 404, description = "Department not found"),
-        (status = 500, description = "Internal server error")
+        (status = 404, description = "Department not found, or deleted and include_deleted not set", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Internal server error", body = ProblemDetails, content_type = "application/problem+json")
     ),
     tag = "Departments"
 )]
 #[get("/api/departments/{id}")]
 pub async fn get_department_by_id(
-    pool: web::Data<DbPool>,
+    departments: web::Data<dyn DepartmentRepository>,
+    id: web::Path<String>,
+    query: web::Query<IncludeDeletedQuery>,
+) -> Result<HttpResponse, ApiError> {
+    let include_deleted = query.include_deleted.unwrap_or(false);
+    let department = blocking(move || departments.find_by_id(&id)).await?;
+
+    match department.filter(|d| include_deleted || d.deleted_at.is_none()) {
+        Some(department) => Ok(HttpResponse::Ok().json(department)),
+        None => Err(ApiError::NotFound("Department not found".to_string())),
+    }
+}
+
+/// Department tree
+///
+/// All departments nested under their parent departments, top level divisions first.
+#[utoipa::path(
+    get,
+    path = "/api/departments/tree",
+    responses(
+        (status = 200, description = "Top level departments with their subdepartments nested", body = Vec<DepartmentNode>),
+        (status = 500, description = "Internal server error", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    tag = "Departments"
+)]
+#[get("/api/departments/tree")]
+pub async fn get_department_tree(
+    departments: web::Data<dyn DepartmentRepository>,
+) -> Result<HttpResponse, ApiError> {
+    let departments = blocking(move || departments.list(false)).await?;
+    Ok(HttpResponse::Ok().json(build_department_tree(departments)))
+}
+
+/// Get the departments directly below a department
+#[utoipa::path(
+    get,
+    path = "/api/departments/{id}/subdepartments",
+    params(
+        ("id" = String, Path, description = "Department UUID")
+    ),
+    responses(
+        (status = 200, description = "Direct subdepartments ordered by name", body = Vec<Department>),
+        (status = 404, description = "Department not found", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Internal server error", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    tag = "Departments"
+)]
+#[get("/api/departments/{id}/subdepartments")]
+pub async fn get_subdepartments(
+    departments: web::Data<dyn DepartmentRepository>,
     id: web::Path<String>,
-) -> impl Responder {
-    let mut conn = match pool.get_conn() {
-        Ok(conn) => conn,
-        Err(e) => {
-            return HttpResponse::InternalServerError().json(serde_json::json!({
-                "error": format!("Database connection error: {}", e)
-            }));
+) -> Result<HttpResponse, ApiError> {
+    let subdepartments = blocking(move || match departments.find_by_id(&id)? {
+        Some(department) if department.deleted_at.is_none() => {
+            departments.subdepartments(&id).map(Some)
         }
-    };
-
-    let result: Result<Option<Department>, mysql::Error> = conn
-        .exec_first(
-            "SELECT id, name, head_id, created_at, updated_at FROM departments WHERE id = ?",
-            (id.as_str(),),
-        )
-        .map(|row: Option<DepartmentRow>| {
-            row.map(|(id, name, head_id, created_at, updated_at)| Department {
-                id,
-                name,
-                head_id,
-                created_at,
-                updated_at,
-            })
-        });
-
-    match result {
-        Ok(Some(department)) => HttpResponse::Ok().json(department),
-        Ok(None) => HttpResponse::NotFound().json(serde_json::json!({
-            "error": "Department not found"
-        })),
-        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({
-            "error": format!("Database error: {}", e)
-        })),
+        _ => Ok(None),
+    })
+    .await?;
+
+    match subdepartments {
+        Some(subdepartments) => Ok(HttpResponse::Ok().json(subdepartments)),
+        None => Err(ApiError::NotFound("Department not found".to_string())),
     }
 }
 
@@ -115,42 +119,32 @@ pub async fn get_department_by_id(
     path = "/api/departments",
     request_body = CreateDepartmentRequest,
     responses(
-        (status = 201, description = "Department created successfully"),
-        (status = 500, description = "Internal server error")
+        (status = 201, description = "Department created successfully", body = Department),
+        (status = 403, description = "Caller is not an admin", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 409, description = "Department name already in use, deleted departments included", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 422, description = "Validation failed, unknown parent department, or a head that is unknown, inactive or not a head of the department without promote_head", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Internal server error", body = ProblemDetails, content_type = "application/problem+json")
     ),
     tag = "Departments"
 )]
 #[post("/api/departments")]
 pub async fn create_department(
-    pool: web::Data<DbPool>,
+    _caller: RequireRole<AdminOnly>,
+    departments: web::Data<dyn DepartmentRepository>,
     department: web::Json<CreateDepartmentRequest>,
-) -> impl Responder {
-    let mut conn = match pool.get_conn() {
-        Ok(conn) => conn,
-        Err(e) => {
-            return HttpResponse::InternalServerError().json(serde_json::json!({
-                "error": format!("Database connection error: {}", e)
-            }));
-        }
-    };
-
-    let id = Uuid::new_v4().to_string();
-
-    let result = conn.exec_drop(
-        "INSERT INTO departments (id, name, head_id) VALUES (?, ?, ?)",
-        (&id, &department.name, &department.head_id),
-    );
-
-    match result {
-        Ok(_) => HttpResponse::Created().json(serde_json::json!({
-            "id": id,
-            "name": department.name,
-            "head_id": department.head_id
-        })),
-        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({
-            "error": format!("Database error: {}", e)
-        })),
-    }
+) -> Result<HttpResponse, ApiError> {
+    department.validate()?;
+
+    let department = department.into_inner();
+    let created = blocking(move || {
+        let id = departments.create(&department)?;
+        departments.find_by_id(&id)?.ok_or_else(|| {
+            ApiError::Internal(format!("Created department {} could not be read back", id))
+        })
+    })
+    .await?;
+
+    Ok(HttpResponse::Created().json(created))
 }
 
 /// Update department
@@ -162,58 +156,28 @@ pub async fn create_department(
     ),
     request_body = UpdateDepartmentRequest,
     responses(
-        (status = 200, description = "Department updated successfully"),
-        (status = 404, description = "Department not found"),
-        (status = 500, description = "Internal server error")
+        (status = 200, description = "Department updated successfully", body = Department),
+        (status = 403, description = "Caller is not an admin", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 400, description = "No fields to update", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Department not found", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 409, description = "Department name already in use, deleted departments included", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 422, description = "Validation failed, a parent that is unknown, the department itself or one below it, or a head that is unknown, inactive or not a head of the department without promote_head", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Internal server error", body = ProblemDetails, content_type = "application/problem+json")
     ),
     tag = "Departments"
 )]
 #[put("/api/departments/{id}")]
 pub async fn update_department(
-    pool: web::Data<DbPool>,
+    _caller: RequireRole<AdminOnly>,
+    departments: web::Data<dyn DepartmentRepository>,
     id: web::Path<String>,
     department: web::Json<UpdateDepartmentRequest>,
-) -> impl Responder {
-    let mut conn = match pool.get_conn() {
-        Ok(conn) => conn,
-        Err(e) => {
-            return HttpResponse::InternalServerError().json(serde_json::json!({
-                "error": format!("Database connection error: {}", e)
-            }));
-        }
-    };
+) -> Result<HttpResponse, ApiError> {
+    department.validate()?;
 
-    let mut updates = Vec::new();
-    let mut params: Vec<mysql::Value> = Vec::new();
+    let department = blocking(move || departments.update(&id, &department)).await?;
 
-    if let Some(ref name) = department.name {
-        updates.push("name = ?");
-        params.push(name.clone().into());
-    }
-    if let Some(ref head_id) = department.head_id {
-        updates.push("head_id = ?");
-        params.push(head_id.clone().into());
-    }
-
-    if updates.is_empty() {
-        return HttpResponse::BadRequest().json(serde_json::json!({
-            "error": "No fields to update"
-        }));
-    }
-
-    params.push(id.as_str().into());
-    let query = format!("UPDATE departments SET {} WHERE id = ?", updates.join(", "));
-
-    let result = conn.exec_drop(&query, params);
-
-    match result {
-        Ok(_) => HttpResponse::Ok().json(serde_json::json!({
-            "message": "Department updated successfully"
-        })),
-        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({
-            "error": format!("Database error: {}", e)
-        })),
-    }
+    Ok(HttpResponse::Ok().json(department))
 }
 
 /// Delete department
@@ -221,33 +185,67 @@ pub async fn update_department(
     delete,
     path = "/api/departments/{id}",
     params(
-        ("id" = String, Path, description = "Department UUID")
+        ("id" = String, Path, description = "Department UUID"),
+        DeleteDepartmentQuery
     ),
     responses(
-        (status = 200, description = "Department deleted successfully"),
-        (status = 500, description = "Internal server error")
+        (status = 200, description = "Department marked deleted, returns it with the number of employees and subdepartments that were changed", body = DepartmentDeletion),
+        (status = 403, description = "Caller is not an admin", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Department not found", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 409, description = "Department still has active employees and neither reassign_to nor force is set", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 422, description = "reassign_to is invalid, unknown or the department itself", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Internal server error", body = ProblemDetails, content_type = "application/problem+json")
     ),
     tag = "Departments"
 )]
 #[delete("/api/departments/{id}")]
-pub async fn delete_department(pool: web::Data<DbPool>, id: web::Path<String>) -> impl Responder {
-    let mut conn = match pool.get_conn() {
-        Ok(conn) => conn,
-        Err(e) => {
-            return HttpResponse::InternalServerError().json(serde_json::json!({
-                "error": format!("Database connection error: {}", e)
-            }));
-        }
-    };
+pub async fn delete_department(
+    _caller: RequireRole<AdminOnly>,
+    departments: web::Data<dyn DepartmentRepository>,
+    id: web::Path<String>,
+    query: web::Query<DeleteDepartmentQuery>,
+) -> Result<HttpResponse, ApiError> {
+    query.validate()?;
+
+    let query = query.into_inner();
+    let deletion = blocking(move || {
+        let employees = match (query.reassign_to.as_deref(), query.force) {
+            (Some(target_id), _) => DepartmentEmployees::ReassignTo(target_id),
+            (None, Some(true)) => DepartmentEmployees::Unassign,
+            (None, _) => DepartmentEmployees::Refuse,
+        };
+        departments.delete(&id, employees)
+    })
+    .await?;
+
+    Ok(HttpResponse::Ok().json(deletion))
+}
 
-    let result = conn.exec_drop("DELETE FROM departments WHERE id = ?", (id.as_str(),));
+/// Restore department
+///
+/// Takes a deleted department back. It returns below its parent unless that has been deleted
+/// as well, employees that were moved out or unassigned stay where they are.
+#[utoipa::path(
+    post,
+    path = "/api/departments/{id}/restore",
+    params(
+        ("id" = String, Path, description = "Department UUID")
+    ),
+    responses(
+        (status = 200, description = "Department restored, or returned as it is if it wasn't deleted", body = Department),
+        (status = 403, description = "Caller is not an admin", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Department not found", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Internal server error", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    tag = "Departments"
+)]
+#[post("/api/departments/{id}/restore")]
+pub async fn restore_department(
+    _caller: RequireRole<AdminOnly>,
+    departments: web::Data<dyn DepartmentRepository>,
+    id: web::Path<String>,
+) -> Result<HttpResponse, ApiError> {
+    let department = blocking(move || departments.restore(&id)).await?;
 
-    match result {
-        Ok(_) => HttpResponse::Ok().json(serde_json::json!({
-            "message": "Department deleted successfully"
-        })),
-        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({
-            "error": format!("Database error: {}", e)
-        })),
-    }
+    Ok(HttpResponse::Ok().json(department))
 }
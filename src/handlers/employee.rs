@@ -1,77 +1,121 @@
 // Employee management handlers
 
-use crate::db::DbPool;
+use crate::auth::{AdminOnly, AuthenticatedUser, DepartmentManager, RequireRole};
+use crate::db::{DbPool, blocking};
+use crate::error::ApiError;
+use crate::handler::{USER_COLUMNS, user_from_row};
+use crate::handlers::api_key::{API_KEY_COLUMNS, api_key_from_row};
+use crate::hierarchy::{build_chain, build_org_chart};
 use crate::models::*;
-use actix_web::{HttpResponse, Responder, delete, get, post, put, web};
+use crate::pagination::{PageRequest, paged_response, parse_sort};
+use crate::repository::{EmployeeFilter, EmployeeRepository, EmployeeSortField, search_terms};
+use actix_web::{HttpRequest, HttpResponse, delete, get, post, put, web};
 use mysql::prelude::*;
-use uuid::Uuid;
+use validator::Validate;
 
-/// Get all employees (active only by default)
+// Employees are listed by name unless the client picks another order
+const DEFAULT_EMPLOYEE_SORT: &str = "last_name,first_name";
+
+fn employee_filter(query: EmployeeListQuery) -> EmployeeFilter {
+    let active = match (query.active, query.include_inactive) {
+        (Some(active), _) => Some(active),
+        (None, Some(true)) => None,
+        (None, _) => Some(true),
+    };
+
+    EmployeeFilter {
+        department_id: query.department_id,
+        manager_id: query.manager_id,
+        salary_grade_id: query.salary_grade_id,
+        role: query.role,
+        hired_from: query.hired_from,
+        hired_to: query.hired_to,
+        active,
+    }
+}
+
+/// List employees page by page (active only by default)
+///
+/// Without `page` and `per_page` only the first 20 employees are returned. The total number of
+/// matches is returned in `X-Total-Count`, links to the other pages in `Link`.
 #[utoipa::path(
     get,
     path = "/api/employees",
-    params(
-        ("include_inactive" = Option<String>, Query, description = "Include inactive employees (true/false)")
-    ),
+    params(EmployeeListQuery),
     responses(
-        (status = 200, description = "List of employees", body = Vec<Employee>),
-        (status = 500, description = "Internal server error")
+        (status = 200, description = "One page of employees", body = Vec<Employee>,
+            headers(
+                ("X-Total-Count" = u64, description = "Employees matching the filters across all pages"),
+                ("X-Page" = u32, description = "Current page"),
+                ("X-Per-Page" = u32, description = "Page size"),
+                ("Link" = String, description = "first, last, prev and next page URLs")
+            )
+        ),
+        (status = 400, description = "Malformed query string", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 403, description = "Caller is a plain employee", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 422, description = "Invalid filter, page or sort field", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Internal server error", body = ProblemDetails, content_type = "application/problem+json")
     ),
     tag = "Employees"
 )]
 #[get("/api/employees")]
 pub async fn get_employees(
-    pool: web::Data<DbPool>,
-    query: web::Query<std::collections::HashMap<String, String>>,
-) -> impl Responder {
-    let mut conn = match pool.get_conn() {
-        Ok(conn) => conn,
-        Err(e) => {
-            return HttpResponse::InternalServerError().json(serde_json::json!({
-                "error": format!("Database connection error: {}", e)
-            }));
-        }
-    };
-
-    let include_inactive = query
-        .get("include_inactive")
-        .map(|v| v == "true")
-        .unwrap_or(false);
-
-    let query_str = if include_inactive {
-        "SELECT id, first_name, last_name, email, department_id, salary_grade_id, manager_id, role, hire_date, active, deleted_at, created_at, updated_at FROM employees"
-    } else {
-        "SELECT id, first_name, last_name, email, department_id, salary_grade_id, manager_id, role, hire_date, active, deleted_at, created_at, updated_at FROM employees WHERE active = TRUE"
-    };
+    _caller: RequireRole<DepartmentManager>,
+    employees: web::Data<dyn EmployeeRepository>,
+    req: HttpRequest,
+    query: web::Query<EmployeeListQuery>,
+) -> Result<HttpResponse, ApiError> {
+    query.validate()?;
+
+    let query = query.into_inner();
+    let sort =
+        parse_sort::<EmployeeSortField>(query.sort.as_deref().unwrap_or(DEFAULT_EMPLOYEE_SORT))?;
+    let page = PageRequest::new(query.page, query.per_page);
+    let filter = employee_filter(query);
+
+    let result = blocking(move || employees.list_page(&filter, &sort, page)).await?;
+    Ok(paged_response(&req, page, result))
+}
 
-    let rows: Result<Vec<mysql::Row>, mysql::Error> = conn.query(query_str);
-
-    match rows {
-        Ok(rows) => {
-            let employees: Vec<Employee> = rows
-                .into_iter()
-                .map(|mut row| Employee {
-                    id: row.take("id").unwrap(),
-                    first_name: row.take("first_name").unwrap(),
-                    last_name: row.take("last_name").unwrap(),
-                    email: row.take("email").unwrap(),
-                    department_id: row.take("department_id").unwrap(),
-                    salary_grade_id: row.take("salary_grade_id").unwrap(),
-                    manager_id: row.take("manager_id").unwrap(),
-                    role: row.take("role").unwrap(),
-                    hire_date: row.take("hire_date").unwrap(),
-                    active: row.take("active").unwrap(),
-                    deleted_at: row.take("deleted_at").unwrap(),
-                    created_at: row.take("created_at").unwrap(),
-                    updated_at: row.take("updated_at").unwrap(),
-                })
-                .collect();
-            HttpResponse::Ok().json(employees)
-        }
-        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({
-            "error": format!("Database error: {}", e)
-        })),
-    }
+/// Search employees by the start of their name or email
+///
+/// Case and accents are ignored, `mull` finds Müller. Results are ranked, whole names first, and
+/// paged like `GET /api/employees`.
+#[utoipa::path(
+    get,
+    path = "/api/employees/search",
+    params(EmployeeSearchQuery),
+    responses(
+        (status = 200, description = "One page of matching employees, best matches first", body = Vec<Employee>,
+            headers(
+                ("X-Total-Count" = u64, description = "Matching employees across all pages"),
+                ("X-Page" = u32, description = "Current page"),
+                ("X-Per-Page" = u32, description = "Page size"),
+                ("Link" = String, description = "first, last, prev and next page URLs")
+            )
+        ),
+        (status = 400, description = "Missing or malformed query string", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 403, description = "Caller is a plain employee", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 422, description = "Blank or too long search, invalid page", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Internal server error", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    tag = "Employees"
+)]
+#[get("/api/employees/search")]
+pub async fn search_employees(
+    _caller: RequireRole<DepartmentManager>,
+    employees: web::Data<dyn EmployeeRepository>,
+    req: HttpRequest,
+    query: web::Query<EmployeeSearchQuery>,
+) -> Result<HttpResponse, ApiError> {
+    query.validate()?;
+
+    let terms = search_terms(&query.q);
+    let include_inactive = query.include_inactive.unwrap_or(false);
+    let page = PageRequest::new(query.page, query.per_page);
+
+    let result = blocking(move || employees.search(&terms, include_inactive, page)).await?;
+    Ok(paged_response(&req, page, result))
 }
 
 /// Get employee by ID
@@ -83,56 +127,24 @@ pub async fn get_employees(
     ),
     responses(
         (status = 200, description = "Employee found", body = Employee),
-        (status = 404, description = "Employee not found"),
-        (status = 500, description = "Internal server error")
+        (status = 403, description = "Employees can only view their own record", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Employee not found", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Internal server error", body = ProblemDetails, content_type = "application/problem+json")
     ),
     tag = "Employees"
 )]
 #[get("/api/employees/{id}")]
-pub async fn get_employee_by_id(pool: web::Data<DbPool>, id: web::Path<String>) -> impl Responder {
-    let mut conn = match pool.get_conn() {
-        Ok(conn) => conn,
-        Err(e) => {
-            return HttpResponse::InternalServerError().json(serde_json::json!({
-                "error": format!("Database connection error: {}", e)
-            }));
-        }
-    };
+pub async fn get_employee_by_id(
+    caller: AuthenticatedUser,
+    employees: web::Data<dyn EmployeeRepository>,
+    id: web::Path<String>,
+) -> Result<HttpResponse, ApiError> {
+    caller.ensure_can_read_employee(&id)?;
 
-    let rows: Result<Vec<mysql::Row>, mysql::Error> = conn.exec(
-        "SELECT id, first_name, last_name, email, department_id, salary_grade_id, manager_id, role, hire_date, active, deleted_at, created_at, updated_at FROM employees WHERE id = ?",
-        (id.as_str(),)
-    );
-
-    match rows {
-        Ok(mut rows) => {
-            if let Some(mut row) = rows.pop() {
-                let employee = Employee {
-                    id: row.take("id").unwrap(),
-                    first_name: row.take("first_name").unwrap(),
-                    last_name: row.take("last_name").unwrap(),
-                    email: row.take("email").unwrap(),
-                    department_id: row.take("department_id").unwrap(),
-                    salary_grade_id: row.take("salary_grade_id").unwrap(),
-                    manager_id: row.take("manager_id").unwrap(),
-                    role: row.take("role").unwrap(),
-                    hire_date: row.take("hire_date").unwrap(),
-                    active: row.take("active").unwrap(),
-                    deleted_at: row.take("deleted_at").unwrap(),
-                    created_at: row.take("created_at").unwrap(),
-                    updated_at: row.take("updated_at").unwrap(),
-                };
-                HttpResponse::Ok().json(employee)
-            } else {
-                HttpResponse::NotFound().json(serde_json::json!({
-                    "error": "Employee not found"
-                }))
-            }
-        }
-        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({
-            "error": format!("Database error: {}", e)
-        })),
-    }
+    let Some(employee) = blocking(move || employees.find_by_id(&id)).await? else {
+        return Err(ApiError::NotFound("Employee not found".to_string()));
+    };
+    Ok(HttpResponse::Ok().json(employee))
 }
 
 /// Create new employee
@@ -141,45 +153,37 @@ pub async fn get_employee_by_id(pool: web::Data<DbPool>, id: web::Path<String>)
     path = "/api/employees",
     request_body = CreateEmployeeRequest,
     responses(
-        (status = 201, description = "Employee created successfully"),
-        (status = 500, description = "Internal server error")
+        (status = 201, description = "Employee created successfully", body = Employee),
+        (status = 403, description = "Department or role outside the caller's permissions", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 409, description = "Email already in use", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 422, description = "Validation failed or unknown department, salary grade or manager", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Internal server error", body = ProblemDetails, content_type = "application/problem+json")
     ),
     tag = "Employees"
 )]
 #[post("/api/employees")]
 pub async fn create_employee(
-    pool: web::Data<DbPool>,
+    caller: RequireRole<DepartmentManager>,
+    employees: web::Data<dyn EmployeeRepository>,
     employee: web::Json<CreateEmployeeRequest>,
-) -> impl Responder {
-    let mut conn = match pool.get_conn() {
-        Ok(conn) => conn,
-        Err(e) => {
-            return HttpResponse::InternalServerError().json(serde_json::json!({
-                "error": format!("Database connection error: {}", e)
-            }));
-        }
-    };
-
-    let id = Uuid::new_v4().to_string();
-    let role = employee.role.as_deref().unwrap_or("Employee");
-
-    let result = conn.exec_drop(
-        "INSERT INTO employees (id, first_name, last_name, email, department_id, salary_grade_id, manager_id, role, hire_date) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
-        (&id, &employee.first_name, &employee.last_name, &employee.email, &employee.department_id, &employee.salary_grade_id, &employee.manager_id, role, &employee.hire_date)
-    );
-
-    match result {
-        Ok(_) => HttpResponse::Created().json(serde_json::json!({
-            "id": id,
-            "first_name": employee.first_name,
-            "last_name": employee.last_name,
-            "email": employee.email,
-            "role": role
-        })),
-        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({
-            "error": format!("Database error: {}", e)
-        })),
-    }
+) -> Result<HttpResponse, ApiError> {
+    employee.validate()?;
+
+    let role = employee.role.unwrap_or_default();
+    caller.ensure_department(employee.department_id.as_deref())?;
+    caller.ensure_can_grant(role)?;
+
+    let employee = employee.into_inner();
+    // The repository checks the manager against the caller in the same step as the insert
+    let created = blocking(move || {
+        let id = employees.create(&employee, role, &caller)?;
+        employees.find_by_id(&id)?.ok_or_else(|| {
+            ApiError::Internal(format!("Created employee {} could not be read back", id))
+        })
+    })
+    .await?;
+
+    Ok(HttpResponse::Created().json(created))
 }
 
 /// Update employee
@@ -191,86 +195,36 @@ pub async fn create_employee(
     ),
     request_body = UpdateEmployeeRequest,
     responses(
-        (status = 200, description = "Employee updated successfully"),
-        (status = 404, description = "Employee not found"),
-        (status = 500, description = "Internal server error")
+        (status = 200, description = "Employee updated successfully", body = Employee),
+        (status = 403, description = "Employee, target department or role outside the caller's permissions", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 400, description = "No fields to update", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Employee not found", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 409, description = "Email already in use or employee anonymized", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 422, description = "Validation failed, unknown department or salary grade, or a manager that is unknown, inactive, the employee itself or one of its reports", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Internal server error", body = ProblemDetails, content_type = "application/problem+json")
     ),
     tag = "Employees"
 )]
 #[put("/api/employees/{id}")]
 pub async fn update_employee(
-    pool: web::Data<DbPool>,
+    caller: RequireRole<DepartmentManager>,
+    employees: web::Data<dyn EmployeeRepository>,
     id: web::Path<String>,
     employee: web::Json<UpdateEmployeeRequest>,
-) -> impl Responder {
-    let mut conn = match pool.get_conn() {
-        Ok(conn) => conn,
-        Err(e) => {
-            return HttpResponse::InternalServerError().json(serde_json::json!({
-                "error": format!("Database connection error: {}", e)
-            }));
-        }
-    };
+) -> Result<HttpResponse, ApiError> {
+    employee.validate()?;
 
-    let mut updates = Vec::new();
-    let mut params: Vec<mysql::Value> = Vec::new();
-
-    if let Some(ref first_name) = employee.first_name {
-        updates.push("first_name = ?");
-        params.push(first_name.clone().into());
-    }
-    if let Some(ref last_name) = employee.last_name {
-        updates.push("last_name = ?");
-        params.push(last_name.clone().into());
-    }
-    if let Some(ref email) = employee.email {
-        updates.push("email = ?");
-        params.push(email.clone().into());
-    }
+    // Moving an employee is only allowed into a department the caller manages as well
     if let Some(ref department_id) = employee.department_id {
-        updates.push("department_id = ?");
-        params.push(department_id.clone().into());
-    }
-    if let Some(ref salary_grade_id) = employee.salary_grade_id {
-        updates.push("salary_grade_id = ?");
-        params.push(salary_grade_id.clone().into());
+        caller.ensure_department(Some(department_id))?;
     }
-    if let Some(ref manager_id) = employee.manager_id {
-        updates.push("manager_id = ?");
-        params.push(manager_id.clone().into());
-    }
-    if let Some(ref role) = employee.role {
-        updates.push("role = ?");
-        params.push(role.clone().into());
-    }
-    if let Some(ref hire_date) = employee.hire_date {
-        updates.push("hire_date = ?");
-        params.push(hire_date.clone().into());
-    }
-    if let Some(active) = employee.active {
-        updates.push("active = ?");
-        params.push(active.into());
+    if let Some(role) = employee.role {
+        caller.ensure_can_grant(role)?;
     }
 
-    if updates.is_empty() {
-        return HttpResponse::BadRequest().json(serde_json::json!({
-            "error": "No fields to update"
-        }));
-    }
+    let employee = blocking(move || employees.update(&id, &employee, &caller)).await?;
 
-    params.push(id.as_str().into());
-    let query = format!("UPDATE employees SET {} WHERE id = ?", updates.join(", "));
-
-    let result = conn.exec_drop(&query, params);
-
-    match result {
-        Ok(_) => HttpResponse::Ok().json(serde_json::json!({
-            "message": "Employee updated successfully"
-        })),
-        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({
-            "error": format!("Database error: {}", e)
-        })),
-    }
+    Ok(HttpResponse::Ok().json(employee))
 }
 
 /// Delete employee (soft delete)
@@ -281,35 +235,146 @@ pub async fn update_employee(
         ("id" = String, Path, description = "Employee UUID")
     ),
     responses(
-        (status = 200, description = "Employee deleted successfully"),
-        (status = 500, description = "Internal server error")
+        (status = 200, description = "Employee deleted, returns it marked inactive", body = Employee),
+        (status = 403, description = "Employee outside the caller's department", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Employee not found", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Internal server error", body = ProblemDetails, content_type = "application/problem+json")
     ),
     tag = "Employees"
 )]
 #[delete("/api/employees/{id}")]
-pub async fn delete_employee(pool: web::Data<DbPool>, id: web::Path<String>) -> impl Responder {
-    let mut conn = match pool.get_conn() {
-        Ok(conn) => conn,
-        Err(e) => {
-            return HttpResponse::InternalServerError().json(serde_json::json!({
-                "error": format!("Database connection error: {}", e)
-            }));
-        }
-    };
+pub async fn delete_employee(
+    caller: RequireRole<DepartmentManager>,
+    employees: web::Data<dyn EmployeeRepository>,
+    id: web::Path<String>,
+) -> Result<HttpResponse, ApiError> {
+    let employee = blocking(move || employees.soft_delete(&id, &caller)).await?;
+
+    Ok(HttpResponse::Ok().json(employee))
+}
 
-    let result = conn.exec_drop(
-        "UPDATE employees SET active = FALSE, deleted_at = NOW() WHERE id = ?",
-        (id.as_str(),),
-    );
-
-    match result {
-        Ok(_) => HttpResponse::Ok().json(serde_json::json!({
-            "message": "Employee deleted successfully"
-        })),
-        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({
-            "error": format!("Database error: {}", e)
-        })),
+/// Restore employee
+///
+/// Makes a deleted employee active again. References that no longer hold are set to null and
+/// listed in the response: a department or salary grade that is gone or deleted, and a manager
+/// that is gone or inactive.
+#[utoipa::path(
+    post,
+    path = "/api/employees/{id}/restore",
+    params(
+        ("id" = String, Path, description = "Employee UUID")
+    ),
+    responses(
+        (status = 200, description = "Employee restored, with the references that were set to null", body = EmployeeRestore),
+        (status = 403, description = "Employee outside the caller's department", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Employee not found", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 409, description = "Employee has been anonymized", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Internal server error", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    tag = "Employees"
+)]
+#[post("/api/employees/{id}/restore")]
+pub async fn restore_employee(
+    caller: RequireRole<DepartmentManager>,
+    employees: web::Data<dyn EmployeeRepository>,
+    id: web::Path<String>,
+) -> Result<HttpResponse, ApiError> {
+    let restored = blocking(move || employees.restore(&id, &caller)).await?;
+
+    Ok(HttpResponse::Ok().json(restored))
+}
+
+/// Export personal data
+///
+/// Everything stored about the employee: the record itself, the names of its department,
+/// manager and salary grade, the departments it heads, its login account and the API keys it
+/// created. Admins can export anyone, everybody else only themselves.
+///
+/// There is no audit log yet, so the export has no audit entries. The record's `created_at`,
+/// `updated_at`, `deleted_at` and `anonymized_at` are all the history that is stored.
+#[utoipa::path(
+    get,
+    path = "/api/employees/{id}/personal-data",
+    params(
+        ("id" = String, Path, description = "Employee UUID")
+    ),
+    responses(
+        (status = 200, description = "Personal data of the employee", body = PersonalData),
+        (status = 403, description = "Caller is neither an admin nor the employee", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Employee not found", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Internal server error", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    tag = "Employees"
+)]
+#[get("/api/employees/{id}/personal-data")]
+pub async fn get_personal_data(
+    caller: AuthenticatedUser,
+    employees: web::Data<dyn EmployeeRepository>,
+    pool: Option<web::Data<DbPool>>,
+    id: web::Path<String>,
+) -> Result<HttpResponse, ApiError> {
+    if !caller.is_admin() && caller.employee_id != *id {
+        return Err(ApiError::Forbidden(
+            "Only admins can export the personal data of someone else".to_string(),
+        ));
     }
+
+    let data = blocking(move || {
+        let mut data = employees.personal_data(&id)?;
+
+        // Accounts and API keys are kept in MySQL whatever storage holds the employees
+        if let Some(pool) = pool {
+            let mut conn = pool.get_conn()?;
+            let account: Option<mysql::Row> = conn.exec_first(
+                format!("SELECT {} FROM users WHERE employee_id = ?", USER_COLUMNS),
+                (id.as_str(),),
+            )?;
+            data.account = account.map(user_from_row);
+
+            let api_keys: Vec<mysql::Row> = conn.exec(
+                format!(
+                    "SELECT {} FROM api_keys WHERE created_by = ? ORDER BY created_at",
+                    API_KEY_COLUMNS
+                ),
+                (id.as_str(),),
+            )?;
+            data.api_keys = api_keys.into_iter().map(api_key_from_row).collect();
+        }
+        Ok(data)
+    })
+    .await?;
+
+    Ok(HttpResponse::Ok().json(data))
+}
+
+/// Anonymize employee
+///
+/// Replaces name and email with pseudonyms for good and deletes the login account. The employee
+/// stays as an inactive row, so reports and the manager_id of its former reports keep working.
+/// Anonymized employees can't be changed or restored anymore.
+#[utoipa::path(
+    post,
+    path = "/api/employees/{id}/anonymize",
+    params(
+        ("id" = String, Path, description = "Employee UUID")
+    ),
+    responses(
+        (status = 200, description = "Employee anonymized", body = Employee),
+        (status = 403, description = "Caller is not an admin", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Employee not found", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Internal server error", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    tag = "Employees"
+)]
+#[post("/api/employees/{id}/anonymize")]
+pub async fn anonymize_employee(
+    _caller: RequireRole<AdminOnly>,
+    employees: web::Data<dyn EmployeeRepository>,
+    id: web::Path<String>,
+) -> Result<HttpResponse, ApiError> {
+    let employee = blocking(move || employees.anonymize(&id)).await?;
+
+    Ok(HttpResponse::Ok().json(employee))
 }
 
 /// Assign manager to employee
@@ -321,39 +386,28 @@ pub async fn delete_employee(pool: web::Data<DbPool>, id: web::Path<String>) ->
     ),
     request_body = AssignManagerRequest,
     responses(
-        (status = 200, description = "Manager assigned successfully"),
-        (status = 500, description = "Internal server error")
+        (status = 200, description = "Manager assigned successfully", body = Employee),
+        (status = 403, description = "Employee or manager outside the caller's department, or an employee not below the caller's role", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Employee not found", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 409, description = "Employee is anonymized", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 422, description = "Unknown or inactive manager, the employee itself or one of its reports", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Internal server error", body = ProblemDetails, content_type = "application/problem+json")
     ),
     tag = "Employees"
 )]
 #[put("/api/employees/{id}/manager")]
 pub async fn assign_manager(
-    pool: web::Data<DbPool>,
+    caller: RequireRole<DepartmentManager>,
+    employees: web::Data<dyn EmployeeRepository>,
     id: web::Path<String>,
     req: web::Json<AssignManagerRequest>,
-) -> impl Responder {
-    let mut conn = match pool.get_conn() {
-        Ok(conn) => conn,
-        Err(e) => {
-            return HttpResponse::InternalServerError().json(serde_json::json!({
-                "error": format!("Database connection error: {}", e)
-            }));
-        }
-    };
+) -> Result<HttpResponse, ApiError> {
+    req.validate()?;
 
-    let result = conn.exec_drop(
-        "UPDATE employees SET manager_id = ? WHERE id = ?",
-        (&req.manager_id, id.as_str()),
-    );
-
-    match result {
-        Ok(_) => HttpResponse::Ok().json(serde_json::json!({
-            "message": "Manager assigned successfully"
-        })),
-        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({
-            "error": format!("Database error: {}", e)
-        })),
-    }
+    let employee =
+        blocking(move || employees.assign_manager(&id, &req.manager_id, &caller)).await?;
+
+    Ok(HttpResponse::Ok().json(employee))
 }
 
 /// Assign salary grade to employee
@@ -365,39 +419,28 @@ pub async fn assign_manager(
     ),
     request_body = AssignSalaryGradeRequest,
     responses(
-        (status = 200, description = "Salary grade assigned successfully"),
-        (status = 500, description = "Internal server error")
+        (status = 200, description = "Salary grade assigned successfully", body = Employee),
+        (status = 403, description = "Employee outside the caller's department", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Employee not found", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 409, description = "Employee is anonymized", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 422, description = "Invalid or unknown salary_grade_id", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Internal server error", body = ProblemDetails, content_type = "application/problem+json")
     ),
     tag = "Employees"
 )]
 #[put("/api/employees/{id}/salary-grade")]
 pub async fn assign_salary_grade(
-    pool: web::Data<DbPool>,
+    caller: RequireRole<DepartmentManager>,
+    employees: web::Data<dyn EmployeeRepository>,
     id: web::Path<String>,
     req: web::Json<AssignSalaryGradeRequest>,
-) -> impl Responder {
-    let mut conn = match pool.get_conn() {
-        Ok(conn) => conn,
-        Err(e) => {
-            return HttpResponse::InternalServerError().json(serde_json::json!({
-                "error": format!("Database connection error: {}", e)
-            }));
-        }
-    };
+) -> Result<HttpResponse, ApiError> {
+    req.validate()?;
 
-    let result = conn.exec_drop(
-        "UPDATE employees SET salary_grade_id = ? WHERE id = ?",
-        (&req.salary_grade_id, id.as_str()),
-    );
-
-    match result {
-        Ok(_) => HttpResponse::Ok().json(serde_json::json!({
-            "message": "Salary grade assigned successfully"
-        })),
-        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({
-            "error": format!("Database error: {}", e)
-        })),
-    }
+    let employee =
+        blocking(move || employees.assign_salary_grade(&id, &req.salary_grade_id, &caller)).await?;
+
+    Ok(HttpResponse::Ok().json(employee))
 }
 
 /// Get employees by department
@@ -405,57 +448,129 @@ pub async fn assign_salary_grade(
     get,
     path = "/api/departments/{id}/employees",
     params(
-        ("id" = String, Path, description = "Department UUID")
+        ("id" = String, Path, description = "Department UUID"),
+        DepartmentEmployeesQuery
     ),
     responses(
         (status = 200, description = "List of employees in department", body = Vec<Employee>),
-        (status = 500, description = "Internal server error")
+        (status = 403, description = "Caller is a plain employee", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Internal server error", body = ProblemDetails, content_type = "application/problem+json")
     ),
     tag = "Employees"
 )]
 #[get("/api/departments/{id}/employees")]
 pub async fn get_employees_by_department(
-    pool: web::Data<DbPool>,
+    _caller: RequireRole<DepartmentManager>,
+    employees: web::Data<dyn EmployeeRepository>,
     id: web::Path<String>,
-) -> impl Responder {
-    let mut conn = match pool.get_conn() {
-        Ok(conn) => conn,
-        Err(e) => {
-            return HttpResponse::InternalServerError().json(serde_json::json!({
-                "error": format!("Database connection error: {}", e)
-            }));
-        }
-    };
+    query: web::Query<DepartmentEmployeesQuery>,
+) -> Result<HttpResponse, ApiError> {
+    let recursive = query.recursive.unwrap_or(false);
+    let employees = blocking(move || employees.list_by_department(&id, recursive)).await?;
+    Ok(HttpResponse::Ok().json(employees))
+}
 
-    let rows: Result<Vec<mysql::Row>, mysql::Error> = conn.exec(
-        "SELECT id, first_name, last_name, email, department_id, salary_grade_id, manager_id, role, hire_date, active, deleted_at, created_at, updated_at FROM employees WHERE department_id = ? AND active = TRUE",
-        (id.as_str(),)
-    );
-
-    match rows {
-        Ok(rows) => {
-            let employees: Vec<Employee> = rows
-                .into_iter()
-                .map(|mut row| Employee {
-                    id: row.take("id").unwrap(),
-                    first_name: row.take("first_name").unwrap(),
-                    last_name: row.take("last_name").unwrap(),
-                    email: row.take("email").unwrap(),
-                    department_id: row.take("department_id").unwrap(),
-                    salary_grade_id: row.take("salary_grade_id").unwrap(),
-                    manager_id: row.take("manager_id").unwrap(),
-                    role: row.take("role").unwrap(),
-                    hire_date: row.take("hire_date").unwrap(),
-                    active: row.take("active").unwrap(),
-                    deleted_at: row.take("deleted_at").unwrap(),
-                    created_at: row.take("created_at").unwrap(),
-                    updated_at: row.take("updated_at").unwrap(),
-                })
-                .collect();
-            HttpResponse::Ok().json(employees)
-        }
-        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({
-            "error": format!("Database error: {}", e)
-        })),
+/// Org chart below one employee
+#[utoipa::path(
+    get,
+    path = "/api/employees/{id}/org-chart",
+    params(
+        ("id" = String, Path, description = "Employee UUID"),
+        OrgChartQuery
+    ),
+    responses(
+        (status = 200, description = "The employee with their direct and indirect reports nested", body = OrgChartNode),
+        (status = 403, description = "Caller is a plain employee", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Employee not found", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 422, description = "Invalid depth", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Internal server error", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    tag = "Employees"
+)]
+#[get("/api/employees/{id}/org-chart")]
+pub async fn get_employee_org_chart(
+    _caller: RequireRole<DepartmentManager>,
+    employees: web::Data<dyn EmployeeRepository>,
+    id: web::Path<String>,
+    query: web::Query<OrgChartQuery>,
+) -> Result<HttpResponse, ApiError> {
+    query.validate()?;
+
+    let depth = query.depth;
+    let mut chart = blocking(move || {
+        let rows = employees.org_chart(Some(&id))?;
+        Ok(build_org_chart(rows, depth))
+    })
+    .await?;
+
+    match chart.pop() {
+        Some(root) => Ok(HttpResponse::Ok().json(root)),
+        None => Err(ApiError::NotFound("Employee not found".to_string())),
     }
 }
+
+/// Reporting chain of an employee
+///
+/// Lists the managers from the direct manager up to the top. If an inactive or missing manager,
+/// or a cycle, interrupts it, the chain stops there and `broken` says why.
+#[utoipa::path(
+    get,
+    path = "/api/employees/{id}/chain",
+    params(
+        ("id" = String, Path, description = "Employee UUID")
+    ),
+    responses(
+        (status = 200, description = "Managers above the employee", body = ReportingChain),
+        (status = 403, description = "Employees can only view their own chain", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Employee not found", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Internal server error", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    tag = "Employees"
+)]
+#[get("/api/employees/{id}/chain")]
+pub async fn get_reporting_chain(
+    caller: AuthenticatedUser,
+    employees: web::Data<dyn EmployeeRepository>,
+    id: web::Path<String>,
+) -> Result<HttpResponse, ApiError> {
+    caller.ensure_can_read_employee(&id)?;
+
+    let chain = blocking(move || Ok(build_chain(employees.management_chain(&id)?))).await?;
+    match chain {
+        Some(chain) => Ok(HttpResponse::Ok().json(chain)),
+        None => Err(ApiError::NotFound("Employee not found".to_string())),
+    }
+}
+
+/// Org chart of the whole company
+///
+/// Roots are the active employees without a manager, or whose manager is no longer active.
+#[utoipa::path(
+    get,
+    path = "/api/org-chart",
+    params(OrgChartQuery),
+    responses(
+        (status = 200, description = "Top-level employees with their reports nested", body = Vec<OrgChartNode>),
+        (status = 403, description = "Caller is a plain employee", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 422, description = "Invalid depth", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Internal server error", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    tag = "Employees"
+)]
+#[get("/api/org-chart")]
+pub async fn get_org_chart(
+    _caller: RequireRole<DepartmentManager>,
+    employees: web::Data<dyn EmployeeRepository>,
+    query: web::Query<OrgChartQuery>,
+) -> Result<HttpResponse, ApiError> {
+    query.validate()?;
+
+    let depth = query.depth;
+    let chart = blocking(move || {
+        let rows = employees.org_chart(None)?;
+        Ok(build_org_chart(rows, depth))
+    })
+    .await?;
+
+    Ok(HttpResponse::Ok().json(chart))
+}
@@ -1,10 +1,14 @@
 // Personnel handlers module - organized by domain
 
+pub mod api_key;
+pub mod auth;
 pub mod department;
 pub mod employee;
 pub mod salary_grade;
 
 // Re-export all handlers for easy access
+pub use api_key::*;
+pub use auth::*;
 pub use department::*;
 pub use employee::*;
 pub use salary_grade::*;
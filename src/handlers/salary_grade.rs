@@ -1,52 +1,32 @@
 // Salary grade management handlers
 
-use crate::db::DbPool;
+use crate::auth::{AdminOnly, RequireRole};
+use crate::db::blocking;
+use crate::error::ApiError;
 use crate::models::*;
-use actix_web::{HttpResponse, Responder, delete, get, post, put, web};
-use mysql::prelude::*;
-use uuid::Uuid;
-
-// Type alias for salary grade database row
-type SalaryGradeRow = (String, String, f64, Option<String>, Option<String>);
+use crate::repository::SalaryGradeRepository;
+use actix_web::{HttpResponse, delete, get, post, put, web};
+use validator::Validate;
 
 /// Get all salary grades
 #[utoipa::path(
     get,
     path = "/api/salary-grades",
+    params(IncludeDeletedQuery),
     responses(
-        (status = 200, description = "List of all salary grades", body = Vec<SalaryGrade>),
-        (status = 500, description = "Internal server error")
+        (status = 200, description = "List of all salary grades, without deleted ones unless include_deleted is set", body = Vec<SalaryGrade>),
+        (status = 500, description = "Internal server error", body = ProblemDetails, content_type = "application/problem+json")
     ),
     tag = "Salary Grades"
 )]
 #[get("/api/salary-grades")]
-pub async fn get_salary_grades(pool: web::Data<DbPool>) -> impl Responder {
-    let mut conn = match pool.get_conn() {
-        Ok(conn) => conn,
-        Err(e) => {
-            return HttpResponse::InternalServerError().json(serde_json::json!({
-                "error": format!("Database connection error: {}", e)
-            }));
-        }
-    };
-
-    let result: Result<Vec<SalaryGrade>, mysql::Error> = conn.query_map(
-        "SELECT id, code, base_salary, description, created_at FROM salary_grades",
-        |(id, code, base_salary, description, created_at)| SalaryGrade {
-            id,
-            code,
-            base_salary,
-            description,
-            created_at,
-        },
-    );
+pub async fn get_salary_grades(
+    grades: web::Data<dyn SalaryGradeRepository>,
+    query: web::Query<IncludeDeletedQuery>,
+) -> Result<HttpResponse, ApiError> {
+    let include_deleted = query.include_deleted.unwrap_or(false);
 
-    match result {
-        Ok(grades) => HttpResponse::Ok().json(grades),
-        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({
-            "error": format!("Database error: {}", e)
-        })),
-    }
+    Ok(HttpResponse::Ok().json(blocking(move || grades.list(include_deleted)).await?))
 }
 
 /// Get salary grade by ID
@@ -54,54 +34,28 @@ pub async fn get_salary_grades(pool: web::Data<DbPool>) -> impl Responder {
     get,
     path = "/api/salary-grades/{id}",
     params(
-        ("id" = String, Path, description = "Salary Grade UUID")
+        ("id" = String, Path, description = "Salary Grade UUID"),
+        IncludeDeletedQuery
     ),
     responses(
         (status = 200, description = "Salary grade found", body = SalaryGrade),
-        (status = 404, description = "Salary grade not found"),
-        (status = 500, description = "Internal server error")
+        (status = 404, description = "Salary grade not found, or deleted and include_deleted not set", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Internal server error", body = ProblemDetails, content_type = "application/problem+json")
     ),
     tag = "Salary Grades"
 )]
 #[get("/api/salary-grades/{id}")]
 pub async fn get_salary_grade_by_id(
-    pool: web::Data<DbPool>,
+    grades: web::Data<dyn SalaryGradeRepository>,
     id: web::Path<String>,
-) -> impl Responder {
-    let mut conn = match pool.get_conn() {
-        Ok(conn) => conn,
-        Err(e) => {
-            return HttpResponse::InternalServerError().json(serde_json::json!({
-                "error": format!("Database connection error: {}", e)
-            }));
-        }
-    };
-
-    let result: Result<Option<SalaryGrade>, mysql::Error> = conn
-        .exec_first(
-            "SELECT id, code, base_salary, description, created_at FROM salary_grades WHERE id = ?",
-            (id.as_str(),),
-        )
-        .map(|row: Option<SalaryGradeRow>| {
-            row.map(
-                |(id, code, base_salary, description, created_at)| SalaryGrade {
-                    id,
-                    code,
-                    base_salary,
-                    description,
-                    created_at,
-                },
-            )
-        });
-
-    match result {
-        Ok(Some(grade)) => HttpResponse::Ok().json(grade),
-        Ok(None) => HttpResponse::NotFound().json(serde_json::json!({
-            "error": "Salary grade not found"
-        })),
-        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({
-            "error": format!("Database error: {}", e)
-        })),
+    query: web::Query<IncludeDeletedQuery>,
+) -> Result<HttpResponse, ApiError> {
+    let include_deleted = query.include_deleted.unwrap_or(false);
+    let grade = blocking(move || grades.find_by_id(&id)).await?;
+
+    match grade.filter(|g| include_deleted || g.deleted_at.is_none()) {
+        Some(grade) => Ok(HttpResponse::Ok().json(grade)),
+        None => Err(ApiError::NotFound("Salary grade not found".to_string())),
     }
 }
 
@@ -111,43 +65,35 @@ pub async fn get_salary_grade_by_id(
     path = "/api/salary-grades",
     request_body = CreateSalaryGradeRequest,
     responses(
-        (status = 201, description = "Salary grade created successfully"),
-        (status = 500, description = "Internal server error")
+        (status = 201, description = "Salary grade created successfully", body = SalaryGrade),
+        (status = 403, description = "Caller is not an admin", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 409, description = "Salary grade code already in use, deleted salary grades included", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 422, description = "Validation failed", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Internal server error", body = ProblemDetails, content_type = "application/problem+json")
     ),
     tag = "Salary Grades"
 )]
 #[post("/api/salary-grades")]
 pub async fn create_salary_grade(
-    pool: web::Data<DbPool>,
+    _caller: RequireRole<AdminOnly>,
+    grades: web::Data<dyn SalaryGradeRepository>,
     grade: web::Json<CreateSalaryGradeRequest>,
-) -> impl Responder {
-    let mut conn = match pool.get_conn() {
-        Ok(conn) => conn,
-        Err(e) => {
-            return HttpResponse::InternalServerError().json(serde_json::json!({
-                "error": format!("Database connection error: {}", e)
-            }));
-        }
-    };
-
-    let id = Uuid::new_v4().to_string();
-
-    let result = conn.exec_drop(
-        "INSERT INTO salary_grades (id, code, base_salary, description) VALUES (?, ?, ?, ?)",
-        (&id, &grade.code, grade.base_salary, &grade.description),
-    );
-
-    match result {
-        Ok(_) => HttpResponse::Created().json(serde_json::json!({
-            "id": id,
-            "code": grade.code,
-            "base_salary": grade.base_salary,
-            "description": grade.description
-        })),
-        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({
-            "error": format!("Database error: {}", e)
-        })),
-    }
+) -> Result<HttpResponse, ApiError> {
+    grade.validate()?;
+
+    let grade = grade.into_inner();
+    let created = blocking(move || {
+        let id = grades.create(&grade)?;
+        grades.find_by_id(&id)?.ok_or_else(|| {
+            ApiError::Internal(format!(
+                "Created salary grade {} could not be read back",
+                id
+            ))
+        })
+    })
+    .await?;
+
+    Ok(HttpResponse::Created().json(created))
 }
 
 /// Update salary grade
@@ -159,65 +105,28 @@ pub async fn create_salary_grade(
     ),
     request_body = UpdateSalaryGradeRequest,
     responses(
-        (status = 200, description = "Salary grade updated successfully"),
-        (status = 404, description = "Salary grade not found"),
-        (status = 500, description = "Internal server error")
+        (status = 200, description = "Salary grade updated successfully", body = SalaryGrade),
+        (status = 403, description = "Caller is not an admin", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 400, description = "No fields to update", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Salary grade not found", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 409, description = "Salary grade code already in use, deleted salary grades included", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 422, description = "Validation failed", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Internal server error", body = ProblemDetails, content_type = "application/problem+json")
     ),
     tag = "Salary Grades"
 )]
 #[put("/api/salary-grades/{id}")]
 pub async fn update_salary_grade(
-    pool: web::Data<DbPool>,
+    _caller: RequireRole<AdminOnly>,
+    grades: web::Data<dyn SalaryGradeRepository>,
     id: web::Path<String>,
     grade: web::Json<UpdateSalaryGradeRequest>,
-) -> impl Responder {
-    let mut conn = match pool.get_conn() {
-        Ok(conn) => conn,
-        Err(e) => {
-            return HttpResponse::InternalServerError().json(serde_json::json!({
-                "error": format!("Database connection error: {}", e)
-            }));
-        }
-    };
-
-    let mut updates = Vec::new();
-    let mut params: Vec<mysql::Value> = Vec::new();
-
-    if let Some(ref code) = grade.code {
-        updates.push("code = ?");
-        params.push(code.clone().into());
-    }
-    if let Some(base_salary) = grade.base_salary {
-        updates.push("base_salary = ?");
-        params.push(base_salary.into());
-    }
-    if let Some(ref description) = grade.description {
-        updates.push("description = ?");
-        params.push(description.clone().into());
-    }
-
-    if updates.is_empty() {
-        return HttpResponse::BadRequest().json(serde_json::json!({
-            "error": "No fields to update"
-        }));
-    }
+) -> Result<HttpResponse, ApiError> {
+    grade.validate()?;
 
-    params.push(id.as_str().into());
-    let query = format!(
-        "UPDATE salary_grades SET {} WHERE id = ?",
-        updates.join(", ")
-    );
+    let grade = blocking(move || grades.update(&id, &grade)).await?;
 
-    let result = conn.exec_drop(&query, params);
-
-    match result {
-        Ok(_) => HttpResponse::Ok().json(serde_json::json!({
-            "message": "Salary grade updated successfully"
-        })),
-        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({
-            "error": format!("Database error: {}", e)
-        })),
-    }
+    Ok(HttpResponse::Ok().json(grade))
 }
 
 /// Delete salary grade
@@ -228,30 +137,48 @@ pub async fn update_salary_grade(
         ("id" = String, Path, description = "Salary Grade UUID")
     ),
     responses(
-        (status = 200, description = "Salary grade deleted successfully"),
-        (status = 500, description = "Internal server error")
+        (status = 200, description = "Salary grade marked deleted, employees on it keep it until it is reassigned", body = SalaryGrade),
+        (status = 403, description = "Caller is not an admin", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Salary grade not found", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Internal server error", body = ProblemDetails, content_type = "application/problem+json")
     ),
     tag = "Salary Grades"
 )]
 #[delete("/api/salary-grades/{id}")]
-pub async fn delete_salary_grade(pool: web::Data<DbPool>, id: web::Path<String>) -> impl Responder {
-    let mut conn = match pool.get_conn() {
-        Ok(conn) => conn,
-        Err(e) => {
-            return HttpResponse::InternalServerError().json(serde_json::json!({
-                "error": format!("Database connection error: {}", e)
-            }));
-        }
-    };
+pub async fn delete_salary_grade(
+    _caller: RequireRole<AdminOnly>,
+    grades: web::Data<dyn SalaryGradeRepository>,
+    id: web::Path<String>,
+) -> Result<HttpResponse, ApiError> {
+    let grade = blocking(move || grades.delete(&id)).await?;
 
-    let result = conn.exec_drop("DELETE FROM salary_grades WHERE id = ?", (id.as_str(),));
+    Ok(HttpResponse::Ok().json(grade))
+}
 
-    match result {
-        Ok(_) => HttpResponse::Ok().json(serde_json::json!({
-            "message": "Salary grade deleted successfully"
-        })),
-        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({
-            "error": format!("Database error: {}", e)
-        })),
-    }
+/// Restore salary grade
+///
+/// Takes a deleted salary grade back, employees that were on it have to be assigned again.
+#[utoipa::path(
+    post,
+    path = "/api/salary-grades/{id}/restore",
+    params(
+        ("id" = String, Path, description = "Salary Grade UUID")
+    ),
+    responses(
+        (status = 200, description = "Salary grade restored, or returned as it is if it wasn't deleted", body = SalaryGrade),
+        (status = 403, description = "Caller is not an admin", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Salary grade not found", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Internal server error", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    tag = "Salary Grades"
+)]
+#[post("/api/salary-grades/{id}/restore")]
+pub async fn restore_salary_grade(
+    _caller: RequireRole<AdminOnly>,
+    grades: web::Data<dyn SalaryGradeRepository>,
+    id: web::Path<String>,
+) -> Result<HttpResponse, ApiError> {
+    let grade = blocking(move || grades.restore(&id)).await?;
+
+    Ok(HttpResponse::Ok().json(grade))
 }
@@ -0,0 +1,297 @@
+// Reporting hierarchy built from employees.manager_id and the department tree built from
+// departments.parent_id. The repositories load the flat rows, turning them into trees and checking
+// changes to them (managers, parents, department heads) happens here so both backends answer the
+// same way.
+
+use crate::error::ApiError;
+use crate::models::{
+    ChainBreak, ChainManager, Department, DepartmentNode, Employee, OrgChartNode, ReportingChain,
+    Role,
+};
+use std::collections::{HashMap, HashSet};
+
+/// Deepest level the recursive queries follow, guards against runaway hierarchies
+pub const MAX_HIERARCHY_DEPTH: u32 = 100;
+
+/// One employee of an org chart or reporting chain, `level` 0 is where the walk started
+#[derive(Debug, Clone, PartialEq)]
+pub struct OrgChartRow {
+    pub id: String,
+    pub first_name: String,
+    pub last_name: String,
+    pub role: Role,
+    pub department_id: Option<String>,
+    pub department_name: Option<String>,
+    pub manager_id: Option<String>,
+    pub active: bool,
+    pub level: u32,
+}
+
+/// Nests the rows under their roots, in row order. Report counts always cover the whole
+/// hierarchy, `depth` only limits how many levels of `reports` are included.
+pub fn build_org_chart(rows: Vec<OrgChartRow>, depth: Option<u32>) -> Vec<OrgChartNode> {
+    let mut roots = Vec::new();
+    let mut children: HashMap<String, Vec<OrgChartRow>> = HashMap::new();
+    for row in rows {
+        match (row.level, row.manager_id.clone()) {
+            (0, _) | (_, None) => roots.push(row),
+            (_, Some(manager_id)) => children.entry(manager_id).or_default().push(row),
+        }
+    }
+
+    roots
+        .into_iter()
+        .map(|root| build_node(root, &mut children, depth))
+        .collect()
+}
+
+fn build_node(
+    row: OrgChartRow,
+    children: &mut HashMap<String, Vec<OrgChartRow>>,
+    depth: Option<u32>,
+) -> OrgChartNode {
+    // Taking the children out also ends the recursion should the rows ever contain a cycle
+    let reports: Vec<OrgChartNode> = children
+        .remove(&row.id)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|child| build_node(child, children, depth.map(|d| d.saturating_sub(1))))
+        .collect();
+
+    let direct_reports = reports.len() as u32;
+    let total_reports = reports.iter().map(|r| 1 + r.total_reports).sum();
+
+    OrgChartNode {
+        id: row.id,
+        first_name: row.first_name,
+        last_name: row.last_name,
+        role: row.role,
+        department_id: row.department_id,
+        department_name: row.department_name,
+        direct_reports,
+        total_reports,
+        reports: if depth == Some(0) {
+            Vec::new()
+        } else {
+            reports
+        },
+    }
+}
+
+/// Follows manager_id upwards from the first row (the employee itself) through the rows of
+/// its managers and stops at the top, or where the chain breaks
+pub fn build_chain(rows: Vec<OrgChartRow>) -> Option<ReportingChain> {
+    let mut rows = rows.into_iter();
+    let employee = rows.next()?;
+    let by_id: HashMap<String, OrgChartRow> = rows.map(|row| (row.id.clone(), row)).collect();
+
+    let mut seen = HashSet::from([employee.id.clone()]);
+    let mut managers = Vec::new();
+    let mut next = employee.manager_id;
+    let broken = loop {
+        let Some(manager_id) = next else {
+            break None;
+        };
+        if seen.contains(&manager_id) {
+            break Some((ChainBreak::Cycle, manager_id));
+        }
+        let Some(manager) = by_id.get(&manager_id) else {
+            break Some((ChainBreak::MissingManager, manager_id));
+        };
+        if !manager.active {
+            break Some((ChainBreak::InactiveManager, manager_id));
+        }
+
+        seen.insert(manager_id);
+        managers.push(ChainManager {
+            id: manager.id.clone(),
+            first_name: manager.first_name.clone(),
+            last_name: manager.last_name.clone(),
+            role: manager.role,
+            department_id: manager.department_id.clone(),
+            department_name: manager.department_name.clone(),
+            level: managers.len() as u32 + 1,
+        });
+        next = manager.manager_id.clone();
+    };
+
+    Some(ReportingChain {
+        employee_id: employee.id,
+        managers,
+        complete: broken.is_none(),
+        broken: broken.as_ref().map(|(reason, _)| *reason),
+        broken_at: broken.map(|(_, manager_id)| manager_id),
+    })
+}
+
+/// Checks that `employee_id` may report to `manager_id`. `load` fetches one employee, the
+/// backends run it inside the same transaction (or lock) as the write that follows, so the walk
+/// up from the new manager sees what the assignment will be committed against.
+pub fn check_manager_assignment<F>(
+    employee_id: &str,
+    manager_id: &str,
+    mut load: F,
+) -> Result<(), ApiError>
+where
+    F: FnMut(&str) -> Result<Option<Employee>, ApiError>,
+{
+    if employee_id == manager_id {
+        return Err(ApiError::Unprocessable(
+            "An employee cannot be their own manager".to_string(),
+        ));
+    }
+    let Some(manager) = load(manager_id)? else {
+        return Err(ApiError::missing_reference("fk_emp_manager"));
+    };
+    if !manager.active {
+        return Err(ApiError::Unprocessable(
+            "manager_id references an inactive employee".to_string(),
+        ));
+    }
+
+    // The employee must not show up above its new manager, or the two would manage each other
+    let mut seen = HashSet::from([manager.id]);
+    let mut next = manager.manager_id;
+    while let Some(id) = next.take() {
+        if id == employee_id {
+            return Err(ApiError::Unprocessable(
+                "manager_id reports to this employee, the assignment would create a cycle"
+                    .to_string(),
+            ));
+        }
+        // A loop further up that doesn't involve the employee is not this request's problem
+        if seen.len() as u32 > MAX_HIERARCHY_DEPTH || !seen.insert(id.clone()) {
+            break;
+        }
+        next = load(&id)?.and_then(|e| e.manager_id);
+    }
+    Ok(())
+}
+
+/// Nests the departments under their parents, siblings ordered by name. Departments whose parent
+/// is unknown become roots, a cycle (never written through the API) is left out.
+pub fn build_department_tree(departments: Vec<Department>) -> Vec<DepartmentNode> {
+    let ids: HashSet<String> = departments.iter().map(|d| d.id.clone()).collect();
+    let mut roots = Vec::new();
+    let mut children: HashMap<String, Vec<Department>> = HashMap::new();
+    for department in departments {
+        match department.parent_id.clone() {
+            Some(parent_id) if ids.contains(&parent_id) => {
+                children.entry(parent_id).or_default().push(department)
+            }
+            _ => roots.push(department),
+        }
+    }
+
+    sort_by_name(&mut roots);
+    roots
+        .into_iter()
+        .map(|root| build_department_node(root, &mut children, 0))
+        .collect()
+}
+
+fn build_department_node(
+    department: Department,
+    children: &mut HashMap<String, Vec<Department>>,
+    level: u32,
+) -> DepartmentNode {
+    let mut subdepartments = children.remove(&department.id).unwrap_or_default();
+    if level >= MAX_HIERARCHY_DEPTH {
+        subdepartments.clear();
+    }
+    sort_by_name(&mut subdepartments);
+
+    DepartmentNode {
+        subdepartments: subdepartments
+            .into_iter()
+            .map(|child| build_department_node(child, children, level + 1))
+            .collect(),
+        id: department.id,
+        name: department.name,
+        head_id: department.head_id,
+    }
+}
+
+fn sort_by_name(departments: &mut [Department]) {
+    departments.sort_by_key(|d| d.name.to_lowercase());
+}
+
+/// Checks that `department_id` may be moved below `parent_id`, `load` works like in
+/// `check_manager_assignment`
+pub fn check_parent_assignment<F>(
+    department_id: &str,
+    parent_id: &str,
+    mut load: F,
+) -> Result<(), ApiError>
+where
+    F: FnMut(&str) -> Result<Option<Department>, ApiError>,
+{
+    if department_id == parent_id {
+        return Err(ApiError::Unprocessable(
+            "A department cannot be its own parent".to_string(),
+        ));
+    }
+    let Some(parent) = load(parent_id)? else {
+        return Err(ApiError::missing_reference("fk_dept_parent"));
+    };
+
+    let mut seen = HashSet::from([parent.id]);
+    let mut next = parent.parent_id;
+    while let Some(id) = next.take() {
+        if id == department_id {
+            return Err(ApiError::Unprocessable(
+                "parent_id is a subdepartment of this department, the move would create a cycle"
+                    .to_string(),
+            ));
+        }
+        if seen.len() as u32 > MAX_HIERARCHY_DEPTH || !seen.insert(id.clone()) {
+            break;
+        }
+        next = load(&id)?.and_then(|d| d.parent_id);
+    }
+    Ok(())
+}
+
+/// Whether `employee` may still lead `department_id`: active, in the department and a
+/// DepartmentHead or Admin. A head that stops meeting this loses the department.
+pub fn can_lead(employee: &Employee, department_id: &str) -> bool {
+    employee.active
+        && employee.department_id.as_deref() == Some(department_id)
+        && matches!(employee.role, Role::Admin | Role::DepartmentHead)
+}
+
+/// Checks that `head` may lead `department_id` and returns the role they end up with. With
+/// `promote` the head becomes a DepartmentHead (Admins keep their role) and is moved into the
+/// department, without it they already have to be a DepartmentHead or Admin of the department.
+pub fn check_head_assignment(
+    department_id: &str,
+    head: Option<&Employee>,
+    promote: bool,
+) -> Result<Role, ApiError> {
+    let Some(head) = head else {
+        return Err(ApiError::missing_reference("fk_dept_head"));
+    };
+    if !head.active {
+        return Err(ApiError::Unprocessable(
+            "head_id references an inactive employee".to_string(),
+        ));
+    }
+
+    if promote {
+        return Ok(match head.role {
+            Role::Admin => Role::Admin,
+            _ => Role::DepartmentHead,
+        });
+    }
+    if head.department_id.as_deref() != Some(department_id) {
+        return Err(ApiError::Unprocessable(
+            "head_id is not in this department, set promote_head to move them".to_string(),
+        ));
+    }
+    if !matches!(head.role, Role::Admin | Role::DepartmentHead) {
+        return Err(ApiError::Unprocessable(
+            "head_id is not a DepartmentHead, set promote_head to promote them".to_string(),
+        ));
+    }
+    Ok(head.role)
+}
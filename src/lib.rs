@@ -1,5 +1,14 @@
 // Library exports for testing
+pub mod auth;
 pub mod db;
+pub mod error;
 pub mod handler;
 pub mod handlers;
+pub mod hierarchy;
+pub mod migrations;
 pub mod models;
+pub mod pagination;
+pub mod repository;
+pub mod retention;
+pub mod seed;
+pub mod validation;
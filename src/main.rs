@@ -1,17 +1,24 @@
 // Main File where the .env data is read and the actix api aka the http server is created
 
-mod db;
-mod handler;
-mod handlers;
-mod models;
-
-use actix_web::{App, HttpServer, web};
-use dotenv::dotenv;
-use handler::{create_user, get_user_by_id, get_users, health};
-use handlers::{
+use actix_web::{App, HttpServer, middleware, web};
+use backend::auth::api_key::API_KEY_HEADER;
+use backend::auth::{
+    AccountPolicy, AuthenticatedUser, JwtConfig, TokenType, account, jwt, require_auth,
+};
+use backend::error::{
+    ApiError, json_error_handler, path_error_handler, problem_instance, query_error_handler,
+    route_not_found,
+};
+use backend::handler::{
+    change_password, create_reset_token, create_user, get_user_by_id, get_users, health,
+};
+use backend::handlers::{
     // Employee endpoints
+    anonymize_employee,
     assign_manager,
     assign_salary_grade,
+    // API key endpoints
+    create_api_key,
     // Department endpoints
     create_department,
     create_employee,
@@ -20,19 +27,43 @@ use handlers::{
     delete_department,
     delete_employee,
     delete_salary_grade,
+    get_api_keys,
     get_department_by_id,
+    get_department_tree,
     get_departments,
     get_employee_by_id,
+    get_employee_org_chart,
     get_employees,
     get_employees_by_department,
+    get_org_chart,
+    get_personal_data,
+    get_reporting_chain,
     get_salary_grade_by_id,
     get_salary_grades,
+    get_subdepartments,
+    // Auth endpoints
+    login,
+    refresh,
+    reset_password,
+    restore_department,
+    restore_employee,
+    restore_salary_grade,
+    revoke_api_key,
+    search_employees,
     update_department,
     update_employee,
     update_salary_grade,
 };
+use backend::models::{CreateEmployeeRequest, Role};
+use backend::repository::{Repositories, Storage};
+use backend::retention::{self, RetentionPolicy};
+use backend::{db, handler, handlers, migrations, models, seed};
+use chrono::Utc;
+use dotenv::dotenv;
 use std::env;
-use utoipa::OpenApi;
+use std::time::Duration;
+use utoipa::openapi::security::{ApiKey, ApiKeyValue, HttpAuthScheme, HttpBuilder, SecurityScheme};
+use utoipa::{Modify, OpenApi};
 use utoipa_swagger_ui::SwaggerUi;
 
 #[derive(OpenApi)]
@@ -43,53 +74,99 @@ use utoipa_swagger_ui::SwaggerUi;
         handler::get_users,
         handler::get_user_by_id,
         handler::create_user,
+        handler::change_password,
+        handler::create_reset_token,
+        // Auth
+        handlers::auth::login,
+        handlers::auth::refresh,
+        handlers::auth::reset_password,
+        // API Keys
+        handlers::api_key::get_api_keys,
+        handlers::api_key::create_api_key,
+        handlers::api_key::revoke_api_key,
         // Employees
         handlers::employee::get_employees,
+        handlers::employee::search_employees,
         handlers::employee::get_employee_by_id,
         handlers::employee::create_employee,
         handlers::employee::update_employee,
         handlers::employee::delete_employee,
+        handlers::employee::restore_employee,
+        handlers::employee::get_personal_data,
+        handlers::employee::anonymize_employee,
         handlers::employee::assign_manager,
         handlers::employee::assign_salary_grade,
+        handlers::employee::get_employee_org_chart,
+        handlers::employee::get_reporting_chain,
+        handlers::employee::get_org_chart,
         handlers::employee::get_employees_by_department,
         // Departments
         handlers::department::get_departments,
+        handlers::department::get_department_tree,
         handlers::department::get_department_by_id,
+        handlers::department::get_subdepartments,
         handlers::department::create_department,
         handlers::department::update_department,
         handlers::department::delete_department,
+        handlers::department::restore_department,
         // Salary Grades
         handlers::salary_grade::get_salary_grades,
         handlers::salary_grade::get_salary_grade_by_id,
         handlers::salary_grade::create_salary_grade,
         handlers::salary_grade::update_salary_grade,
         handlers::salary_grade::delete_salary_grade,
+        handlers::salary_grade::restore_salary_grade,
     ),
     components(
         schemas(
             models::HealthResponse,
             models::User,
             models::CreateUserRequest,
+            models::ChangePasswordRequest,
+            models::ResetPasswordRequest,
+            models::ResetTokenResponse,
             models::Employee,
+            models::EmployeeRestore,
+            models::PersonalData,
+            models::Role,
             models::CreateEmployeeRequest,
             models::UpdateEmployeeRequest,
             models::AssignManagerRequest,
             models::AssignSalaryGradeRequest,
+            models::OrgChartNode,
+            models::ChainManager,
+            models::ChainBreak,
+            models::ReportingChain,
             models::Department,
             models::CreateDepartmentRequest,
             models::UpdateDepartmentRequest,
+            models::DepartmentNode,
+            models::DepartmentDeletion,
             models::SalaryGrade,
             models::CreateSalaryGradeRequest,
             models::UpdateSalaryGradeRequest,
+            models::LoginRequest,
+            models::RefreshRequest,
+            models::TokenResponse,
+            models::ApiScope,
+            models::ApiKey,
+            models::CreateApiKeyRequest,
+            models::CreateApiKeyResponse,
+            models::ProblemDetails,
+            models::FieldError,
         )
     ),
     tags(
         (name = "Health", description = "Health check endpoints"),
-        (name = "Users", description = "User management endpoints"),
+        (name = "Auth", description = "Login and token refresh"),
+        (name = "API Keys", description = "API keys for non-interactive clients"),
+        (name = "Users", description = "User accounts, passwords and reset tokens"),
         (name = "Employees", description = "Employee management endpoints"),
         (name = "Departments", description = "Department management endpoints"),
         (name = "Salary Grades", description = "Salary grade management endpoints")
     ),
+    modifiers(&SecurityAddon),
+    security(("bearerAuth" = []), ("apiKeyAuth" = [])),
     info(
         title = "Personnel Management API",
         version = "1.0.0",
@@ -98,22 +175,287 @@ use utoipa_swagger_ui::SwaggerUi;
 )]
 struct ApiDoc;
 
+// Registers the bearer token and API key schemes referenced by `security` above
+struct SecurityAddon;
+
+impl Modify for SecurityAddon {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "bearerAuth",
+            SecurityScheme::Http(
+                HttpBuilder::new()
+                    .scheme(HttpAuthScheme::Bearer)
+                    .bearer_format("JWT")
+                    .build(),
+            ),
+        );
+        components.add_security_scheme(
+            "apiKeyAuth",
+            SecurityScheme::ApiKey(ApiKey::Header(ApiKeyValue::new(API_KEY_HEADER))),
+        );
+    }
+}
+
+fn number_from_env<T: std::str::FromStr>(name: &str, default: T) -> T {
+    env::var(name)
+        .map(|value| {
+            value
+                .parse::<T>()
+                .unwrap_or_else(|_| panic!("{} must be a number", name))
+        })
+        .unwrap_or(default)
+}
+
+fn connect_database() -> db::DbPool {
+    let database_url = env::var("DATABASE_URL").expect("DATABASE_URL must be set in .env file");
+
+    let defaults = db::PoolConfig::default();
+    // A query timeout of 0 leaves statements unbounded
+    let query_timeout_seconds = number_from_env(
+        "DB_QUERY_TIMEOUT_SECONDS",
+        defaults
+            .query_timeout
+            .map_or(0, |timeout| timeout.as_secs()),
+    );
+    let config = db::PoolConfig {
+        min_connections: number_from_env("DB_POOL_MIN", defaults.min_connections),
+        max_connections: number_from_env("DB_POOL_MAX", defaults.max_connections),
+        acquire_timeout: Duration::from_secs(number_from_env(
+            "DB_ACQUIRE_TIMEOUT_SECONDS",
+            defaults.acquire_timeout.as_secs(),
+        )),
+        query_timeout: (query_timeout_seconds > 0)
+            .then(|| Duration::from_secs(query_timeout_seconds)),
+    };
+    db::create_pool_with(&database_url, &config).expect("Failed to create database pool")
+}
+
+// With in-memory storage there are no user accounts to log in with, so with DEMO_ADMIN_TOKEN=true
+// an admin employee is created and a token for it is handed out on startup
+fn demo_admin_token(repositories: &Repositories, jwt: &JwtConfig) -> Result<String, ApiError> {
+    let admin = CreateEmployeeRequest {
+        first_name: "Demo".to_string(),
+        last_name: "Admin".to_string(),
+        email: "admin@example.com".to_string(),
+        department_id: None,
+        salary_grade_id: None,
+        manager_id: None,
+        role: Some(Role::Admin),
+        hire_date: None,
+    };
+    // Nobody is logged in yet, the employee is created with admin rights
+    let mut user = AuthenticatedUser {
+        employee_id: String::new(),
+        role: Role::Admin,
+        department_id: None,
+    };
+    user.employee_id = repositories.employees.create(&admin, Role::Admin, &user)?;
+
+    jwt.issue(&user, TokenType::Access)
+}
+
+fn io_error(err: impl std::fmt::Display) -> std::io::Error {
+    std::io::Error::other(err.to_string())
+}
+
+// `backend migrate [up | down [steps] | status]`
+fn migrate_command(pool: &db::DbPool, args: &[String]) -> std::io::Result<()> {
+    let mut conn = pool.get_conn().map_err(io_error)?;
+
+    match args.first().map(String::as_str).unwrap_or("up") {
+        "up" => {
+            let applied = migrations::migrate_up(&mut conn).map_err(io_error)?;
+            if applied.is_empty() {
+                println!("Database is up to date");
+            }
+            for migration in applied {
+                println!("Applied {}", migration.name);
+            }
+        }
+        "down" => {
+            let steps = match args.get(1) {
+                Some(steps) => steps
+                    .parse::<usize>()
+                    .map_err(|_| io_error("steps must be a number"))?,
+                None => 1,
+            };
+            for migration in migrations::migrate_down(&mut conn, steps).map_err(io_error)? {
+                println!("Rolled back {}", migration.name);
+            }
+        }
+        "status" => {
+            for (migration, applied) in migrations::status(&mut conn).map_err(io_error)? {
+                let state = if applied { "applied" } else { "pending" };
+                println!("{:<8} {}", state, migration.name);
+            }
+        }
+        other => {
+            return Err(io_error(format!(
+                "Unknown migrate command '{}', expected up, down or status",
+                other
+            )));
+        }
+    }
+    Ok(())
+}
+
+fn retention_policy() -> RetentionPolicy {
+    let defaults = RetentionPolicy::default();
+    RetentionPolicy {
+        retention_years: number_from_env("RETENTION_YEARS", defaults.retention_years),
+        mode: env::var("RETENTION_MODE")
+            .map(|value| {
+                value
+                    .parse()
+                    .unwrap_or_else(|err: String| panic!("{}", err))
+            })
+            .unwrap_or(defaults.mode),
+    }
+}
+
+// `backend purge [--dry-run]`
+fn purge_command(pool: &db::DbPool, args: &[String]) -> std::io::Result<()> {
+    let policy = retention_policy();
+    let employees = Repositories::mysql(pool.clone()).employees;
+
+    match args.first().map(String::as_str) {
+        None => {
+            let summary = retention::purge_expired(employees.get_ref(), &policy, Utc::now())
+                .map_err(io_error)?;
+            println!("{}", summary);
+        }
+        Some("--dry-run") => {
+            let cutoff = policy.cutoff(Utc::now());
+            let due = employees.expired(&cutoff, policy.mode).map_err(io_error)?;
+            println!("{} employees deleted before {} are due", due.len(), cutoff);
+        }
+        Some(other) => {
+            return Err(io_error(format!(
+                "Unknown purge option '{}', expected --dry-run",
+                other
+            )));
+        }
+    }
+    Ok(())
+}
+
+// `backend seed [remove]`
+fn seed_command(pool: &db::DbPool, args: &[String]) -> std::io::Result<()> {
+    match args.first().map(String::as_str) {
+        None => {
+            let password = seed::load(pool).map_err(io_error)?;
+            println!("Sample data loaded");
+            println!(
+                "Admin login: {} / {} (shown only once)",
+                seed::SAMPLE_ADMIN_EMAIL,
+                password
+            );
+        }
+        Some("remove") => {
+            seed::remove(pool).map_err(io_error)?;
+            println!("Sample data removed");
+        }
+        Some(other) => {
+            return Err(io_error(format!(
+                "Unknown seed command '{}', expected remove",
+                other
+            )));
+        }
+    }
+    Ok(())
+}
+
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
     // Load environment variables from .env file
     dotenv().ok();
 
-    let database_url = env::var("DATABASE_URL").expect("DATABASE_URL must be set in .env file");
+    let args: Vec<String> = env::args().skip(1).collect();
+    match args.first().map(String::as_str) {
+        Some("migrate") => return migrate_command(&connect_database(), &args[1..]),
+        Some("purge") => return purge_command(&connect_database(), &args[1..]),
+        Some("seed") => return seed_command(&connect_database(), &args[1..]),
+        Some(other) => return Err(io_error(format!("Unknown command '{}'", other))),
+        None => {}
+    }
+
+    let storage: Storage = env::var("STORAGE")
+        .map(|value| {
+            value
+                .parse()
+                .unwrap_or_else(|err: String| panic!("{}", err))
+        })
+        .unwrap_or_default();
+
     let host = env::var("HOST").unwrap_or_else(|_| "127.0.0.1".to_string());
     let port = env::var("PORT")
         .unwrap_or_else(|_| "8080".to_string())
         .parse::<u16>()
         .expect("PORT must be a valid number");
+    let jwt_secret = env::var("JWT_SECRET").expect("JWT_SECRET must be set in .env file");
+    let jwt_config = JwtConfig::new(&jwt_secret).with_ttls(
+        number_from_env("JWT_ACCESS_TTL_SECONDS", jwt::DEFAULT_ACCESS_TTL_SECONDS),
+        number_from_env("JWT_REFRESH_TTL_SECONDS", jwt::DEFAULT_REFRESH_TTL_SECONDS),
+    );
+    let account_policy = AccountPolicy {
+        max_failed_logins: number_from_env(
+            "LOGIN_MAX_FAILED_ATTEMPTS",
+            account::DEFAULT_MAX_FAILED_LOGINS,
+        ),
+        lockout_seconds: number_from_env("LOGIN_LOCKOUT_SECONDS", account::DEFAULT_LOCKOUT_SECONDS),
+        reset_token_ttl_seconds: number_from_env(
+            "PASSWORD_RESET_TTL_SECONDS",
+            account::DEFAULT_RESET_TOKEN_TTL_SECONDS,
+        ),
+    };
+
+    // User accounts and API keys always live in MySQL, only the personnel data can be kept in memory
+    let (pool, repositories) = match storage {
+        Storage::Mysql => {
+            let pool = connect_database();
+            println!("Database connected successfully!");
+
+            // Opt-in so production deployments decide when the schema changes
+            if env::var("RUN_MIGRATIONS").is_ok_and(|value| value == "true") {
+                let mut conn = pool.get_conn().map_err(io_error)?;
+                for migration in migrations::migrate_up(&mut conn).map_err(io_error)? {
+                    println!("Applied migration {}", migration.name);
+                }
+            }
+
+            let repositories = Repositories::mysql(pool.clone());
+            (Some(pool), repositories)
+        }
+        Storage::Memory => {
+            let repositories = Repositories::memory();
+            println!("Using in-memory storage, data is lost on restart");
+            println!("Login, user and API key routes answer 503, they need MySQL");
+
+            // Development only, anyone who can read the output gets admin access
+            if env::var("DEMO_ADMIN_TOKEN").is_ok_and(|value| value == "true") {
+                let token = demo_admin_token(&repositories, &jwt_config).map_err(io_error)?;
+                println!("Demo admin token: {}", token);
+            }
+            (None, repositories)
+        }
+    };
 
-    // Create database connection pool
-    let pool = db::create_pool(&database_url).expect("Failed to create database pool");
+    // Off unless an interval is set, erasing data is up to each deployment
+    let purge_interval_hours: u64 = number_from_env("PURGE_INTERVAL_HOURS", 0);
+    if purge_interval_hours > 0 {
+        let policy = retention_policy();
+        println!(
+            "Purging employees {} years after deletion every {} hours",
+            policy.retention_years, purge_interval_hours
+        );
+        actix_web::rt::spawn(retention::run_periodically(
+            repositories.employees.clone(),
+            policy,
+            Duration::from_secs(purge_interval_hours * 60 * 60),
+        ));
+    }
 
-    println!("Database connected successfully!");
     println!("Starting server at http://{}:{}", host, port);
     println!("Swagger UI available at http://{}:{}/docs/", host, port);
 
@@ -122,7 +464,21 @@ async fn main() -> std::io::Result<()> {
 
     HttpServer::new(move || {
         App::new()
-            .app_data(web::Data::new(pool.clone()))
+            .configure(|cfg| {
+                if let Some(pool) = &pool {
+                    cfg.app_data(web::Data::new(pool.clone()));
+                }
+                repositories.register(cfg);
+            })
+            .app_data(web::Data::new(jwt_config.clone()))
+            .app_data(web::Data::new(account_policy.clone()))
+            // Malformed requests and handler errors are answered with problem+json
+            .app_data(web::JsonConfig::default().error_handler(json_error_handler))
+            .app_data(web::QueryConfig::default().error_handler(query_error_handler))
+            .app_data(web::PathConfig::default().error_handler(path_error_handler))
+            // Every /api/* route except login, refresh and password reset needs a bearer token or API key
+            .wrap(middleware::from_fn(require_auth))
+            .wrap(middleware::from_fn(problem_instance))
             // Swagger UI
             .service(SwaggerUi::new("/docs/{_:.*}").url("/api-docs/openapi.json", openapi.clone()))
             // Health and legacy endpoints
@@ -130,27 +486,51 @@ async fn main() -> std::io::Result<()> {
             .service(get_users)
             .service(get_user_by_id)
             .service(create_user)
+            .service(change_password)
+            .service(create_reset_token)
+            // Auth endpoints
+            .service(login)
+            .service(refresh)
+            .service(reset_password)
+            // API key endpoints
+            .service(get_api_keys)
+            .service(create_api_key)
+            .service(revoke_api_key)
             // Employee endpoints
             .service(get_employees)
+            // Registered before /{id} so "search" isn't taken for an employee id
+            .service(search_employees)
             .service(get_employee_by_id)
             .service(create_employee)
             .service(update_employee)
             .service(delete_employee)
+            .service(restore_employee)
+            .service(get_personal_data)
+            .service(anonymize_employee)
             .service(assign_manager)
             .service(assign_salary_grade)
             .service(get_employees_by_department)
+            .service(get_employee_org_chart)
+            .service(get_reporting_chain)
+            .service(get_org_chart)
             // Department endpoints
             .service(get_departments)
+            // Registered before /{id} so "tree" isn't taken for a department id
+            .service(get_department_tree)
             .service(get_department_by_id)
+            .service(get_subdepartments)
             .service(create_department)
             .service(update_department)
             .service(delete_department)
+            .service(restore_department)
             // Salary grade endpoints
             .service(get_salary_grades)
             .service(get_salary_grade_by_id)
             .service(create_salary_grade)
             .service(update_salary_grade)
             .service(delete_salary_grade)
+            .service(restore_salary_grade)
+            .default_service(web::to(route_not_found))
     })
     .bind((host.as_str(), port))?
     .run()
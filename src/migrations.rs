@@ -0,0 +1,240 @@
+// Versioned schema migrations - the SQL files in migrations/ are embedded at compile time
+// and applied in version order, `schema_migrations` records which ones have run
+
+use mysql::prelude::*;
+use std::fmt;
+
+pub struct Migration {
+    pub version: u32,
+    pub name: &'static str,
+    pub up: &'static str,
+    pub down: &'static str,
+}
+
+macro_rules! migration {
+    ($version:literal, $name:literal) => {
+        Migration {
+            version: $version,
+            name: $name,
+            up: include_str!(concat!("../migrations/", $name, ".up.sql")),
+            down: include_str!(concat!("../migrations/", $name, ".down.sql")),
+        }
+    };
+}
+
+/// All migrations, oldest first. New ones are appended with the next version number.
+pub const MIGRATIONS: &[Migration] = &[
+    migration!(1, "0001_create_personnel_tables"),
+    migration!(2, "0002_create_auth_tables"),
+    migration!(3, "0003_add_employee_search_indexes"),
+    migration!(4, "0004_add_department_parent"),
+    migration!(5, "0005_add_department_head_fk"),
+    migration!(6, "0006_add_soft_delete_to_departments_and_salary_grades"),
+    migration!(7, "0007_add_employee_anonymized_at"),
+];
+
+// Held while migrating so two instances starting at once don't apply the same script twice
+const LOCK_NAME: &str = "schema_migrations";
+const LOCK_TIMEOUT_SECONDS: u32 = 30;
+
+#[derive(Debug)]
+pub enum MigrationError {
+    Database(mysql::Error),
+    Failed {
+        version: u32,
+        name: &'static str,
+        source: mysql::Error,
+    },
+    // The database has a migration applied that this build does not know about
+    UnknownVersion(u32),
+    Locked,
+}
+
+impl fmt::Display for MigrationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MigrationError::Database(err) => write!(f, "Database error: {}", err),
+            MigrationError::Failed {
+                version,
+                name,
+                source,
+            } => write!(f, "Migration {} ({}) failed: {}", version, name, source),
+            MigrationError::UnknownVersion(version) => write!(
+                f,
+                "Migration {} is applied but not part of this build",
+                version
+            ),
+            MigrationError::Locked => write!(f, "Another process is running migrations"),
+        }
+    }
+}
+
+impl std::error::Error for MigrationError {}
+
+impl From<mysql::Error> for MigrationError {
+    fn from(err: mysql::Error) -> Self {
+        MigrationError::Database(err)
+    }
+}
+
+/// Splits a script into single statements. Statements end with `;` at the end of a line,
+/// `--` comment lines are dropped.
+pub fn split_statements(sql: &str) -> Vec<String> {
+    let mut statements = Vec::new();
+    let mut current = String::new();
+
+    for line in sql.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with("--") {
+            continue;
+        }
+        if !current.is_empty() {
+            current.push('\n');
+        }
+        current.push_str(line);
+
+        if trimmed.ends_with(';') {
+            let statement = current.trim().trim_end_matches(';').trim().to_string();
+            if !statement.is_empty() {
+                statements.push(statement);
+            }
+            current.clear();
+        }
+    }
+
+    let rest = current.trim();
+    if !rest.is_empty() {
+        statements.push(rest.to_string());
+    }
+    statements
+}
+
+/// Migrations that have not been applied yet, in the order they have to run
+pub fn pending(applied: &[u32]) -> Vec<&'static Migration> {
+    MIGRATIONS
+        .iter()
+        .filter(|migration| !applied.contains(&migration.version))
+        .collect()
+}
+
+/// The last `steps` applied migrations, newest first
+pub fn to_roll_back(
+    applied: &[u32],
+    steps: usize,
+) -> Result<Vec<&'static Migration>, MigrationError> {
+    let mut versions = applied.to_vec();
+    versions.sort_unstable_by(|a, b| b.cmp(a));
+
+    versions
+        .into_iter()
+        .take(steps)
+        .map(|version| {
+            MIGRATIONS
+                .iter()
+                .find(|migration| migration.version == version)
+                .ok_or(MigrationError::UnknownVersion(version))
+        })
+        .collect()
+}
+
+fn ensure_table<C: Queryable>(conn: &mut C) -> Result<(), MigrationError> {
+    conn.query_drop(
+        "CREATE TABLE IF NOT EXISTS schema_migrations (
+          version INT UNSIGNED PRIMARY KEY,
+          name VARCHAR(255) NOT NULL,
+          applied_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+        ) ENGINE=InnoDB",
+    )?;
+    Ok(())
+}
+
+pub fn applied_versions<C: Queryable>(conn: &mut C) -> Result<Vec<u32>, MigrationError> {
+    ensure_table(conn)?;
+    Ok(conn.query("SELECT version FROM schema_migrations ORDER BY version")?)
+}
+
+fn run_script<C: Queryable>(
+    conn: &mut C,
+    migration: &'static Migration,
+    script: &str,
+) -> Result<(), MigrationError> {
+    for statement in split_statements(script) {
+        conn.query_drop(&statement)
+            .map_err(|source| MigrationError::Failed {
+                version: migration.version,
+                name: migration.name,
+                source,
+            })?;
+    }
+    Ok(())
+}
+
+fn with_lock<C, T>(
+    conn: &mut C,
+    work: impl FnOnce(&mut C) -> Result<T, MigrationError>,
+) -> Result<T, MigrationError>
+where
+    C: Queryable,
+{
+    let locked: Option<Option<i32>> =
+        conn.exec_first("SELECT GET_LOCK(?, ?)", (LOCK_NAME, LOCK_TIMEOUT_SECONDS))?;
+    if locked.flatten() != Some(1) {
+        return Err(MigrationError::Locked);
+    }
+
+    let result = work(conn);
+    conn.exec_drop("SELECT RELEASE_LOCK(?)", (LOCK_NAME,))?;
+    result
+}
+
+/// Applies every pending migration and returns the ones that ran.
+///
+/// MySQL commits DDL implicitly, so a migration is not atomic. A migration is only recorded
+/// once all of its statements went through. When one fails halfway, the statements before the
+/// failing one stay applied and have to be undone by hand before the next run.
+pub fn migrate_up<C: Queryable>(conn: &mut C) -> Result<Vec<&'static Migration>, MigrationError> {
+    with_lock(conn, |conn| {
+        let applied = applied_versions(conn)?;
+        let pending = pending(&applied);
+
+        for migration in &pending {
+            run_script(conn, migration, migration.up)?;
+            conn.exec_drop(
+                "INSERT INTO schema_migrations (version, name) VALUES (?, ?)",
+                (migration.version, migration.name),
+            )?;
+        }
+        Ok(pending)
+    })
+}
+
+/// Rolls back the last `steps` applied migrations and returns the ones that were undone
+pub fn migrate_down<C: Queryable>(
+    conn: &mut C,
+    steps: usize,
+) -> Result<Vec<&'static Migration>, MigrationError> {
+    with_lock(conn, |conn| {
+        let applied = applied_versions(conn)?;
+        let rolled_back = to_roll_back(&applied, steps)?;
+
+        for migration in &rolled_back {
+            run_script(conn, migration, migration.down)?;
+            conn.exec_drop(
+                "DELETE FROM schema_migrations WHERE version = ?",
+                (migration.version,),
+            )?;
+        }
+        Ok(rolled_back)
+    })
+}
+
+/// Every known migration with whether it has been applied
+pub fn status<C: Queryable>(
+    conn: &mut C,
+) -> Result<Vec<(&'static Migration, bool)>, MigrationError> {
+    let applied = applied_versions(conn)?;
+    Ok(MIGRATIONS
+        .iter()
+        .map(|migration| (migration, applied.contains(&migration.version)))
+        .collect())
+}
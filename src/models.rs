@@ -1,8 +1,14 @@
 // Models used for Api and database connection (everything that has option, can be set but doesn't
 // have to and could stay null or not set if wanted)
 
+use crate::validation;
+use mysql::prelude::FromValue;
+use mysql::{FromValueError, Value};
 use serde::{Deserialize, Serialize};
-use utoipa::ToSchema;
+use std::fmt;
+use std::str::FromStr;
+use utoipa::{IntoParams, ToSchema};
+use validator::Validate;
 
 #[derive(Serialize, Deserialize, ToSchema)]
 pub struct HealthResponse {
@@ -10,17 +16,58 @@ pub struct HealthResponse {
     pub message: String,
 }
 
-#[derive(Serialize, Deserialize, ToSchema)]
+// Login account, password hash and reset token are deliberately not part of this type
+#[derive(Serialize, Deserialize, Debug, Clone, Default, ToSchema)]
 pub struct User {
     pub id: i32,
     pub name: String,
     pub email: String,
+    /// Employee the account logs in as
+    pub employee_id: Option<String>,
+    pub failed_login_attempts: u32,
+    /// Set while the account is locked after too many failed logins
+    pub locked_until: Option<String>,
 }
 
-#[derive(Serialize, Deserialize, ToSchema)]
+#[derive(Serialize, Deserialize, ToSchema, Validate)]
 pub struct CreateUserRequest {
+    #[validate(
+        length(max = 255, message = "must be at most 255 characters"),
+        custom(function = "validation::not_blank")
+    )]
     pub name: String,
+    #[validate(
+        email(message = "must be a valid email address"),
+        length(max = 255, message = "must be at most 255 characters")
+    )]
     pub email: String,
+    #[validate(custom(function = "validation::uuid"))]
+    pub employee_id: Option<String>,
+    /// Without a password the account can only be activated through a reset token
+    #[validate(length(min = 8, max = 128, message = "must be between 8 and 128 characters"))]
+    pub password: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Validate)]
+pub struct ChangePasswordRequest {
+    pub current_password: String,
+    #[validate(length(min = 8, max = 128, message = "must be between 8 and 128 characters"))]
+    pub new_password: String,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Validate)]
+pub struct ResetPasswordRequest {
+    pub reset_token: String,
+    #[validate(length(min = 8, max = 128, message = "must be between 8 and 128 characters"))]
+    pub new_password: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+pub struct ResetTokenResponse {
+    /// Shown only once, hand it to the user out of band
+    pub reset_token: String,
+    /// Lifetime of the reset token in seconds
+    pub expires_in: i64,
 }
 
 // Department Models
@@ -29,20 +76,91 @@ pub struct Department {
     pub id: String,
     pub name: String,
     pub head_id: Option<String>,
+    /// Department this one belongs to, None for a top level division
+    pub parent_id: Option<String>,
+    /// Set while the department is deleted, it can be restored until then
+    pub deleted_at: Option<String>,
     pub created_at: Option<String>,
     pub updated_at: Option<String>,
 }
 
-#[derive(Serialize, Deserialize, ToSchema)]
+#[derive(Serialize, Deserialize, ToSchema, Validate)]
 pub struct CreateDepartmentRequest {
+    #[validate(
+        length(max = 255, message = "must be at most 255 characters"),
+        custom(function = "validation::not_blank")
+    )]
     pub name: String,
+    #[validate(custom(function = "validation::uuid"))]
     pub head_id: Option<String>,
+    #[validate(custom(function = "validation::uuid"))]
+    pub parent_id: Option<String>,
+    /// Make head_id a DepartmentHead and move them into the department
+    pub promote_head: Option<bool>,
 }
 
-#[derive(Serialize, Deserialize, ToSchema)]
+#[derive(Serialize, Deserialize, ToSchema, Validate)]
 pub struct UpdateDepartmentRequest {
+    #[validate(
+        length(max = 255, message = "must be at most 255 characters"),
+        custom(function = "validation::not_blank")
+    )]
     pub name: Option<String>,
+    #[validate(custom(function = "validation::uuid"))]
+    pub head_id: Option<String>,
+    #[validate(custom(function = "validation::uuid"))]
+    pub parent_id: Option<String>,
+    /// Make head_id a DepartmentHead and move them into the department
+    pub promote_head: Option<bool>,
+}
+
+/// Department with the departments below it, as returned by the department tree
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct DepartmentNode {
+    pub id: String,
+    pub name: String,
     pub head_id: Option<String>,
+    pub subdepartments: Vec<DepartmentNode>,
+}
+
+/// Query string of the employees by department endpoint
+#[derive(Serialize, Deserialize, Debug, Default, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct DepartmentEmployeesQuery {
+    /// Also list the employees of all subdepartments, at any depth
+    pub recursive: Option<bool>,
+}
+
+/// Query string of `DELETE /api/departments/{id}`, needed while active employees are left
+#[derive(Serialize, Deserialize, Debug, Default, IntoParams, Validate)]
+#[into_params(parameter_in = Query)]
+pub struct DeleteDepartmentQuery {
+    /// Move the active employees of the department here before deleting it
+    #[validate(custom(function = "validation::uuid"))]
+    pub reassign_to: Option<String>,
+    /// Delete anyway and leave the active employees without a department
+    pub force: Option<bool>,
+}
+
+/// Deleted department and what became of the records pointing at it
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
+pub struct DepartmentDeletion {
+    /// The department, marked deleted
+    pub department: Department,
+    /// Employees moved to `reassign_to`
+    pub reassigned_employees: u64,
+    /// Employees left without a department
+    pub unassigned_employees: u64,
+    /// Subdepartments that became top level departments
+    pub detached_subdepartments: u64,
+}
+
+/// Query string of the department and salary grade list and get endpoints
+#[derive(Serialize, Deserialize, Debug, Default, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct IncludeDeletedQuery {
+    /// Also return deleted rows
+    pub include_deleted: Option<bool>,
 }
 
 // Salary Grade Models
@@ -52,23 +170,114 @@ pub struct SalaryGrade {
     pub code: String,
     pub base_salary: f64,
     pub description: Option<String>,
+    /// Set while the salary grade is deleted, it can be restored until then
+    pub deleted_at: Option<String>,
     pub created_at: Option<String>,
 }
 
-#[derive(Serialize, Deserialize, ToSchema)]
+#[derive(Serialize, Deserialize, ToSchema, Validate)]
 pub struct CreateSalaryGradeRequest {
+    #[validate(
+        length(max = 50, message = "must be at most 50 characters"),
+        custom(function = "validation::not_blank")
+    )]
     pub code: String,
+    #[validate(range(
+        exclusive_min = 0.0,
+        max = validation::MAX_SALARY,
+        message = "must be greater than 0 and fit into DECIMAL(12,2)"
+    ))]
     pub base_salary: f64,
     pub description: Option<String>,
 }
 
-#[derive(Serialize, Deserialize, ToSchema)]
+#[derive(Serialize, Deserialize, ToSchema, Validate)]
 pub struct UpdateSalaryGradeRequest {
+    #[validate(
+        length(max = 50, message = "must be at most 50 characters"),
+        custom(function = "validation::not_blank")
+    )]
     pub code: Option<String>,
+    #[validate(range(
+        exclusive_min = 0.0,
+        max = validation::MAX_SALARY,
+        message = "must be greater than 0 and fit into DECIMAL(12,2)"
+    ))]
     pub base_salary: Option<f64>,
     pub description: Option<String>,
 }
 
+// Role Models
+
+/// Employee role, mirrors the ENUM of the employees.role column
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash, Default, ToSchema)]
+pub enum Role {
+    Admin,
+    DepartmentHead,
+    DeputyHead,
+    #[default]
+    Employee,
+}
+
+impl Role {
+    pub const ALL: [Role; 4] = [
+        Role::Admin,
+        Role::DepartmentHead,
+        Role::DeputyHead,
+        Role::Employee,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::Admin => "Admin",
+            Role::DepartmentHead => "DepartmentHead",
+            Role::DeputyHead => "DeputyHead",
+            Role::Employee => "Employee",
+        }
+    }
+}
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Role {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Role::ALL
+            .into_iter()
+            .find(|role| role.as_str() == s)
+            .ok_or_else(|| format!("unknown role: {}", s))
+    }
+}
+
+impl From<Role> for Value {
+    fn from(role: Role) -> Self {
+        Value::from(role.as_str())
+    }
+}
+
+impl TryFrom<Value> for Role {
+    type Error = FromValueError;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        match value {
+            Value::Bytes(ref bytes) => std::str::from_utf8(bytes)
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .ok_or(FromValueError(value)),
+            other => Err(FromValueError(other)),
+        }
+    }
+}
+
+impl FromValue for Role {
+    type Intermediate = Role;
+}
+
 // Employee Models
 #[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
 pub struct Employee {
@@ -79,45 +288,359 @@ pub struct Employee {
     pub department_id: Option<String>,
     pub salary_grade_id: Option<String>,
     pub manager_id: Option<String>,
-    pub role: String,
+    pub role: Role,
     pub hire_date: Option<String>,
     pub active: bool,
     pub deleted_at: Option<String>,
+    /// Set once name and email have been replaced by pseudonyms, such an employee can't be
+    /// changed or restored anymore
+    pub anonymized_at: Option<String>,
     pub created_at: Option<String>,
     pub updated_at: Option<String>,
 }
 
-#[derive(Serialize, Deserialize, ToSchema)]
+/// Everything stored about an employee, for access requests under the GDPR. There is no audit
+/// log, the timestamps on the record are all the history kept.
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
+pub struct PersonalData {
+    pub employee: Employee,
+    pub department_name: Option<String>,
+    /// First and last name of the manager
+    pub manager_name: Option<String>,
+    pub salary_grade_code: Option<String>,
+    /// Names of the departments the employee is head of
+    pub headed_departments: Vec<String>,
+    /// Login account of the employee, null without one
+    pub account: Option<User>,
+    /// API keys the employee created
+    pub api_keys: Vec<ApiKey>,
+}
+
+/// Restored employee and the references that had to be cleared on the way
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
+pub struct EmployeeRestore {
+    pub employee: Employee,
+    /// Fields out of department_id, manager_id and salary_grade_id that were set to null, their
+    /// department or salary grade is gone or deleted, or their manager is gone or inactive
+    pub nulled_references: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Validate)]
 pub struct CreateEmployeeRequest {
+    #[validate(
+        length(max = 100, message = "must be at most 100 characters"),
+        custom(function = "validation::not_blank")
+    )]
     pub first_name: String,
+    #[validate(
+        length(max = 100, message = "must be at most 100 characters"),
+        custom(function = "validation::not_blank")
+    )]
     pub last_name: String,
+    #[validate(
+        email(message = "must be a valid email address"),
+        length(max = 255, message = "must be at most 255 characters")
+    )]
     pub email: String,
+    #[validate(custom(function = "validation::uuid"))]
     pub department_id: Option<String>,
+    #[validate(custom(function = "validation::uuid"))]
     pub salary_grade_id: Option<String>,
+    #[validate(custom(function = "validation::uuid"))]
     pub manager_id: Option<String>,
-    pub role: Option<String>,
+    pub role: Option<Role>,
+    #[validate(custom(function = "validation::iso_date"))]
     pub hire_date: Option<String>,
 }
 
-#[derive(Serialize, Deserialize, ToSchema)]
+#[derive(Serialize, Deserialize, ToSchema, Validate)]
 pub struct UpdateEmployeeRequest {
+    #[validate(
+        length(max = 100, message = "must be at most 100 characters"),
+        custom(function = "validation::not_blank")
+    )]
     pub first_name: Option<String>,
+    #[validate(
+        length(max = 100, message = "must be at most 100 characters"),
+        custom(function = "validation::not_blank")
+    )]
     pub last_name: Option<String>,
+    #[validate(
+        email(message = "must be a valid email address"),
+        length(max = 255, message = "must be at most 255 characters")
+    )]
     pub email: Option<String>,
+    #[validate(custom(function = "validation::uuid"))]
     pub department_id: Option<String>,
+    #[validate(custom(function = "validation::uuid"))]
     pub salary_grade_id: Option<String>,
+    #[validate(custom(function = "validation::uuid"))]
     pub manager_id: Option<String>,
-    pub role: Option<String>,
+    pub role: Option<Role>,
+    #[validate(custom(function = "validation::iso_date"))]
     pub hire_date: Option<String>,
     pub active: Option<bool>,
 }
 
-#[derive(Serialize, Deserialize, ToSchema)]
+/// Query string of `GET /api/employees`
+#[derive(Serialize, Deserialize, Debug, Default, IntoParams, Validate)]
+#[into_params(parameter_in = Query)]
+pub struct EmployeeListQuery {
+    /// Page number, starting at 1
+    #[validate(range(min = 1, message = "must be at least 1"))]
+    pub page: Option<u32>,
+    /// Employees per page, 20 by default
+    #[validate(range(min = 1, max = 100, message = "must be between 1 and 100"))]
+    pub per_page: Option<u32>,
+    /// Comma separated fields, `-` in front sorts descending, e.g. `last_name,-hire_date`.
+    /// Allowed: first_name, last_name, email, role, hire_date, created_at
+    pub sort: Option<String>,
+    #[validate(custom(function = "validation::uuid"))]
+    pub department_id: Option<String>,
+    #[validate(custom(function = "validation::uuid"))]
+    pub manager_id: Option<String>,
+    #[validate(custom(function = "validation::uuid"))]
+    pub salary_grade_id: Option<String>,
+    pub role: Option<Role>,
+    /// Hired on or after this date (YYYY-MM-DD)
+    #[validate(custom(function = "validation::iso_date"))]
+    pub hired_from: Option<String>,
+    /// Hired on or before this date (YYYY-MM-DD)
+    #[validate(custom(function = "validation::iso_date"))]
+    pub hired_to: Option<String>,
+    /// Only active (true) or only inactive (false) employees, active ones if not set
+    pub active: Option<bool>,
+    /// List active and inactive employees together, ignored when `active` is set
+    pub include_inactive: Option<bool>,
+}
+
+/// Query string of `GET /api/employees/search`
+#[derive(Serialize, Deserialize, Debug, Default, IntoParams, Validate)]
+#[into_params(parameter_in = Query)]
+pub struct EmployeeSearchQuery {
+    /// Start of a first name, last name or email, several words narrow the search down
+    #[validate(
+        length(max = 100, message = "must be at most 100 characters"),
+        custom(function = "validation::not_blank")
+    )]
+    pub q: String,
+    /// Page number, starting at 1
+    #[validate(range(min = 1, message = "must be at least 1"))]
+    pub page: Option<u32>,
+    /// Employees per page, 20 by default
+    #[validate(range(min = 1, max = 100, message = "must be between 1 and 100"))]
+    pub per_page: Option<u32>,
+    /// Also search inactive employees
+    pub include_inactive: Option<bool>,
+}
+
+/// Query string of the org chart endpoints
+#[derive(Serialize, Deserialize, Debug, Default, IntoParams, Validate)]
+#[into_params(parameter_in = Query)]
+pub struct OrgChartQuery {
+    /// Levels of reports to include below each root, all of them if not set
+    #[validate(range(max = 100, message = "must be at most 100"))]
+    pub depth: Option<u32>,
+}
+
+/// Employee in the org chart with the people reporting to them
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct OrgChartNode {
+    pub id: String,
+    pub first_name: String,
+    pub last_name: String,
+    pub role: Role,
+    pub department_id: Option<String>,
+    pub department_name: Option<String>,
+    /// Employees whose manager this is
+    pub direct_reports: u32,
+    /// Direct and indirect reports, also those cut off by `depth`
+    pub total_reports: u32,
+    pub reports: Vec<OrgChartNode>,
+}
+
+/// Manager in someone's reporting chain, `level` 1 is the direct manager
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct ChainManager {
+    pub id: String,
+    pub first_name: String,
+    pub last_name: String,
+    pub role: Role,
+    pub department_id: Option<String>,
+    pub department_name: Option<String>,
+    pub level: u32,
+}
+
+/// Why a reporting chain ends before reaching a top-level employee
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ChainBreak {
+    /// The next manager is no longer active
+    InactiveManager,
+    /// manager_id points to an employee that doesn't exist
+    MissingManager,
+    /// The next manager already appeared further down the chain
+    Cycle,
+}
+
+/// Managers above an employee, ordered from the direct manager up
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct ReportingChain {
+    pub employee_id: String,
+    pub managers: Vec<ChainManager>,
+    /// True if the chain ends at an employee without a manager
+    pub complete: bool,
+    pub broken: Option<ChainBreak>,
+    /// manager_id at which the chain breaks
+    pub broken_at: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Validate)]
 pub struct AssignManagerRequest {
+    #[validate(custom(function = "validation::uuid"))]
     pub manager_id: String,
 }
 
-#[derive(Serialize, Deserialize, ToSchema)]
+#[derive(Serialize, Deserialize, ToSchema, Validate)]
 pub struct AssignSalaryGradeRequest {
+    #[validate(custom(function = "validation::uuid"))]
     pub salary_grade_id: String,
 }
+
+// Auth Models
+#[derive(Serialize, Deserialize, ToSchema, Validate)]
+pub struct LoginRequest {
+    #[validate(email(message = "must be a valid email address"))]
+    pub email: String,
+    #[validate(length(min = 1, message = "must not be empty"))]
+    pub password: String,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct RefreshRequest {
+    pub refresh_token: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+pub struct TokenResponse {
+    pub access_token: String,
+    pub refresh_token: String,
+    pub token_type: String,
+    /// Lifetime of the access token in seconds
+    pub expires_in: i64,
+}
+
+// API Key Models
+
+/// Permission granted to an API key, `<resource>:<read|write>`
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash, ToSchema)]
+pub enum ApiScope {
+    #[serde(rename = "employees:read")]
+    EmployeesRead,
+    #[serde(rename = "employees:write")]
+    EmployeesWrite,
+    #[serde(rename = "departments:read")]
+    DepartmentsRead,
+    #[serde(rename = "departments:write")]
+    DepartmentsWrite,
+    #[serde(rename = "salary_grades:read")]
+    SalaryGradesRead,
+    #[serde(rename = "salary_grades:write")]
+    SalaryGradesWrite,
+}
+
+impl ApiScope {
+    pub const ALL: [ApiScope; 6] = [
+        ApiScope::EmployeesRead,
+        ApiScope::EmployeesWrite,
+        ApiScope::DepartmentsRead,
+        ApiScope::DepartmentsWrite,
+        ApiScope::SalaryGradesRead,
+        ApiScope::SalaryGradesWrite,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ApiScope::EmployeesRead => "employees:read",
+            ApiScope::EmployeesWrite => "employees:write",
+            ApiScope::DepartmentsRead => "departments:read",
+            ApiScope::DepartmentsWrite => "departments:write",
+            ApiScope::SalaryGradesRead => "salary_grades:read",
+            ApiScope::SalaryGradesWrite => "salary_grades:write",
+        }
+    }
+}
+
+impl fmt::Display for ApiScope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ApiScope {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ApiScope::ALL
+            .into_iter()
+            .find(|scope| scope.as_str() == s)
+            .ok_or_else(|| format!("unknown scope: {}", s))
+    }
+}
+
+// The key itself is never part of this type, only its prefix for recognising it
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
+pub struct ApiKey {
+    pub id: String,
+    pub name: String,
+    /// First characters of the key, enough to tell keys apart
+    pub key_prefix: String,
+    pub scopes: Vec<ApiScope>,
+    /// Employee who created the key
+    pub created_by: Option<String>,
+    pub created_at: Option<String>,
+    pub last_used_at: Option<String>,
+    pub revoked_at: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Validate)]
+pub struct CreateApiKeyRequest {
+    #[validate(
+        length(max = 255, message = "must be at most 255 characters"),
+        custom(function = "validation::not_blank")
+    )]
+    pub name: String,
+    #[validate(length(min = 1, message = "must contain at least one scope"))]
+    pub scopes: Vec<ApiScope>,
+}
+
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+pub struct CreateApiKeyResponse {
+    pub id: String,
+    pub name: String,
+    pub key_prefix: String,
+    pub scopes: Vec<ApiScope>,
+    /// Shown only once, send it in the X-Api-Key header
+    pub key: String,
+}
+
+// Error Models (RFC 7807 problem details)
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
+pub struct ProblemDetails {
+    #[serde(rename = "type")]
+    pub problem_type: String,
+    pub title: String,
+    pub status: u16,
+    pub detail: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instance: Option<String>,
+    pub code: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub errors: Option<Vec<FieldError>>,
+}
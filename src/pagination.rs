@@ -0,0 +1,132 @@
+// Paging and sorting shared by the list endpoints. The body stays a plain JSON array and the
+// totals travel in headers, but a request without `page` gets the first page only, clients that
+// expect every row have to follow the `Link` header.
+
+use crate::error::ApiError;
+use actix_web::{HttpRequest, HttpResponse};
+use serde::Serialize;
+use std::str::FromStr;
+
+pub const DEFAULT_PER_PAGE: u32 = 20;
+pub const MAX_PER_PAGE: u32 = 100;
+
+pub const TOTAL_COUNT_HEADER: &str = "X-Total-Count";
+pub const PAGE_HEADER: &str = "X-Page";
+pub const PER_PAGE_HEADER: &str = "X-Per-Page";
+
+/// 1-based page number and page size, already clamped to sane values
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PageRequest {
+    pub page: u32,
+    pub per_page: u32,
+}
+
+impl Default for PageRequest {
+    fn default() -> Self {
+        PageRequest {
+            page: 1,
+            per_page: DEFAULT_PER_PAGE,
+        }
+    }
+}
+
+impl PageRequest {
+    pub fn new(page: Option<u32>, per_page: Option<u32>) -> Self {
+        PageRequest {
+            page: page.unwrap_or(1).max(1),
+            per_page: per_page.unwrap_or(DEFAULT_PER_PAGE).clamp(1, MAX_PER_PAGE),
+        }
+    }
+
+    /// Rows to skip before this page starts
+    pub fn offset(&self) -> u64 {
+        u64::from(self.page - 1) * u64::from(self.per_page)
+    }
+
+    /// Cuts this page out of an already filtered and sorted list
+    pub fn slice<T>(&self, items: Vec<T>) -> Page<T> {
+        let total = items.len() as u64;
+        let items = items
+            .into_iter()
+            .skip(self.offset() as usize)
+            .take(self.per_page as usize)
+            .collect();
+        Page { items, total }
+    }
+
+    /// Number of the last page, at least 1 even for an empty result
+    pub fn last_page(&self, total: u64) -> u64 {
+        total.div_ceil(u64::from(self.per_page)).max(1)
+    }
+}
+
+/// One page of results together with the number of matches across all pages
+#[derive(Debug, Clone, PartialEq)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub total: u64,
+}
+
+/// Sort field with direction, parsed from `name` or `-name`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SortKey<F> {
+    pub field: F,
+    pub descending: bool,
+}
+
+/// Parses a comma separated `sort` parameter like `last_name,-hire_date`
+pub fn parse_sort<F: FromStr>(value: &str) -> Result<Vec<SortKey<F>>, ApiError> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let (name, descending) = match part.strip_prefix('-') {
+                Some(name) => (name, true),
+                None => (part, false),
+            };
+            let field = name.parse().map_err(|_| {
+                ApiError::invalid_field("sort", &format!("cannot sort by '{}'", name))
+            })?;
+            Ok(SortKey { field, descending })
+        })
+        .collect()
+}
+
+/// 200 with the page as JSON array plus total count, paging and RFC 8288 `Link` headers
+pub fn paged_response<T: Serialize>(
+    req: &HttpRequest,
+    page: PageRequest,
+    result: Page<T>,
+) -> HttpResponse {
+    let last = page.last_page(result.total);
+    let current = u64::from(page.page);
+
+    let mut links = vec![link(req, 1, "first"), link(req, last, "last")];
+    if current > 1 {
+        links.push(link(req, (current - 1).min(last), "prev"));
+    }
+    if current < last {
+        links.push(link(req, current + 1, "next"));
+    }
+
+    HttpResponse::Ok()
+        .insert_header((TOTAL_COUNT_HEADER, result.total.to_string()))
+        .insert_header((PAGE_HEADER, page.page.to_string()))
+        .insert_header((PER_PAGE_HEADER, page.per_page.to_string()))
+        .insert_header(("Link", links.join(", ")))
+        .json(result.items)
+}
+
+// Same path and query as the request, only the page number swapped out
+fn link(req: &HttpRequest, page: u64, rel: &str) -> String {
+    let mut params: Vec<&str> = req
+        .query_string()
+        .split('&')
+        .filter(|param| !param.is_empty() && !param.starts_with("page="))
+        .collect();
+    let page_param = format!("page={}", page);
+    params.push(&page_param);
+
+    format!("<{}?{}>; rel=\"{}\"", req.path(), params.join("&"), rel)
+}
@@ -0,0 +1,1101 @@
+// In-memory implementation of the repositories for tests and local demos without MySQL.
+// It enforces the unique and foreign keys of the migrations with the same client errors.
+
+use super::{
+    DepartmentEmployees, DepartmentRepository, EmployeeFilter, EmployeeRepository,
+    EmployeeSortField, PurgeMode, PurgedReferences, SalaryGradeRepository,
+    check_department_deletion, check_not_anonymized, deleted_reference, not_found, pseudonyms,
+    purge_due,
+};
+use crate::auth::AuthenticatedUser;
+use crate::error::ApiError;
+use crate::hierarchy::{
+    MAX_HIERARCHY_DEPTH, OrgChartRow, can_lead, check_head_assignment, check_manager_assignment,
+    check_parent_assignment,
+};
+use crate::models::*;
+use crate::pagination::{Page, PageRequest, SortKey};
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex, MutexGuard};
+use uuid::Uuid;
+
+#[derive(Default)]
+struct Tables {
+    departments: Vec<Department>,
+    salary_grades: Vec<SalaryGrade>,
+    employees: Vec<Employee>,
+}
+
+impl Tables {
+    // Departments the employee at `index` can no longer lead are left without a head
+    fn clear_stale_heads(&mut self, index: usize, timestamp: &str) {
+        let employee = &self.employees[index];
+        for department in self.departments.iter_mut() {
+            if department.head_id.as_deref() == Some(employee.id.as_str())
+                && !can_lead(employee, &department.id)
+            {
+                department.head_id = None;
+                department.updated_at = Some(timestamp.to_string());
+            }
+        }
+    }
+
+    fn employee_index(&self, id: &str) -> Result<usize, ApiError> {
+        self.employees
+            .iter()
+            .position(|e| e.id == id)
+            .ok_or_else(|| not_found("Employee"))
+    }
+
+    // Deleted departments and salary grades are only found by restore
+    fn department_index(&self, id: &str) -> Result<usize, ApiError> {
+        self.departments
+            .iter()
+            .position(|d| d.id == id && d.deleted_at.is_none())
+            .ok_or_else(|| not_found("Department"))
+    }
+
+    fn salary_grade_index(&self, id: &str) -> Result<usize, ApiError> {
+        self.salary_grades
+            .iter()
+            .position(|g| g.id == id && g.deleted_at.is_none())
+            .ok_or_else(|| not_found("Salary grade"))
+    }
+
+    fn check_department_reference(&self, field: &str, id: &str, fk: &str) -> Result<(), ApiError> {
+        match self.departments.iter().find(|d| d.id == id) {
+            None => Err(ApiError::missing_reference(fk)),
+            Some(d) if d.deleted_at.is_some() => Err(deleted_reference(field, "department")),
+            Some(_) => Ok(()),
+        }
+    }
+
+    fn check_employee_references(
+        &self,
+        department_id: Option<&str>,
+        salary_grade_id: Option<&str>,
+        manager_id: Option<&str>,
+    ) -> Result<(), ApiError> {
+        if let Some(id) = department_id {
+            self.check_department_reference("department_id", id, "fk_emp_department")?;
+        }
+        if let Some(id) = salary_grade_id {
+            match self.salary_grades.iter().find(|g| g.id == id) {
+                None => return Err(ApiError::missing_reference("fk_emp_salary")),
+                Some(g) if g.deleted_at.is_some() => {
+                    return Err(deleted_reference("salary_grade_id", "salary grade"));
+                }
+                Some(_) => {}
+            }
+        }
+        if let Some(id) = manager_id
+            && !self.employees.iter().any(|e| e.id == id)
+        {
+            return Err(ApiError::missing_reference("fk_emp_manager"));
+        }
+        Ok(())
+    }
+
+    // Holding the store lock makes the check and the following write one atomic step
+    fn check_manager(&self, employee_id: &str, manager_id: &str) -> Result<(), ApiError> {
+        check_manager_assignment(employee_id, manager_id, |id| {
+            Ok(self.employees.iter().find(|e| e.id == id).cloned())
+        })
+    }
+
+    // Unknown managers are left to check_manager, which answers with 422
+    fn check_manager_scope(
+        &self,
+        caller: &AuthenticatedUser,
+        manager_id: &str,
+    ) -> Result<(), ApiError> {
+        match self.employees.iter().find(|e| e.id == manager_id) {
+            Some(manager) => caller.ensure_can_assign_manager(manager),
+            None => Ok(()),
+        }
+    }
+
+    fn check_parent(&self, department_id: &str, parent_id: &str) -> Result<(), ApiError> {
+        check_parent_assignment(department_id, parent_id, |id| {
+            Ok(self.departments.iter().find(|d| d.id == id).cloned())
+        })?;
+        self.check_department_reference("parent_id", parent_id, "fk_dept_parent")
+    }
+
+    fn check_head(
+        &self,
+        department_id: &str,
+        head_id: &str,
+        promote: bool,
+    ) -> Result<Role, ApiError> {
+        let head = self.employees.iter().find(|e| e.id == head_id);
+        check_head_assignment(department_id, head, promote)
+    }
+
+    // Makes a checked head the head of `department_id`. The previous head steps down to Employee
+    // and a department the new head led so far is left without one.
+    fn set_head(&mut self, department_id: &str, head_id: &str, role: Role) {
+        let timestamp = now();
+        for department in self.departments.iter_mut() {
+            if department.id == department_id {
+                let previous = department.head_id.replace(head_id.to_string());
+                if let Some(previous) = previous.filter(|p| p != head_id)
+                    && let Some(employee) = self.employees.iter_mut().find(|e| e.id == previous)
+                    && employee.role == Role::DepartmentHead
+                {
+                    employee.role = Role::Employee;
+                    employee.updated_at = Some(timestamp.clone());
+                }
+            } else if department.head_id.as_deref() == Some(head_id) {
+                department.head_id = None;
+                department.updated_at = Some(timestamp.clone());
+            }
+        }
+
+        if let Some(head) = self.employees.iter_mut().find(|e| e.id == head_id)
+            && (head.role != role || head.department_id.as_deref() != Some(department_id))
+        {
+            head.role = role;
+            head.department_id = Some(department_id.to_string());
+            head.updated_at = Some(timestamp);
+        }
+    }
+
+    // The department and every department below it, the visited set guards against cycles
+    fn department_subtree(&self, id: &str) -> HashSet<String> {
+        let mut subtree = HashSet::from([id.to_string()]);
+        let mut level = vec![id.to_string()];
+        for _ in 0..MAX_HIERARCHY_DEPTH {
+            level = self
+                .departments
+                .iter()
+                .filter(|d| d.parent_id.as_ref().is_some_and(|p| level.contains(p)))
+                .filter(|d| subtree.insert(d.id.clone()))
+                .map(|d| d.id.clone())
+                .collect();
+            if level.is_empty() {
+                break;
+            }
+        }
+        subtree
+    }
+
+    fn check_email(&self, email: &str, own_id: Option<&str>) -> Result<(), ApiError> {
+        if self
+            .employees
+            .iter()
+            .any(|e| same_key(&e.email, email) && Some(e.id.as_str()) != own_id)
+        {
+            return Err(ApiError::duplicate_key("employees.email"));
+        }
+        Ok(())
+    }
+
+    fn check_department_name(&self, name: &str, own_id: Option<&str>) -> Result<(), ApiError> {
+        if self
+            .departments
+            .iter()
+            .any(|d| same_key(&d.name, name) && Some(d.id.as_str()) != own_id)
+        {
+            return Err(ApiError::duplicate_key("departments.name"));
+        }
+        Ok(())
+    }
+
+    fn check_salary_grade_code(&self, code: &str, own_id: Option<&str>) -> Result<(), ApiError> {
+        if self
+            .salary_grades
+            .iter()
+            .any(|g| same_key(&g.code, code) && Some(g.id.as_str()) != own_id)
+        {
+            return Err(ApiError::duplicate_key("salary_grades.code"));
+        }
+        Ok(())
+    }
+}
+
+// Unique keys compare case-insensitively like the default MySQL collation
+fn same_key(a: &str, b: &str) -> bool {
+    a.to_lowercase() == b.to_lowercase()
+}
+
+fn now() -> String {
+    chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string()
+}
+
+// Pseudonyms instead of name and email, the employee stays as inactive placeholder
+fn anonymize_row(employee: &mut Employee) {
+    if employee.anonymized_at.is_some() {
+        return;
+    }
+    let (first_name, last_name, email) = pseudonyms(&employee.id);
+    let timestamp = now();
+    employee.first_name = first_name;
+    employee.last_name = last_name;
+    employee.email = email;
+    employee.active = false;
+    employee.deleted_at.get_or_insert_with(|| timestamp.clone());
+    employee.anonymized_at = Some(timestamp.clone());
+    employee.updated_at = Some(timestamp);
+}
+
+fn no_fields() -> ApiError {
+    ApiError::BadRequest("No fields to update".to_string())
+}
+
+/// Tables shared by the three memory repositories, so references between them are checked
+#[derive(Default)]
+pub struct MemoryStore {
+    tables: Mutex<Tables>,
+}
+
+impl MemoryStore {
+    pub fn new() -> Arc<Self> {
+        Arc::new(MemoryStore::default())
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Tables> {
+        // A panic while holding the lock leaves no half written rows behind, keep serving
+        self.tables
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+pub struct MemoryEmployeeRepository {
+    store: Arc<MemoryStore>,
+}
+
+impl MemoryEmployeeRepository {
+    pub fn new(store: Arc<MemoryStore>) -> Self {
+        MemoryEmployeeRepository { store }
+    }
+}
+
+fn employee_matches(employee: &Employee, filter: &EmployeeFilter) -> bool {
+    let equals =
+        |wanted: &Option<String>, actual: &Option<String>| wanted.is_none() || wanted == actual;
+    // Dates are YYYY-MM-DD, so comparing the strings compares the dates
+    let hired = employee.hire_date.as_deref();
+
+    equals(&filter.department_id, &employee.department_id)
+        && equals(&filter.manager_id, &employee.manager_id)
+        && equals(&filter.salary_grade_id, &employee.salary_grade_id)
+        && filter.role.is_none_or(|role| role == employee.role)
+        && filter.active.is_none_or(|active| active == employee.active)
+        && filter
+            .hired_from
+            .as_deref()
+            .is_none_or(|from| hired.is_some_and(|date| date >= from))
+        && filter
+            .hired_to
+            .as_deref()
+            .is_none_or(|to| hired.is_some_and(|date| date <= to))
+}
+
+// Same order as MySQL: text ignores case, NULL sorts first, roles in ENUM declaration order
+fn compare_employees(a: &Employee, b: &Employee, field: EmployeeSortField) -> Ordering {
+    let text = |a: &str, b: &str| a.to_lowercase().cmp(&b.to_lowercase());
+    let role_index = |role: Role| Role::ALL.iter().position(|r| *r == role);
+    match field {
+        EmployeeSortField::FirstName => text(&a.first_name, &b.first_name),
+        EmployeeSortField::LastName => text(&a.last_name, &b.last_name),
+        EmployeeSortField::Email => text(&a.email, &b.email),
+        EmployeeSortField::Role => role_index(a.role).cmp(&role_index(b.role)),
+        EmployeeSortField::HireDate => a.hire_date.cmp(&b.hire_date),
+        EmployeeSortField::CreatedAt => a.created_at.cmp(&b.created_at),
+    }
+}
+
+fn hierarchy_row(tables: &Tables, employee: &Employee, level: u32) -> OrgChartRow {
+    OrgChartRow {
+        id: employee.id.clone(),
+        first_name: employee.first_name.clone(),
+        last_name: employee.last_name.clone(),
+        role: employee.role,
+        department_id: employee.department_id.clone(),
+        department_name: employee.department_id.as_ref().and_then(|id| {
+            tables
+                .departments
+                .iter()
+                .find(|d| &d.id == id)
+                .map(|d| d.name.clone())
+        }),
+        manager_id: employee.manager_id.clone(),
+        active: employee.active,
+        level,
+    }
+}
+
+// Stand-in for the _ai_ci collation: no accents, lower case
+fn fold(value: &str) -> String {
+    deunicode::deunicode(value).to_lowercase()
+}
+
+// Ranking of the MySQL search, None if some term matches no field
+fn search_score(employee: &Employee, terms: &[String]) -> Option<u32> {
+    let first_name = fold(&employee.first_name);
+    let last_name = fold(&employee.last_name);
+    let email = fold(&employee.email);
+
+    terms
+        .iter()
+        .map(|term| {
+            if first_name == *term || last_name == *term {
+                Some(3)
+            } else if first_name.starts_with(term.as_str()) || last_name.starts_with(term.as_str())
+            {
+                Some(2)
+            } else if email.starts_with(term.as_str()) {
+                Some(1)
+            } else {
+                None
+            }
+        })
+        .sum()
+}
+
+impl EmployeeRepository for MemoryEmployeeRepository {
+    fn list_page(
+        &self,
+        filter: &EmployeeFilter,
+        sort: &[SortKey<EmployeeSortField>],
+        page: PageRequest,
+    ) -> Result<Page<Employee>, ApiError> {
+        let tables = self.store.lock();
+        let mut matches: Vec<Employee> = tables
+            .employees
+            .iter()
+            .filter(|e| employee_matches(e, filter))
+            .cloned()
+            .collect();
+
+        matches.sort_by(|a, b| {
+            sort.iter()
+                .map(|key| {
+                    let order = compare_employees(a, b, key.field);
+                    if key.descending {
+                        order.reverse()
+                    } else {
+                        order
+                    }
+                })
+                .find(|order| order.is_ne())
+                .unwrap_or_else(|| a.id.cmp(&b.id))
+        });
+        Ok(page.slice(matches))
+    }
+
+    fn search(
+        &self,
+        terms: &[String],
+        include_inactive: bool,
+        page: PageRequest,
+    ) -> Result<Page<Employee>, ApiError> {
+        let terms: Vec<String> = terms.iter().map(|term| fold(term)).collect();
+        let tables = self.store.lock();
+        let mut matches: Vec<(u32, &Employee)> = tables
+            .employees
+            .iter()
+            .filter(|e| include_inactive || e.active)
+            .filter_map(|e| search_score(e, &terms).map(|score| (score, e)))
+            .collect();
+
+        matches.sort_by(|(score_a, a), (score_b, b)| {
+            score_b
+                .cmp(score_a)
+                .then_with(|| compare_employees(a, b, EmployeeSortField::LastName))
+                .then_with(|| compare_employees(a, b, EmployeeSortField::FirstName))
+                .then_with(|| a.id.cmp(&b.id))
+        });
+        Ok(page.slice(matches.into_iter().map(|(_, e)| e.clone()).collect()))
+    }
+
+    fn list_by_department(
+        &self,
+        department_id: &str,
+        recursive: bool,
+    ) -> Result<Vec<Employee>, ApiError> {
+        let tables = self.store.lock();
+        let departments = if recursive {
+            tables.department_subtree(department_id)
+        } else {
+            HashSet::from([department_id.to_string()])
+        };
+        Ok(tables
+            .employees
+            .iter()
+            .filter(|e| {
+                e.active
+                    && e.department_id
+                        .as_ref()
+                        .is_some_and(|id| departments.contains(id))
+            })
+            .cloned()
+            .collect())
+    }
+
+    fn org_chart(&self, root_id: Option<&str>) -> Result<Vec<OrgChartRow>, ApiError> {
+        let tables = self.store.lock();
+        let active_manager = |id: &Option<String>| {
+            tables
+                .employees
+                .iter()
+                .any(|m| m.active && Some(&m.id) == id.as_ref())
+        };
+        let by_name = |a: &&Employee, b: &&Employee| {
+            compare_employees(a, b, EmployeeSortField::LastName)
+                .then_with(|| compare_employees(a, b, EmployeeSortField::FirstName))
+                .then_with(|| a.id.cmp(&b.id))
+        };
+
+        let mut level: Vec<&Employee> = tables
+            .employees
+            .iter()
+            .filter(|e| match root_id {
+                Some(id) => e.id == id,
+                None => e.active && !active_manager(&e.manager_id),
+            })
+            .collect();
+        level.sort_by(by_name);
+
+        // Breadth first, like the recursive CTE, the visited set stops at manager cycles
+        let mut visited: HashSet<&str> = level.iter().map(|e| e.id.as_str()).collect();
+        let mut rows = Vec::new();
+        for depth in 0..=MAX_HIERARCHY_DEPTH {
+            if level.is_empty() {
+                break;
+            }
+            let mut next: Vec<&Employee> = tables
+                .employees
+                .iter()
+                .filter(|e| {
+                    e.active
+                        && !visited.contains(e.id.as_str())
+                        && level.iter().any(|m| e.manager_id.as_ref() == Some(&m.id))
+                })
+                .collect();
+            next.sort_by(by_name);
+            visited.extend(next.iter().map(|e| e.id.as_str()));
+
+            rows.extend(level.into_iter().map(|e| hierarchy_row(&tables, e, depth)));
+            level = next;
+        }
+        Ok(rows)
+    }
+
+    fn management_chain(&self, id: &str) -> Result<Vec<OrgChartRow>, ApiError> {
+        let tables = self.store.lock();
+        let mut rows: Vec<OrgChartRow> = Vec::new();
+        let mut next = Some(id.to_string());
+        while let Some(id) = next.take() {
+            let visited = rows.iter().any(|row| row.id == id);
+            let Some(employee) = tables.employees.iter().find(|e| e.id == id) else {
+                break;
+            };
+            if visited || rows.len() as u32 > MAX_HIERARCHY_DEPTH {
+                break;
+            }
+            rows.push(hierarchy_row(&tables, employee, rows.len() as u32));
+            next = employee.manager_id.clone();
+        }
+        Ok(rows)
+    }
+
+    fn find_by_id(&self, id: &str) -> Result<Option<Employee>, ApiError> {
+        let tables = self.store.lock();
+        Ok(tables.employees.iter().find(|e| e.id == id).cloned())
+    }
+
+    fn create(
+        &self,
+        employee: &CreateEmployeeRequest,
+        role: Role,
+        caller: &AuthenticatedUser,
+    ) -> Result<String, ApiError> {
+        let mut tables = self.store.lock();
+        if let Some(manager_id) = &employee.manager_id {
+            tables.check_manager_scope(caller, manager_id)?;
+        }
+        tables.check_email(&employee.email, None)?;
+        tables.check_employee_references(
+            employee.department_id.as_deref(),
+            employee.salary_grade_id.as_deref(),
+            employee.manager_id.as_deref(),
+        )?;
+
+        let id = Uuid::new_v4().to_string();
+        if let Some(manager_id) = &employee.manager_id {
+            tables.check_manager(&id, manager_id)?;
+        }
+        tables.employees.push(Employee {
+            id: id.clone(),
+            first_name: employee.first_name.clone(),
+            last_name: employee.last_name.clone(),
+            email: employee.email.clone(),
+            department_id: employee.department_id.clone(),
+            salary_grade_id: employee.salary_grade_id.clone(),
+            manager_id: employee.manager_id.clone(),
+            role,
+            hire_date: employee.hire_date.clone(),
+            active: true,
+            deleted_at: None,
+            anonymized_at: None,
+            created_at: Some(now()),
+            updated_at: None,
+        });
+        Ok(id)
+    }
+
+    fn update(
+        &self,
+        id: &str,
+        changes: &UpdateEmployeeRequest,
+        caller: &AuthenticatedUser,
+    ) -> Result<Employee, ApiError> {
+        let UpdateEmployeeRequest {
+            first_name,
+            last_name,
+            email,
+            department_id,
+            salary_grade_id,
+            manager_id,
+            role,
+            hire_date,
+            active,
+        } = changes;
+        if first_name.is_none()
+            && last_name.is_none()
+            && email.is_none()
+            && department_id.is_none()
+            && salary_grade_id.is_none()
+            && manager_id.is_none()
+            && role.is_none()
+            && hire_date.is_none()
+            && active.is_none()
+        {
+            return Err(no_fields());
+        }
+
+        let mut tables = self.store.lock();
+        let index = tables.employee_index(id)?;
+        caller.ensure_can_change(&tables.employees[index])?;
+        if let Some(manager_id) = manager_id {
+            tables.check_manager_scope(caller, manager_id)?;
+        }
+        check_not_anonymized(&tables.employees[index])?;
+        if let Some(email) = email {
+            tables.check_email(email, Some(id))?;
+        }
+        tables.check_employee_references(
+            department_id.as_deref(),
+            salary_grade_id.as_deref(),
+            None,
+        )?;
+        if let Some(manager_id) = manager_id {
+            tables.check_manager(id, manager_id)?;
+        }
+
+        let employee = &mut tables.employees[index];
+        if let Some(first_name) = first_name {
+            employee.first_name = first_name.clone();
+        }
+        if let Some(last_name) = last_name {
+            employee.last_name = last_name.clone();
+        }
+        if let Some(email) = email {
+            employee.email = email.clone();
+        }
+        if let Some(department_id) = department_id {
+            employee.department_id = Some(department_id.clone());
+        }
+        if let Some(salary_grade_id) = salary_grade_id {
+            employee.salary_grade_id = Some(salary_grade_id.clone());
+        }
+        if let Some(manager_id) = manager_id {
+            employee.manager_id = Some(manager_id.clone());
+        }
+        if let Some(role) = role {
+            employee.role = *role;
+        }
+        if let Some(hire_date) = hire_date {
+            employee.hire_date = Some(hire_date.clone());
+        }
+        let timestamp = now();
+        // deleted_at follows active, so deactivating and reactivating look like delete and restore
+        match active {
+            Some(true) => employee.deleted_at = None,
+            Some(false) if employee.deleted_at.is_none() => {
+                employee.deleted_at = Some(timestamp.clone())
+            }
+            _ => {}
+        }
+        if let Some(active) = active {
+            employee.active = *active;
+        }
+        employee.updated_at = Some(timestamp.clone());
+        tables.clear_stale_heads(index, &timestamp);
+        Ok(tables.employees[index].clone())
+    }
+
+    fn soft_delete(&self, id: &str, caller: &AuthenticatedUser) -> Result<Employee, ApiError> {
+        let mut tables = self.store.lock();
+        let index = tables.employee_index(id)?;
+        caller.ensure_can_change(&tables.employees[index])?;
+
+        let employee = &mut tables.employees[index];
+        // Deleting again keeps the first timestamp, the retention period counts from there
+        if !employee.active && employee.deleted_at.is_some() {
+            return Ok(employee.clone());
+        }
+        let timestamp = now();
+        employee.active = false;
+        employee.deleted_at = Some(timestamp.clone());
+        employee.updated_at = Some(timestamp.clone());
+        tables.clear_stale_heads(index, &timestamp);
+        Ok(tables.employees[index].clone())
+    }
+
+    fn restore(&self, id: &str, caller: &AuthenticatedUser) -> Result<EmployeeRestore, ApiError> {
+        let mut tables = self.store.lock();
+        let index = tables.employee_index(id)?;
+        let employee = &tables.employees[index];
+        caller.ensure_can_change(employee)?;
+        check_not_anonymized(employee)?;
+        let stale_department = (employee.department_id.as_ref())
+            .is_some_and(|department_id| tables.department_index(department_id).is_err());
+        let stale_manager = (employee.manager_id.as_ref())
+            .is_some_and(|manager_id| tables.check_manager(id, manager_id).is_err());
+        let stale_salary_grade = (employee.salary_grade_id.as_ref())
+            .is_some_and(|salary_grade_id| tables.salary_grade_index(salary_grade_id).is_err());
+
+        let employee = &mut tables.employees[index];
+        let mut nulled_references = Vec::new();
+        if stale_department {
+            employee.department_id = None;
+            nulled_references.push("department_id".to_string());
+        }
+        if stale_manager {
+            employee.manager_id = None;
+            nulled_references.push("manager_id".to_string());
+        }
+        if stale_salary_grade {
+            employee.salary_grade_id = None;
+            nulled_references.push("salary_grade_id".to_string());
+        }
+        employee.active = true;
+        employee.deleted_at = None;
+        employee.updated_at = Some(now());
+        Ok(EmployeeRestore {
+            employee: employee.clone(),
+            nulled_references,
+        })
+    }
+
+    fn personal_data(&self, id: &str) -> Result<PersonalData, ApiError> {
+        let tables = self.store.lock();
+        let employee = tables.employees[tables.employee_index(id)?].clone();
+
+        // Deleted departments, grades and managers are still part of what is stored
+        let department_name = (tables.departments.iter())
+            .find(|d| employee.department_id.as_ref() == Some(&d.id))
+            .map(|d| d.name.clone());
+        let manager_name = (tables.employees.iter())
+            .find(|e| employee.manager_id.as_ref() == Some(&e.id))
+            .map(|e| format!("{} {}", e.first_name, e.last_name));
+        let salary_grade_code = (tables.salary_grades.iter())
+            .find(|g| employee.salary_grade_id.as_ref() == Some(&g.id))
+            .map(|g| g.code.clone());
+        let headed_departments = (tables.departments.iter())
+            .filter(|d| d.head_id.as_deref() == Some(id))
+            .map(|d| d.name.clone())
+            .collect();
+
+        Ok(PersonalData {
+            employee,
+            department_name,
+            manager_name,
+            salary_grade_code,
+            headed_departments,
+            account: None,
+            api_keys: Vec::new(),
+        })
+    }
+
+    fn anonymize(&self, id: &str) -> Result<Employee, ApiError> {
+        let mut tables = self.store.lock();
+        let index = tables.employee_index(id)?;
+
+        anonymize_row(&mut tables.employees[index]);
+        tables.clear_stale_heads(index, &now());
+        Ok(tables.employees[index].clone())
+    }
+
+    fn expired(&self, deleted_before: &str, mode: PurgeMode) -> Result<Vec<String>, ApiError> {
+        let tables = self.store.lock();
+        let mut expired: Vec<&Employee> = (tables.employees.iter())
+            .filter(|e| purge_due(e, deleted_before, mode))
+            .collect();
+        expired.sort_by(|a, b| a.deleted_at.cmp(&b.deleted_at));
+        Ok(expired.into_iter().map(|e| e.id.clone()).collect())
+    }
+
+    fn purge(
+        &self,
+        id: &str,
+        mode: PurgeMode,
+        deleted_before: &str,
+    ) -> Result<Option<PurgedReferences>, ApiError> {
+        let mut tables = self.store.lock();
+        let index = tables.employee_index(id)?;
+        if !purge_due(&tables.employees[index], deleted_before, mode) {
+            return Ok(None);
+        }
+        let new_manager = (tables.employees[index].manager_id.clone()).filter(|manager_id| {
+            tables
+                .employees
+                .iter()
+                .any(|e| &e.id == manager_id && e.active)
+        });
+
+        let mut purged = PurgedReferences::default();
+        let timestamp = now();
+        for employee in tables.employees.iter_mut() {
+            if employee.manager_id.as_deref() != Some(id) {
+                continue;
+            }
+            // Except the new manager itself, it would end up managing itself
+            employee.manager_id = new_manager.clone().filter(|m| *m != employee.id);
+            if employee.manager_id.is_some() {
+                purged.reassigned_reports += 1;
+            } else {
+                purged.unassigned_reports += 1;
+            }
+            employee.updated_at = Some(timestamp.clone());
+        }
+        for department in tables.departments.iter_mut() {
+            if department.head_id.as_deref() == Some(id) {
+                department.head_id = None;
+                department.updated_at = Some(timestamp.clone());
+                purged.cleared_heads += 1;
+            }
+        }
+
+        match mode {
+            PurgeMode::Anonymize => anonymize_row(&mut tables.employees[index]),
+            PurgeMode::Delete => {
+                tables.employees.remove(index);
+            }
+        }
+        Ok(Some(purged))
+    }
+
+    fn assign_manager(
+        &self,
+        id: &str,
+        manager_id: &str,
+        caller: &AuthenticatedUser,
+    ) -> Result<Employee, ApiError> {
+        let mut tables = self.store.lock();
+        let index = tables.employee_index(id)?;
+        caller.ensure_can_change(&tables.employees[index])?;
+        tables.check_manager_scope(caller, manager_id)?;
+        check_not_anonymized(&tables.employees[index])?;
+        tables.check_manager(id, manager_id)?;
+
+        let employee = &mut tables.employees[index];
+        employee.manager_id = Some(manager_id.to_string());
+        employee.updated_at = Some(now());
+        Ok(employee.clone())
+    }
+
+    fn assign_salary_grade(
+        &self,
+        id: &str,
+        salary_grade_id: &str,
+        caller: &AuthenticatedUser,
+    ) -> Result<Employee, ApiError> {
+        let mut tables = self.store.lock();
+        let index = tables.employee_index(id)?;
+        caller.ensure_can_change(&tables.employees[index])?;
+        check_not_anonymized(&tables.employees[index])?;
+        tables.check_employee_references(None, Some(salary_grade_id), None)?;
+
+        let employee = &mut tables.employees[index];
+        employee.salary_grade_id = Some(salary_grade_id.to_string());
+        employee.updated_at = Some(now());
+        Ok(employee.clone())
+    }
+}
+
+pub struct MemoryDepartmentRepository {
+    store: Arc<MemoryStore>,
+}
+
+impl MemoryDepartmentRepository {
+    pub fn new(store: Arc<MemoryStore>) -> Self {
+        MemoryDepartmentRepository { store }
+    }
+}
+
+impl DepartmentRepository for MemoryDepartmentRepository {
+    fn list(&self, include_deleted: bool) -> Result<Vec<Department>, ApiError> {
+        let tables = self.store.lock();
+        Ok(tables
+            .departments
+            .iter()
+            .filter(|d| include_deleted || d.deleted_at.is_none())
+            .cloned()
+            .collect())
+    }
+
+    fn find_by_id(&self, id: &str) -> Result<Option<Department>, ApiError> {
+        let tables = self.store.lock();
+        Ok(tables.departments.iter().find(|d| d.id == id).cloned())
+    }
+
+    fn subdepartments(&self, id: &str) -> Result<Vec<Department>, ApiError> {
+        let tables = self.store.lock();
+        let mut children: Vec<Department> = tables
+            .departments
+            .iter()
+            .filter(|d| d.parent_id.as_deref() == Some(id) && d.deleted_at.is_none())
+            .cloned()
+            .collect();
+        children
+            .sort_by(|a, b| (a.name.to_lowercase(), &a.id).cmp(&(b.name.to_lowercase(), &b.id)));
+        Ok(children)
+    }
+
+    fn create(&self, department: &CreateDepartmentRequest) -> Result<String, ApiError> {
+        let mut tables = self.store.lock();
+        tables.check_department_name(&department.name, None)?;
+        if let Some(ref parent_id) = department.parent_id {
+            tables.check_department_reference("parent_id", parent_id, "fk_dept_parent")?;
+        }
+
+        let id = Uuid::new_v4().to_string();
+        let head = match department.head_id {
+            Some(ref head_id) => {
+                let promote = department.promote_head.unwrap_or(false);
+                Some((head_id, tables.check_head(&id, head_id, promote)?))
+            }
+            None => None,
+        };
+
+        tables.departments.push(Department {
+            id: id.clone(),
+            name: department.name.clone(),
+            head_id: None,
+            parent_id: department.parent_id.clone(),
+            deleted_at: None,
+            created_at: Some(now()),
+            updated_at: None,
+        });
+        if let Some((head_id, role)) = head {
+            tables.set_head(&id, head_id, role);
+        }
+        Ok(id)
+    }
+
+    fn update(&self, id: &str, changes: &UpdateDepartmentRequest) -> Result<Department, ApiError> {
+        if changes.name.is_none() && changes.head_id.is_none() && changes.parent_id.is_none() {
+            return Err(no_fields());
+        }
+
+        let mut tables = self.store.lock();
+        let index = tables.department_index(id)?;
+        if let Some(ref name) = changes.name {
+            tables.check_department_name(name, Some(id))?;
+        }
+        if let Some(ref parent_id) = changes.parent_id {
+            tables.check_parent(id, parent_id)?;
+        }
+        if let Some(ref head_id) = changes.head_id {
+            let promote = changes.promote_head.unwrap_or(false);
+            let role = tables.check_head(id, head_id, promote)?;
+            tables.set_head(id, head_id, role);
+        }
+
+        let department = &mut tables.departments[index];
+        if let Some(ref name) = changes.name {
+            department.name = name.clone();
+        }
+        if let Some(ref parent_id) = changes.parent_id {
+            department.parent_id = Some(parent_id.clone());
+        }
+        department.updated_at = Some(now());
+        Ok(department.clone())
+    }
+
+    fn delete(
+        &self,
+        id: &str,
+        employees: DepartmentEmployees,
+    ) -> Result<DepartmentDeletion, ApiError> {
+        let mut tables = self.store.lock();
+        let index = tables.department_index(id)?;
+        let active_employees = tables
+            .employees
+            .iter()
+            .filter(|e| e.active && e.department_id.as_deref() == Some(id))
+            .count() as u64;
+        let target = match employees {
+            DepartmentEmployees::ReassignTo(target_id) => tables
+                .departments
+                .iter()
+                .find(|d| d.id == target_id && d.deleted_at.is_none()),
+            _ => None,
+        };
+        check_department_deletion(id, employees, active_employees, target)?;
+
+        let timestamp = now();
+        let department = &mut tables.departments[index];
+        department.deleted_at = Some(timestamp.clone());
+        department.updated_at = Some(timestamp.clone());
+        let mut deletion = DepartmentDeletion {
+            department: department.clone(),
+            reassigned_employees: 0,
+            unassigned_employees: 0,
+            detached_subdepartments: 0,
+        };
+        for employee in tables.employees.iter_mut() {
+            if !employee.active || employee.department_id.as_deref() != Some(id) {
+                continue;
+            }
+            match employees {
+                DepartmentEmployees::ReassignTo(target_id) => {
+                    employee.department_id = Some(target_id.to_string());
+                    deletion.reassigned_employees += 1;
+                }
+                _ => {
+                    employee.department_id = None;
+                    deletion.unassigned_employees += 1;
+                }
+            }
+            employee.updated_at = Some(timestamp.clone());
+        }
+        for child in tables.departments.iter_mut() {
+            if child.parent_id.as_deref() == Some(id) && child.deleted_at.is_none() {
+                child.parent_id = None;
+                child.updated_at = Some(timestamp.clone());
+                deletion.detached_subdepartments += 1;
+            }
+        }
+        Ok(deletion)
+    }
+
+    fn restore(&self, id: &str) -> Result<Department, ApiError> {
+        let mut tables = self.store.lock();
+        let index = tables
+            .departments
+            .iter()
+            .position(|d| d.id == id)
+            .ok_or_else(|| not_found("Department"))?;
+        let parent_deleted = tables.departments[index]
+            .parent_id
+            .as_ref()
+            .is_some_and(|parent_id| tables.department_index(parent_id).is_err());
+
+        let department = &mut tables.departments[index];
+        if department.deleted_at.take().is_some() {
+            department.updated_at = Some(now());
+        }
+        if parent_deleted {
+            department.parent_id = None;
+        }
+        Ok(department.clone())
+    }
+}
+
+pub struct MemorySalaryGradeRepository {
+    store: Arc<MemoryStore>,
+}
+
+impl MemorySalaryGradeRepository {
+    pub fn new(store: Arc<MemoryStore>) -> Self {
+        MemorySalaryGradeRepository { store }
+    }
+}
+
+impl SalaryGradeRepository for MemorySalaryGradeRepository {
+    fn list(&self, include_deleted: bool) -> Result<Vec<SalaryGrade>, ApiError> {
+        let tables = self.store.lock();
+        Ok(tables
+            .salary_grades
+            .iter()
+            .filter(|g| include_deleted || g.deleted_at.is_none())
+            .cloned()
+            .collect())
+    }
+
+    fn find_by_id(&self, id: &str) -> Result<Option<SalaryGrade>, ApiError> {
+        let tables = self.store.lock();
+        Ok(tables.salary_grades.iter().find(|g| g.id == id).cloned())
+    }
+
+    fn create(&self, grade: &CreateSalaryGradeRequest) -> Result<String, ApiError> {
+        let mut tables = self.store.lock();
+        tables.check_salary_grade_code(&grade.code, None)?;
+
+        let id = Uuid::new_v4().to_string();
+        tables.salary_grades.push(SalaryGrade {
+            id: id.clone(),
+            code: grade.code.clone(),
+            base_salary: grade.base_salary,
+            description: grade.description.clone(),
+            deleted_at: None,
+            created_at: Some(now()),
+        });
+        Ok(id)
+    }
+
+    fn update(
+        &self,
+        id: &str,
+        changes: &UpdateSalaryGradeRequest,
+    ) -> Result<SalaryGrade, ApiError> {
+        if changes.code.is_none() && changes.base_salary.is_none() && changes.description.is_none()
+        {
+            return Err(no_fields());
+        }
+
+        let mut tables = self.store.lock();
+        let index = tables.salary_grade_index(id)?;
+        if let Some(ref code) = changes.code {
+            tables.check_salary_grade_code(code, Some(id))?;
+        }
+
+        let grade = &mut tables.salary_grades[index];
+        if let Some(ref code) = changes.code {
+            grade.code = code.clone();
+        }
+        if let Some(base_salary) = changes.base_salary {
+            grade.base_salary = base_salary;
+        }
+        if let Some(ref description) = changes.description {
+            grade.description = Some(description.clone());
+        }
+        Ok(grade.clone())
+    }
+
+    fn delete(&self, id: &str) -> Result<SalaryGrade, ApiError> {
+        let mut tables = self.store.lock();
+        let index = tables.salary_grade_index(id)?;
+        let grade = &mut tables.salary_grades[index];
+        grade.deleted_at = Some(now());
+        Ok(grade.clone())
+    }
+
+    fn restore(&self, id: &str) -> Result<SalaryGrade, ApiError> {
+        let mut tables = self.store.lock();
+        let grade = tables
+            .salary_grades
+            .iter_mut()
+            .find(|g| g.id == id)
+            .ok_or_else(|| not_found("Salary grade"))?;
+        grade.deleted_at = None;
+        Ok(grade.clone())
+    }
+}
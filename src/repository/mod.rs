@@ -0,0 +1,452 @@
+// Storage abstraction for the personnel resources. Handlers only see these traits, the
+// backend behind them is chosen once in main.rs and shared through `web::Data<dyn ...>`.
+
+pub mod memory;
+pub mod mysql;
+
+use crate::auth::AuthenticatedUser;
+use crate::db::DbPool;
+use crate::error::ApiError;
+use crate::hierarchy::OrgChartRow;
+use crate::models::*;
+use crate::pagination::{Page, PageRequest, SortKey};
+use actix_web::web;
+use std::str::FromStr;
+use std::sync::Arc;
+
+pub use self::memory::{
+    MemoryDepartmentRepository, MemoryEmployeeRepository, MemorySalaryGradeRepository, MemoryStore,
+};
+pub use self::mysql::{
+    MysqlDepartmentRepository, MysqlEmployeeRepository, MysqlSalaryGradeRepository,
+};
+
+/// Backend selected with the `STORAGE` env var
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Storage {
+    #[default]
+    Mysql,
+    Memory,
+}
+
+impl FromStr for Storage {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "mysql" => Ok(Storage::Mysql),
+            "memory" => Ok(Storage::Memory),
+            other => Err(format!(
+                "Unknown storage '{}', expected mysql or memory",
+                other
+            )),
+        }
+    }
+}
+
+/// Conditions for listing employees, unset fields don't filter
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EmployeeFilter {
+    pub department_id: Option<String>,
+    pub manager_id: Option<String>,
+    pub salary_grade_id: Option<String>,
+    pub role: Option<Role>,
+    /// Inclusive lower bound for hire_date (YYYY-MM-DD)
+    pub hired_from: Option<String>,
+    /// Inclusive upper bound for hire_date (YYYY-MM-DD)
+    pub hired_to: Option<String>,
+    pub active: Option<bool>,
+}
+
+/// Columns employees can be sorted by
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EmployeeSortField {
+    FirstName,
+    LastName,
+    Email,
+    Role,
+    HireDate,
+    CreatedAt,
+}
+
+impl EmployeeSortField {
+    pub const ALL: [EmployeeSortField; 6] = [
+        EmployeeSortField::FirstName,
+        EmployeeSortField::LastName,
+        EmployeeSortField::Email,
+        EmployeeSortField::Role,
+        EmployeeSortField::HireDate,
+        EmployeeSortField::CreatedAt,
+    ];
+
+    /// Name in the `sort` parameter, same as the column
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EmployeeSortField::FirstName => "first_name",
+            EmployeeSortField::LastName => "last_name",
+            EmployeeSortField::Email => "email",
+            EmployeeSortField::Role => "role",
+            EmployeeSortField::HireDate => "hire_date",
+            EmployeeSortField::CreatedAt => "created_at",
+        }
+    }
+}
+
+impl FromStr for EmployeeSortField {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        EmployeeSortField::ALL
+            .into_iter()
+            .find(|field| field.as_str() == s)
+            .ok_or_else(|| format!("unknown sort field: {}", s))
+    }
+}
+
+/// Words of a search query that are matched, the rest is ignored to keep the query cheap
+pub const MAX_SEARCH_TERMS: usize = 5;
+
+/// Splits a search query into the terms every match has to start one of its fields with
+pub fn search_terms(query: &str) -> Vec<String> {
+    query
+        .split_whitespace()
+        .take(MAX_SEARCH_TERMS)
+        .map(str::to_string)
+        .collect()
+}
+
+/// What happens to the active employees of a department that is deleted. Inactive ones keep
+/// the department they left from, restoring them clears it if it is still deleted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DepartmentEmployees<'a> {
+    /// Refuse to delete while active employees are left
+    Refuse,
+    /// Move the active employees to this department
+    ReassignTo(&'a str),
+    /// Leave the active employees without a department
+    Unassign,
+}
+
+/// What the retention purge does with an employee whose retention period is over
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PurgeMode {
+    /// Replace name and email with pseudonyms and keep the row for reports
+    #[default]
+    Anonymize,
+    /// Remove the row and the login account for good
+    Delete,
+}
+
+impl FromStr for PurgeMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "anonymize" => Ok(PurgeMode::Anonymize),
+            "delete" => Ok(PurgeMode::Delete),
+            other => Err(format!(
+                "Unknown purge mode '{}', expected anonymize or delete",
+                other
+            )),
+        }
+    }
+}
+
+/// References to a purged employee that were moved or cleared along with it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct PurgedReferences {
+    /// Reports handed on to the manager of the purged employee
+    pub reassigned_reports: u64,
+    /// Reports left without a manager, the purged employee had no active one
+    pub unassigned_reports: u64,
+    /// Departments the purged employee was head of, now without a head
+    pub cleared_heads: u64,
+}
+
+// Whether the employee is still due for the purge, `deleted_before` formatted like `deleted_at`.
+// The purge checks again on the locked row, it may have been restored since `expired` listed it.
+pub(crate) fn purge_due(employee: &Employee, deleted_before: &str, mode: PurgeMode) -> bool {
+    !employee.active
+        && employee
+            .deleted_at
+            .as_deref()
+            .is_some_and(|at| at < deleted_before)
+        && (mode == PurgeMode::Delete || employee.anonymized_at.is_none())
+}
+
+// Checked by both backends before anything is changed, `target` is the loaded reassign_to
+pub(crate) fn check_department_deletion(
+    id: &str,
+    employees: DepartmentEmployees,
+    active_employees: u64,
+    target: Option<&Department>,
+) -> Result<(), ApiError> {
+    match employees {
+        DepartmentEmployees::Refuse if active_employees > 0 => Err(ApiError::Conflict(format!(
+            "Department still has {} active employee(s), pass reassign_to or force=true to delete it",
+            active_employees
+        ))),
+        DepartmentEmployees::ReassignTo(target_id) if target_id == id => Err(
+            ApiError::Unprocessable("reassign_to is the department being deleted".to_string()),
+        ),
+        DepartmentEmployees::ReassignTo(_) if target.is_none() => Err(ApiError::Unprocessable(
+            "reassign_to does not reference an existing department".to_string(),
+        )),
+        _ => Ok(()),
+    }
+}
+
+// Deleted departments and salary grades still satisfy the foreign keys, new references to them
+// are refused with this, `resource` like "department"
+pub(crate) fn deleted_reference(field: &str, resource: &str) -> ApiError {
+    ApiError::Unprocessable(format!("{} references a deleted {}", field, resource))
+}
+
+// Both backends report a missing row with the same message, `resource` like "Employee"
+pub(crate) fn not_found(resource: &str) -> ApiError {
+    ApiError::NotFound(format!("{} not found", resource))
+}
+
+// An anonymized employee only stays as a placeholder, changing or restoring it could bring the
+// person back
+pub(crate) fn check_not_anonymized(employee: &Employee) -> Result<(), ApiError> {
+    if employee.anonymized_at.is_some() {
+        return Err(ApiError::Conflict(
+            "Employee has been anonymized and can't be changed".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+// First name, last name and email an anonymized employee is left with. They only derive from the
+// random id, so nothing in them points back to the person.
+pub(crate) fn pseudonyms(id: &str) -> (String, String, String) {
+    let short_id: String = id.chars().take(8).collect();
+    (
+        "Anonymized".to_string(),
+        format!("Employee {}", short_id),
+        format!("anonymized-{}@example.invalid", id),
+    )
+}
+
+/// Methods that change an employee take the caller and check it in the same atomic step as the
+/// write: the employee has to pass `AuthenticatedUser::ensure_can_change` and a new manager
+/// `ensure_can_assign_manager`, otherwise `Forbidden` before anything else is checked.
+pub trait EmployeeRepository: Send + Sync {
+    /// One page of the employees matching `filter`, ordered by `sort` with the id as tiebreaker
+    fn list_page(
+        &self,
+        filter: &EmployeeFilter,
+        sort: &[SortKey<EmployeeSortField>],
+        page: PageRequest,
+    ) -> Result<Page<Employee>, ApiError>;
+
+    /// Employees where every term starts the first name, last name or email, ignoring case and
+    /// accents. Best matches first: a whole name scores 3, a name prefix 2, an email prefix 1.
+    fn search(
+        &self,
+        terms: &[String],
+        include_inactive: bool,
+        page: PageRequest,
+    ) -> Result<Page<Employee>, ApiError>;
+
+    /// Active employees of one department, with `recursive` also those of every department
+    /// below it
+    fn list_by_department(
+        &self,
+        department_id: &str,
+        recursive: bool,
+    ) -> Result<Vec<Employee>, ApiError>;
+
+    /// The employee `root_id` and every active employee below it, or with `None` all active
+    /// employees under the roots (no manager, or one that is no longer active). Ordered by
+    /// level and name, at most `MAX_HIERARCHY_DEPTH` levels deep.
+    fn org_chart(&self, root_id: Option<&str>) -> Result<Vec<OrgChartRow>, ApiError>;
+
+    /// The employee followed by its managers up the hierarchy, inactive ones included, ordered
+    /// by level. Stops at a manager that was already visited. Empty if the employee is unknown.
+    fn management_chain(&self, id: &str) -> Result<Vec<OrgChartRow>, ApiError>;
+
+    fn find_by_id(&self, id: &str) -> Result<Option<Employee>, ApiError>;
+
+    /// Inserts the employee with the given role and returns its new id
+    fn create(
+        &self,
+        employee: &CreateEmployeeRequest,
+        role: Role,
+        caller: &AuthenticatedUser,
+    ) -> Result<String, ApiError>;
+
+    /// Applies the fields that are set and returns the updated employee. `BadRequest` if no
+    /// field is set, `NotFound` for an unknown id and `Conflict` for an anonymized employee. A new
+    /// manager is checked like in `assign_manager`. Departments the employee heads are left
+    /// without a head once it is deactivated, moved out or no longer a DepartmentHead or Admin.
+    fn update(
+        &self,
+        id: &str,
+        changes: &UpdateEmployeeRequest,
+        caller: &AuthenticatedUser,
+    ) -> Result<Employee, ApiError>;
+
+    /// Marks the employee inactive, stamps `deleted_at` unless it is already set and returns it,
+    /// `NotFound` if unknown. Departments it heads are left without a head.
+    fn soft_delete(&self, id: &str, caller: &AuthenticatedUser) -> Result<Employee, ApiError>;
+
+    /// Makes a deleted employee active again and clears `deleted_at`, `NotFound` if unknown and
+    /// `Conflict` if anonymized. A department or salary grade that is gone or deleted and a
+    /// manager that is gone, inactive or would close a cycle are set to null and listed, all in one
+    /// atomic step.
+    fn restore(&self, id: &str, caller: &AuthenticatedUser) -> Result<EmployeeRestore, ApiError>;
+
+    /// The employee with the names of its department, manager and salary grade and the
+    /// departments it heads, `NotFound` if unknown. `account` and `api_keys` are left empty, they
+    /// are stored with the user accounts.
+    fn personal_data(&self, id: &str) -> Result<PersonalData, ApiError>;
+
+    /// Replaces name and email with pseudonyms for good, deletes the login account, clears
+    /// `head_id` of departments it heads and leaves the employee inactive and anonymized, all in
+    /// one atomic step. `NotFound` if unknown. The row
+    /// stays so reports and manager_id references keep working. Anonymizing again changes nothing.
+    fn anonymize(&self, id: &str) -> Result<Employee, ApiError>;
+
+    /// Ids of the inactive employees deleted before `deleted_before` (formatted like `deleted_at`) that
+    /// the purge still has to handle, oldest first. With `Anonymize` that leaves out employees
+    /// that are anonymized already.
+    fn expired(&self, deleted_before: &str, mode: PurgeMode) -> Result<Vec<String>, ApiError>;
+
+    /// Anonymizes or deletes the employee in one atomic step, `NotFound` if unknown. Its reports
+    /// move to its manager while that one is active and lose their manager otherwise, departments
+    /// it heads are left without a head. `None` and nothing changed if the employee is no longer
+    /// inactive and deleted before `deleted_before`, e.g. because it was restored meanwhile.
+    fn purge(
+        &self,
+        id: &str,
+        mode: PurgeMode,
+        deleted_before: &str,
+    ) -> Result<Option<PurgedReferences>, ApiError>;
+
+    /// Sets the manager after checking it exists, is active, is not the employee itself and does
+    /// not report to the employee. The check and the write happen atomically. `Conflict` for an
+    /// anonymized employee.
+    fn assign_manager(
+        &self,
+        id: &str,
+        manager_id: &str,
+        caller: &AuthenticatedUser,
+    ) -> Result<Employee, ApiError>;
+
+    /// Sets the salary grade, which must exist and not be deleted. `Conflict` for an anonymized
+    /// employee.
+    fn assign_salary_grade(
+        &self,
+        id: &str,
+        salary_grade_id: &str,
+        caller: &AuthenticatedUser,
+    ) -> Result<Employee, ApiError>;
+}
+
+pub trait DepartmentRepository: Send + Sync {
+    /// All departments, deleted ones only if asked for
+    fn list(&self, include_deleted: bool) -> Result<Vec<Department>, ApiError>;
+
+    /// The department, deleted or not
+    fn find_by_id(&self, id: &str) -> Result<Option<Department>, ApiError>;
+
+    /// Departments directly below this one that aren't deleted, ordered by name
+    fn subdepartments(&self, id: &str) -> Result<Vec<Department>, ApiError>;
+
+    /// Inserts the department and returns its new id
+    fn create(&self, department: &CreateDepartmentRequest) -> Result<String, ApiError>;
+
+    /// Applies the fields that are set and returns the updated department. `BadRequest` if no
+    /// field is set, `NotFound` for an unknown or deleted id. A new parent must exist, must not be
+    /// deleted and must not be the department itself or one below it.
+    fn update(&self, id: &str, changes: &UpdateDepartmentRequest) -> Result<Department, ApiError>;
+
+    /// Marks the department deleted, moving or unassigning its active employees as asked, and
+    /// reports what changed. `NotFound` if unknown or already deleted, `Conflict` if active
+    /// employees would be left behind. Subdepartments become top level departments. The name
+    /// stays taken until the department is deleted for good.
+    fn delete(
+        &self,
+        id: &str,
+        employees: DepartmentEmployees,
+    ) -> Result<DepartmentDeletion, ApiError>;
+
+    /// Takes a deleted department back and returns it, `NotFound` if unknown. It returns to its
+    /// parent unless that is deleted by now, then it becomes a top level department.
+    fn restore(&self, id: &str) -> Result<Department, ApiError>;
+}
+
+pub trait SalaryGradeRepository: Send + Sync {
+    /// All salary grades, deleted ones only if asked for
+    fn list(&self, include_deleted: bool) -> Result<Vec<SalaryGrade>, ApiError>;
+
+    /// The salary grade, deleted or not
+    fn find_by_id(&self, id: &str) -> Result<Option<SalaryGrade>, ApiError>;
+
+    /// Inserts the salary grade and returns its new id
+    fn create(&self, grade: &CreateSalaryGradeRequest) -> Result<String, ApiError>;
+
+    /// Applies the fields that are set and returns the updated salary grade. `BadRequest` if no
+    /// field is set, `NotFound` for an unknown or deleted id.
+    fn update(&self, id: &str, changes: &UpdateSalaryGradeRequest)
+    -> Result<SalaryGrade, ApiError>;
+
+    /// Marks the salary grade deleted and returns it, `NotFound` if unknown or already deleted.
+    /// Employees keep the grade, it is left out of the list and can't be assigned anymore until
+    /// it is restored. The code stays taken.
+    fn delete(&self, id: &str) -> Result<SalaryGrade, ApiError>;
+
+    /// Takes a deleted salary grade back and returns it, `NotFound` if unknown
+    fn restore(&self, id: &str) -> Result<SalaryGrade, ApiError>;
+}
+
+/// One repository per resource, cloned into every worker's app data
+#[derive(Clone)]
+pub struct Repositories {
+    pub employees: web::Data<dyn EmployeeRepository>,
+    pub departments: web::Data<dyn DepartmentRepository>,
+    pub salary_grades: web::Data<dyn SalaryGradeRepository>,
+}
+
+impl Repositories {
+    pub fn mysql(pool: DbPool) -> Self {
+        let employees: Arc<dyn EmployeeRepository> =
+            Arc::new(MysqlEmployeeRepository::new(pool.clone()));
+        let departments: Arc<dyn DepartmentRepository> =
+            Arc::new(MysqlDepartmentRepository::new(pool.clone()));
+        let salary_grades: Arc<dyn SalaryGradeRepository> =
+            Arc::new(MysqlSalaryGradeRepository::new(pool));
+
+        Repositories {
+            employees: web::Data::from(employees),
+            departments: web::Data::from(departments),
+            salary_grades: web::Data::from(salary_grades),
+        }
+    }
+
+    /// Empty in-memory tables, nothing survives a restart
+    pub fn memory() -> Self {
+        let store = MemoryStore::new();
+        let employees: Arc<dyn EmployeeRepository> =
+            Arc::new(MemoryEmployeeRepository::new(store.clone()));
+        let departments: Arc<dyn DepartmentRepository> =
+            Arc::new(MemoryDepartmentRepository::new(store.clone()));
+        let salary_grades: Arc<dyn SalaryGradeRepository> =
+            Arc::new(MemorySalaryGradeRepository::new(store));
+
+        Repositories {
+            employees: web::Data::from(employees),
+            departments: web::Data::from(departments),
+            salary_grades: web::Data::from(salary_grades),
+        }
+    }
+
+    /// Registers the repositories as app data, use with `App::configure`
+    pub fn register(&self, cfg: &mut web::ServiceConfig) {
+        cfg.app_data(self.employees.clone())
+            .app_data(self.departments.clone())
+            .app_data(self.salary_grades.clone());
+    }
+}
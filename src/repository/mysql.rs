@@ -0,0 +1,1207 @@
+// MySQL implementation of the repositories, all personnel SQL lives here
+
+use super::{
+    DepartmentEmployees, DepartmentRepository, EmployeeFilter, EmployeeRepository,
+    EmployeeSortField, PurgeMode, PurgedReferences, SalaryGradeRepository,
+    check_department_deletion, check_not_anonymized, deleted_reference, not_found, pseudonyms,
+    purge_due,
+};
+use crate::auth::AuthenticatedUser;
+use crate::db::DbPool;
+use crate::error::ApiError;
+use crate::hierarchy::{
+    MAX_HIERARCHY_DEPTH, OrgChartRow, can_lead, check_head_assignment, check_manager_assignment,
+    check_parent_assignment,
+};
+use crate::models::*;
+use crate::pagination::{Page, PageRequest, SortKey};
+use mysql::prelude::*;
+use mysql::{Row, Transaction, TxOpts, Value};
+use uuid::Uuid;
+
+// Dates are formatted by MySQL, the binary protocol would hand them over as date values
+// that don't convert into the String fields of the models
+const EMPLOYEE_COLUMNS: &str = "id, first_name, last_name, email, department_id, salary_grade_id, manager_id, role, \
+     DATE_FORMAT(hire_date, '%Y-%m-%d') AS hire_date, active, \
+     DATE_FORMAT(deleted_at, '%Y-%m-%dT%H:%i:%sZ') AS deleted_at, \
+     DATE_FORMAT(anonymized_at, '%Y-%m-%dT%H:%i:%sZ') AS anonymized_at, \
+     DATE_FORMAT(created_at, '%Y-%m-%dT%H:%i:%sZ') AS created_at, \
+     DATE_FORMAT(updated_at, '%Y-%m-%dT%H:%i:%sZ') AS updated_at";
+
+const DEPARTMENT_COLUMNS: &str = "id, name, head_id, parent_id, \
+     DATE_FORMAT(deleted_at, '%Y-%m-%dT%H:%i:%sZ') AS deleted_at, \
+     DATE_FORMAT(created_at, '%Y-%m-%dT%H:%i:%sZ') AS created_at, \
+     DATE_FORMAT(updated_at, '%Y-%m-%dT%H:%i:%sZ') AS updated_at";
+
+const SALARY_GRADE_COLUMNS: &str = "id, code, base_salary, description, \
+     DATE_FORMAT(deleted_at, '%Y-%m-%dT%H:%i:%sZ') AS deleted_at, \
+     DATE_FORMAT(created_at, '%Y-%m-%dT%H:%i:%sZ') AS created_at";
+
+fn employee_from_row(mut row: Row) -> Employee {
+    Employee {
+        id: row.take("id").unwrap(),
+        first_name: row.take("first_name").unwrap(),
+        last_name: row.take("last_name").unwrap(),
+        email: row.take("email").unwrap(),
+        department_id: row.take("department_id").unwrap(),
+        salary_grade_id: row.take("salary_grade_id").unwrap(),
+        manager_id: row.take("manager_id").unwrap(),
+        role: row.take("role").unwrap(),
+        hire_date: row.take("hire_date").unwrap(),
+        active: row.take("active").unwrap(),
+        deleted_at: row.take("deleted_at").unwrap(),
+        anonymized_at: row.take("anonymized_at").unwrap(),
+        created_at: row.take("created_at").unwrap(),
+        updated_at: row.take("updated_at").unwrap(),
+    }
+}
+
+// WHERE clause and its parameters for an employee filter
+fn employee_conditions(filter: &EmployeeFilter) -> (String, Vec<Value>) {
+    let mut conditions: Vec<&str> = Vec::new();
+    let mut params: Vec<Value> = Vec::new();
+
+    let columns = [
+        ("department_id = ?", &filter.department_id),
+        ("manager_id = ?", &filter.manager_id),
+        ("salary_grade_id = ?", &filter.salary_grade_id),
+        ("hire_date >= ?", &filter.hired_from),
+        ("hire_date <= ?", &filter.hired_to),
+    ];
+    for (condition, value) in columns {
+        if let Some(value) = value {
+            conditions.push(condition);
+            params.push(value.clone().into());
+        }
+    }
+    if let Some(role) = filter.role {
+        conditions.push("role = ?");
+        params.push(role.into());
+    }
+    if let Some(active) = filter.active {
+        conditions.push("active = ?");
+        params.push(active.into());
+    }
+
+    if conditions.is_empty() {
+        (String::new(), params)
+    } else {
+        (format!(" WHERE {}", conditions.join(" AND ")), params)
+    }
+}
+
+// ORDER BY clause, the column names come from the sort field enum and never from the client
+fn employee_order(sort: &[SortKey<EmployeeSortField>]) -> String {
+    let mut keys: Vec<String> = sort
+        .iter()
+        .map(|key| {
+            let direction = if key.descending { "DESC" } else { "ASC" };
+            format!("{} {}", key.field.as_str(), direction)
+        })
+        .collect();
+    keys.push("id ASC".to_string());
+    format!(" ORDER BY {}", keys.join(", "))
+}
+
+// Escapes LIKE wildcards so a search term only ever matches literally
+fn escape_like(term: &str) -> String {
+    term.replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
+// Columns of the hierarchy queries, `c` is the recursive CTE and `d` the joined department
+const HIERARCHY_COLUMNS: &str = "e.id, e.first_name, e.last_name, e.role, e.department_id, \
+     d.name AS department_name, e.manager_id, e.active, c.level";
+
+fn hierarchy_from_row(mut row: Row) -> OrgChartRow {
+    OrgChartRow {
+        id: row.take("id").unwrap(),
+        first_name: row.take("first_name").unwrap(),
+        last_name: row.take("last_name").unwrap(),
+        role: row.take("role").unwrap(),
+        department_id: row.take("department_id").unwrap(),
+        department_name: row.take("department_name").unwrap(),
+        manager_id: row.take("manager_id").unwrap(),
+        active: row.take("active").unwrap(),
+        level: row.take("level").unwrap(),
+    }
+}
+
+fn department_from_row(mut row: Row) -> Department {
+    Department {
+        id: row.take("id").unwrap(),
+        name: row.take("name").unwrap(),
+        head_id: row.take("head_id").unwrap(),
+        parent_id: row.take("parent_id").unwrap(),
+        deleted_at: row.take("deleted_at").unwrap(),
+        created_at: row.take("created_at").unwrap(),
+        updated_at: row.take("updated_at").unwrap(),
+    }
+}
+
+fn salary_grade_from_row(mut row: Row) -> SalaryGrade {
+    SalaryGrade {
+        id: row.take("id").unwrap(),
+        code: row.take("code").unwrap(),
+        base_salary: row.take("base_salary").unwrap(),
+        description: row.take("description").unwrap(),
+        deleted_at: row.take("deleted_at").unwrap(),
+        created_at: row.take("created_at").unwrap(),
+    }
+}
+
+// What the row helpers below need to know about a table
+struct Table<T> {
+    name: &'static str,
+    columns: &'static str,
+    resource: &'static str,
+    /// Condition that leaves out deleted rows, empty where deleted rows stay editable
+    not_deleted: &'static str,
+    from_row: fn(Row) -> T,
+}
+
+const EMPLOYEES: Table<Employee> = Table {
+    name: "employees",
+    columns: EMPLOYEE_COLUMNS,
+    resource: "Employee",
+    not_deleted: "",
+    from_row: employee_from_row,
+};
+
+const DEPARTMENTS: Table<Department> = Table {
+    name: "departments",
+    columns: DEPARTMENT_COLUMNS,
+    resource: "Department",
+    not_deleted: " AND deleted_at IS NULL",
+    from_row: department_from_row,
+};
+
+const SALARY_GRADES: Table<SalaryGrade> = Table {
+    name: "salary_grades",
+    columns: SALARY_GRADE_COLUMNS,
+    resource: "Salary grade",
+    not_deleted: " AND deleted_at IS NULL",
+    from_row: salary_grade_from_row,
+};
+
+// Reads the row and keeps it locked until the transaction ends, NotFound if there is none.
+// Deleted rows are only found with `include_deleted`.
+fn lock_by_id<T>(
+    tx: &mut Transaction,
+    table: &Table<T>,
+    id: &str,
+    include_deleted: bool,
+) -> Result<T, ApiError> {
+    let not_deleted = if include_deleted {
+        ""
+    } else {
+        table.not_deleted
+    };
+    let query = format!(
+        "SELECT {} FROM {} WHERE id = ?{} FOR UPDATE",
+        table.columns, table.name, not_deleted
+    );
+
+    let row: Option<Row> = tx.exec_first(query, (id,))?;
+    row.map(table.from_row)
+        .ok_or_else(|| not_found(table.resource))
+}
+
+// Runs `UPDATE <table> SET <updates> WHERE id = ?` for the collected assignments and returns the
+// changed row. The row is locked first so an unknown id is NotFound instead of a silent no-op,
+// `check` runs in the same transaction right before the update.
+fn update_by_id<T>(
+    pool: &DbPool,
+    table: &Table<T>,
+    id: &str,
+    updates: Vec<&str>,
+    mut params: Vec<Value>,
+    check: impl FnOnce(&mut Transaction) -> Result<(), ApiError>,
+) -> Result<T, ApiError> {
+    if updates.is_empty() {
+        return Err(ApiError::BadRequest("No fields to update".to_string()));
+    }
+
+    let mut conn = pool.get_conn()?;
+    let mut tx = conn.start_transaction(TxOpts::default())?;
+    lock_by_id(&mut tx, table, id, false)?;
+    check(&mut tx)?;
+
+    params.push(id.into());
+    let query = format!(
+        "UPDATE {} SET {} WHERE id = ?",
+        table.name,
+        updates.join(", ")
+    );
+    tx.exec_drop(&query, params)?;
+
+    let row = lock_by_id(&mut tx, table, id, true)?;
+    tx.commit()?;
+    Ok(row)
+}
+
+// Clears deleted_at and returns the row, `check` runs on the locked row before. A row that isn't
+// deleted is returned as it is.
+fn restore_by_id<T>(
+    pool: &DbPool,
+    table: &Table<T>,
+    id: &str,
+    check: impl FnOnce(&mut Transaction, &T) -> Result<(), ApiError>,
+) -> Result<T, ApiError> {
+    let mut conn = pool.get_conn()?;
+    let mut tx = conn.start_transaction(TxOpts::default())?;
+    let row = lock_by_id(&mut tx, table, id, true)?;
+    check(&mut tx, &row)?;
+
+    let query = format!(
+        "UPDATE {} SET deleted_at = NULL WHERE id = ? AND deleted_at IS NOT NULL",
+        table.name
+    );
+    tx.exec_drop(query, (id,))?;
+
+    let row = lock_by_id(&mut tx, table, id, true)?;
+    tx.commit()?;
+    Ok(row)
+}
+
+// Whether the row is deleted, None if there is no such row. The row stays share locked.
+fn deleted_state<T>(
+    tx: &mut Transaction,
+    table: &Table<T>,
+    id: &str,
+) -> Result<Option<bool>, ApiError> {
+    let query = format!(
+        "SELECT deleted_at IS NOT NULL FROM {} WHERE id = ? FOR SHARE",
+        table.name
+    );
+    Ok(tx.exec_first(query, (id,))?)
+}
+
+// Deleted rows still satisfy the foreign keys, so references to them are refused here. Unknown
+// ids are left to the foreign key.
+fn check_not_deleted<T>(
+    tx: &mut Transaction,
+    table: &Table<T>,
+    field: &str,
+    id: &str,
+) -> Result<(), ApiError> {
+    if deleted_state(tx, table, id)? == Some(true) {
+        return Err(deleted_reference(field, &table.resource.to_lowercase()));
+    }
+    Ok(())
+}
+
+// The department and salary grade an employee is put into must not be deleted
+fn check_employee_references(
+    tx: &mut Transaction,
+    department_id: Option<&str>,
+    salary_grade_id: Option<&str>,
+) -> Result<(), ApiError> {
+    if let Some(id) = department_id {
+        check_not_deleted(tx, &DEPARTMENTS, "department_id", id)?;
+    }
+    if let Some(id) = salary_grade_id {
+        check_not_deleted(tx, &SALARY_GRADES, "salary_grade_id", id)?;
+    }
+    Ok(())
+}
+
+// Locking reads, so a concurrent reassignment waits for this transaction and then sees its
+// result instead of both passing the cycle check against the old hierarchy
+fn check_manager_locked(
+    tx: &mut Transaction,
+    employee_id: &str,
+    manager_id: &str,
+) -> Result<(), ApiError> {
+    let query = format!(
+        "SELECT {} FROM employees WHERE id = ? FOR UPDATE",
+        EMPLOYEE_COLUMNS
+    );
+    check_manager_assignment(employee_id, manager_id, |id| {
+        let row: Option<Row> = tx.exec_first(&query, (id,))?;
+        Ok(row.map(employee_from_row))
+    })
+}
+
+// The caller's check on a new manager, against the locked row. Unknown managers are left to
+// check_manager_locked, which answers with 422.
+fn check_manager_scope_locked(
+    tx: &mut Transaction,
+    caller: &AuthenticatedUser,
+    manager_id: &str,
+) -> Result<(), ApiError> {
+    if caller.is_admin() {
+        return Ok(());
+    }
+    let query = format!(
+        "SELECT {} FROM employees WHERE id = ? FOR UPDATE",
+        EMPLOYEE_COLUMNS
+    );
+    let row: Option<Row> = tx.exec_first(query, (manager_id,))?;
+    match row {
+        Some(row) => caller.ensure_can_assign_manager(&employee_from_row(row)),
+        None => Ok(()),
+    }
+}
+
+// Same locking walk as for managers, up the departments from the new parent
+fn check_parent_locked(
+    tx: &mut Transaction,
+    department_id: &str,
+    parent_id: &str,
+) -> Result<(), ApiError> {
+    let query = format!(
+        "SELECT {} FROM departments WHERE id = ? FOR UPDATE",
+        DEPARTMENT_COLUMNS
+    );
+    check_parent_assignment(department_id, parent_id, |id| {
+        let row: Option<Row> = tx.exec_first(&query, (id,))?;
+        Ok(row.map(department_from_row))
+    })?;
+    check_not_deleted(tx, &DEPARTMENTS, "parent_id", parent_id)
+}
+
+// Pseudonyms instead of name and email, the employee stays as inactive placeholder. Its login
+// account is deleted and departments it heads lose their head in the same transaction. The pseudonyms only depend on the id, so an
+// anonymized employee gets the same values again.
+fn anonymize_locked(tx: &mut Transaction, id: &str) -> Result<(), ApiError> {
+    let (first_name, last_name, email) = pseudonyms(id);
+    tx.exec_drop(
+        "UPDATE employees SET first_name = ?, last_name = ?, email = ?, active = FALSE, \
+         deleted_at = COALESCE(deleted_at, NOW()), anonymized_at = COALESCE(anonymized_at, NOW()) \
+         WHERE id = ?",
+        (first_name, last_name, email, id),
+    )?;
+    tx.exec_drop("DELETE FROM users WHERE employee_id = ?", (id,))?;
+    tx.exec_drop(
+        "UPDATE departments SET head_id = NULL WHERE head_id = ?",
+        (id,),
+    )?;
+    Ok(())
+}
+
+// Departments the employee can no longer lead once it is written like `employee` are left
+// without a head, in the transaction that writes the employee
+fn clear_stale_heads_locked(tx: &mut Transaction, employee: &Employee) -> Result<(), ApiError> {
+    let headed: Vec<String> = tx.exec(
+        "SELECT id FROM departments WHERE head_id = ? FOR UPDATE",
+        (&employee.id,),
+    )?;
+    for department_id in headed.iter().filter(|id| !can_lead(employee, id)) {
+        tx.exec_drop(
+            "UPDATE departments SET head_id = NULL WHERE id = ?",
+            (department_id,),
+        )?;
+    }
+    Ok(())
+}
+
+// Checks the new head of a department and moves the employees around it, in the transaction that
+// writes departments.head_id: the head is promoted and moved in if asked for, the previous head
+// steps down to Employee and a department the new head led so far is left without one
+fn set_head_locked(
+    tx: &mut Transaction,
+    department_id: &str,
+    head_id: &str,
+    promote: bool,
+) -> Result<(), ApiError> {
+    let query = format!(
+        "SELECT {} FROM employees WHERE id = ? FOR UPDATE",
+        EMPLOYEE_COLUMNS
+    );
+    let row: Option<Row> = tx.exec_first(query, (head_id,))?;
+    let head = row.map(employee_from_row);
+    let role = check_head_assignment(department_id, head.as_ref(), promote)?;
+
+    tx.exec_drop(
+        "UPDATE employees e JOIN departments d ON d.head_id = e.id SET e.role = 'Employee' WHERE d.id = ? AND e.id <> ? AND e.role = 'DepartmentHead'",
+        (department_id, head_id),
+    )?;
+    tx.exec_drop(
+        "UPDATE departments SET head_id = NULL WHERE head_id = ? AND id <> ?",
+        (head_id, department_id),
+    )?;
+    tx.exec_drop(
+        "UPDATE employees SET role = ?, department_id = ? WHERE id = ?",
+        (role, department_id, head_id),
+    )?;
+    Ok(())
+}
+
+pub struct MysqlEmployeeRepository {
+    pool: DbPool,
+}
+
+impl MysqlEmployeeRepository {
+    pub fn new(pool: DbPool) -> Self {
+        MysqlEmployeeRepository { pool }
+    }
+}
+
+impl EmployeeRepository for MysqlEmployeeRepository {
+    fn list_page(
+        &self,
+        filter: &EmployeeFilter,
+        sort: &[SortKey<EmployeeSortField>],
+        page: PageRequest,
+    ) -> Result<Page<Employee>, ApiError> {
+        let (conditions, params) = employee_conditions(filter);
+        let mut conn = self.pool.get_conn()?;
+
+        let total: u64 = conn
+            .exec_first(
+                format!("SELECT COUNT(*) FROM employees{}", conditions),
+                params.clone(),
+            )?
+            .unwrap_or(0);
+
+        let query = format!(
+            "SELECT {} FROM employees{}{} LIMIT {} OFFSET {}",
+            EMPLOYEE_COLUMNS,
+            conditions,
+            employee_order(sort),
+            page.per_page,
+            page.offset()
+        );
+        let rows: Vec<Row> = conn.exec(query, params)?;
+
+        Ok(Page {
+            items: rows.into_iter().map(employee_from_row).collect(),
+            total,
+        })
+    }
+
+    fn search(
+        &self,
+        terms: &[String],
+        include_inactive: bool,
+        page: PageRequest,
+    ) -> Result<Page<Employee>, ApiError> {
+        // first_name and last_name use an _ai_ci collation, so = and LIKE ignore case and accents
+        let mut conditions: Vec<&str> = Vec::new();
+        let mut params: Vec<Value> = Vec::new();
+        let mut scores: Vec<&str> = Vec::new();
+        let mut score_params: Vec<Value> = Vec::new();
+        for term in terms {
+            let prefix = format!("{}%", escape_like(term));
+            conditions.push("(first_name LIKE ? OR last_name LIKE ? OR email LIKE ?)");
+            params.extend([&prefix, &prefix, &prefix].map(|p| Value::from(p.as_str())));
+            scores.push(
+                "CASE WHEN first_name = ? OR last_name = ? THEN 3 \
+                 WHEN first_name LIKE ? OR last_name LIKE ? THEN 2 ELSE 1 END",
+            );
+            score_params.extend([term, term, &prefix, &prefix].map(|p| Value::from(p.as_str())));
+        }
+        if !include_inactive {
+            conditions.push("active = TRUE");
+        }
+        let conditions = conditions.join(" AND ");
+        let mut conn = self.pool.get_conn()?;
+
+        let total: u64 = conn
+            .exec_first(
+                format!("SELECT COUNT(*) FROM employees WHERE {}", conditions),
+                params.clone(),
+            )?
+            .unwrap_or(0);
+
+        let query = format!(
+            "SELECT {}, {} AS relevance FROM employees WHERE {} \
+             ORDER BY relevance DESC, last_name, first_name, id LIMIT {} OFFSET {}",
+            EMPLOYEE_COLUMNS,
+            scores.join(" + "),
+            conditions,
+            page.per_page,
+            page.offset()
+        );
+        score_params.extend(params);
+        let rows: Vec<Row> = conn.exec(query, score_params)?;
+
+        Ok(Page {
+            items: rows.into_iter().map(employee_from_row).collect(),
+            total,
+        })
+    }
+
+    fn list_by_department(
+        &self,
+        department_id: &str,
+        recursive: bool,
+    ) -> Result<Vec<Employee>, ApiError> {
+        let query = if recursive {
+            format!(
+                "WITH RECURSIVE tree (id, level, path) AS ( \
+                   SELECT id, 0, CAST(id AS CHAR(4000)) FROM departments WHERE id = ? \
+                   UNION ALL \
+                   SELECT d.id, t.level + 1, CONCAT(t.path, ',', d.id) \
+                   FROM departments d JOIN tree t ON d.parent_id = t.id \
+                   WHERE FIND_IN_SET(d.id, t.path) = 0 AND t.level < {} \
+                 ) \
+                 SELECT {} FROM employees \
+                 WHERE department_id IN (SELECT id FROM tree) AND active = TRUE",
+                MAX_HIERARCHY_DEPTH, EMPLOYEE_COLUMNS
+            )
+        } else {
+            format!(
+                "SELECT {} FROM employees WHERE department_id = ? AND active = TRUE",
+                EMPLOYEE_COLUMNS
+            )
+        };
+
+        let rows: Vec<Row> = self.pool.get_conn()?.exec(query, (department_id,))?;
+        Ok(rows.into_iter().map(employee_from_row).collect())
+    }
+
+    fn org_chart(&self, root_id: Option<&str>) -> Result<Vec<OrgChartRow>, ApiError> {
+        let (roots, params) = match root_id {
+            Some(id) => ("e.id = ?", vec![Value::from(id)]),
+            None => (
+                "e.active = TRUE AND NOT EXISTS \
+                 (SELECT 1 FROM employees m WHERE m.id = e.manager_id AND m.active = TRUE)",
+                vec![],
+            ),
+        };
+        // The path of visited ids stops the recursion at a manager cycle
+        let query = format!(
+            "WITH RECURSIVE chart (id, level, path) AS ( \
+               SELECT e.id, 0, CAST(e.id AS CHAR(4000)) FROM employees e WHERE {} \
+               UNION ALL \
+               SELECT e.id, c.level + 1, CONCAT(c.path, ',', e.id) \
+               FROM employees e JOIN chart c ON e.manager_id = c.id \
+               WHERE e.active = TRUE AND FIND_IN_SET(e.id, c.path) = 0 AND c.level < {} \
+             ) \
+             SELECT {} FROM chart c JOIN employees e ON e.id = c.id \
+             LEFT JOIN departments d ON d.id = e.department_id \
+             ORDER BY c.level, e.last_name, e.first_name, e.id",
+            roots, MAX_HIERARCHY_DEPTH, HIERARCHY_COLUMNS
+        );
+
+        let rows: Vec<Row> = self.pool.get_conn()?.exec(query, params)?;
+        Ok(rows.into_iter().map(hierarchy_from_row).collect())
+    }
+
+    fn management_chain(&self, id: &str) -> Result<Vec<OrgChartRow>, ApiError> {
+        let query = format!(
+            "WITH RECURSIVE chain (id, manager_id, level, path) AS ( \
+               SELECT id, manager_id, 0, CAST(id AS CHAR(4000)) FROM employees WHERE id = ? \
+               UNION ALL \
+               SELECT e.id, e.manager_id, c.level + 1, CONCAT(c.path, ',', e.id) \
+               FROM employees e JOIN chain c ON e.id = c.manager_id \
+               WHERE FIND_IN_SET(e.id, c.path) = 0 AND c.level < {} \
+             ) \
+             SELECT {} FROM chain c JOIN employees e ON e.id = c.id \
+             LEFT JOIN departments d ON d.id = e.department_id \
+             ORDER BY c.level",
+            MAX_HIERARCHY_DEPTH, HIERARCHY_COLUMNS
+        );
+
+        let rows: Vec<Row> = self.pool.get_conn()?.exec(query, (id,))?;
+        Ok(rows.into_iter().map(hierarchy_from_row).collect())
+    }
+
+    fn find_by_id(&self, id: &str) -> Result<Option<Employee>, ApiError> {
+        let query = format!("SELECT {} FROM employees WHERE id = ?", EMPLOYEE_COLUMNS);
+
+        let row: Option<Row> = self.pool.get_conn()?.exec_first(query, (id,))?;
+        Ok(row.map(employee_from_row))
+    }
+
+    fn create(
+        &self,
+        employee: &CreateEmployeeRequest,
+        role: Role,
+        caller: &AuthenticatedUser,
+    ) -> Result<String, ApiError> {
+        let id = Uuid::new_v4().to_string();
+
+        let mut conn = self.pool.get_conn()?;
+        let mut tx = conn.start_transaction(TxOpts::default())?;
+        if let Some(manager_id) = &employee.manager_id {
+            check_manager_scope_locked(&mut tx, caller, manager_id)?;
+        }
+        check_employee_references(
+            &mut tx,
+            employee.department_id.as_deref(),
+            employee.salary_grade_id.as_deref(),
+        )?;
+        if let Some(manager_id) = &employee.manager_id {
+            check_manager_locked(&mut tx, &id, manager_id)?;
+        }
+        tx.exec_drop(
+            "INSERT INTO employees (id, first_name, last_name, email, department_id, salary_grade_id, manager_id, role, hire_date) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
+            (&id, &employee.first_name, &employee.last_name, &employee.email, &employee.department_id, &employee.salary_grade_id, &employee.manager_id, role, &employee.hire_date)
+        )?;
+        tx.commit()?;
+        Ok(id)
+    }
+
+    fn update(
+        &self,
+        id: &str,
+        changes: &UpdateEmployeeRequest,
+        caller: &AuthenticatedUser,
+    ) -> Result<Employee, ApiError> {
+        let mut updates = Vec::new();
+        let mut params: Vec<Value> = Vec::new();
+
+        if let Some(ref first_name) = changes.first_name {
+            updates.push("first_name = ?");
+            params.push(first_name.clone().into());
+        }
+        if let Some(ref last_name) = changes.last_name {
+            updates.push("last_name = ?");
+            params.push(last_name.clone().into());
+        }
+        if let Some(ref email) = changes.email {
+            updates.push("email = ?");
+            params.push(email.clone().into());
+        }
+        if let Some(ref department_id) = changes.department_id {
+            updates.push("department_id = ?");
+            params.push(department_id.clone().into());
+        }
+        if let Some(ref salary_grade_id) = changes.salary_grade_id {
+            updates.push("salary_grade_id = ?");
+            params.push(salary_grade_id.clone().into());
+        }
+        if let Some(ref manager_id) = changes.manager_id {
+            updates.push("manager_id = ?");
+            params.push(manager_id.clone().into());
+        }
+        if let Some(role) = changes.role {
+            updates.push("role = ?");
+            params.push(role.into());
+        }
+        if let Some(ref hire_date) = changes.hire_date {
+            updates.push("hire_date = ?");
+            params.push(hire_date.clone().into());
+        }
+        // deleted_at follows active, so deactivating and reactivating look like delete and restore
+        if let Some(active) = changes.active {
+            updates.push("active = ?");
+            params.push(active.into());
+            updates.push(if active {
+                "deleted_at = NULL"
+            } else {
+                "deleted_at = COALESCE(deleted_at, NOW())"
+            });
+        }
+
+        update_by_id(&self.pool, &EMPLOYEES, id, updates, params, |tx| {
+            let employee = lock_by_id(tx, &EMPLOYEES, id, false)?;
+            caller.ensure_can_change(&employee)?;
+            if let Some(ref manager_id) = changes.manager_id {
+                check_manager_scope_locked(tx, caller, manager_id)?;
+            }
+            check_not_anonymized(&employee)?;
+            check_employee_references(
+                tx,
+                changes.department_id.as_deref(),
+                changes.salary_grade_id.as_deref(),
+            )?;
+            if let Some(ref manager_id) = changes.manager_id {
+                check_manager_locked(tx, id, manager_id)?;
+            }
+
+            // The head rules are checked against the row as it is about to be written
+            let updated = Employee {
+                active: changes.active.unwrap_or(employee.active),
+                department_id: changes.department_id.clone().or(employee.department_id),
+                role: changes.role.unwrap_or(employee.role),
+                ..employee
+            };
+            clear_stale_heads_locked(tx, &updated)
+        })
+    }
+
+    fn soft_delete(&self, id: &str, caller: &AuthenticatedUser) -> Result<Employee, ApiError> {
+        update_by_id(
+            &self.pool,
+            &EMPLOYEES,
+            id,
+            // Deleting again keeps the first timestamp, the retention period counts from there
+            vec!["active = FALSE", "deleted_at = COALESCE(deleted_at, NOW())"],
+            vec![],
+            |tx| {
+                let employee = lock_by_id(tx, &EMPLOYEES, id, false)?;
+                caller.ensure_can_change(&employee)?;
+                clear_stale_heads_locked(
+                    tx,
+                    &Employee {
+                        active: false,
+                        ..employee
+                    },
+                )
+            },
+        )
+    }
+
+    fn restore(&self, id: &str, caller: &AuthenticatedUser) -> Result<EmployeeRestore, ApiError> {
+        let mut conn = self.pool.get_conn()?;
+        let mut tx = conn.start_transaction(TxOpts::default())?;
+        let employee = lock_by_id(&mut tx, &EMPLOYEES, id, true)?;
+        caller.ensure_can_change(&employee)?;
+        check_not_anonymized(&employee)?;
+
+        let mut updates = vec!["active = TRUE", "deleted_at = NULL"];
+        let mut nulled_references = Vec::new();
+        if let Some(ref department_id) = employee.department_id
+            && deleted_state(&mut tx, &DEPARTMENTS, department_id)? != Some(false)
+        {
+            updates.push("department_id = NULL");
+            nulled_references.push("department_id".to_string());
+        }
+        if let Some(ref manager_id) = employee.manager_id {
+            match check_manager_locked(&mut tx, id, manager_id) {
+                Ok(()) => {}
+                Err(ApiError::Unprocessable(_)) => {
+                    updates.push("manager_id = NULL");
+                    nulled_references.push("manager_id".to_string());
+                }
+                Err(err) => return Err(err),
+            }
+        }
+        if let Some(ref salary_grade_id) = employee.salary_grade_id
+            && deleted_state(&mut tx, &SALARY_GRADES, salary_grade_id)? != Some(false)
+        {
+            updates.push("salary_grade_id = NULL");
+            nulled_references.push("salary_grade_id".to_string());
+        }
+
+        let query = format!("UPDATE employees SET {} WHERE id = ?", updates.join(", "));
+        tx.exec_drop(query, (id,))?;
+        let employee = lock_by_id(&mut tx, &EMPLOYEES, id, true)?;
+        tx.commit()?;
+        Ok(EmployeeRestore {
+            employee,
+            nulled_references,
+        })
+    }
+
+    fn personal_data(&self, id: &str) -> Result<PersonalData, ApiError> {
+        let mut conn = self.pool.get_conn()?;
+        let query = format!("SELECT {} FROM employees WHERE id = ?", EMPLOYEE_COLUMNS);
+        let row: Option<Row> = conn.exec_first(query, (id,))?;
+        let employee = row
+            .map(employee_from_row)
+            .ok_or_else(|| not_found("Employee"))?;
+
+        // Deleted departments, grades and managers are still part of what is stored
+        let names: Option<(Option<String>, Option<String>, Option<String>)> = conn.exec_first(
+            "SELECT d.name, CONCAT(m.first_name, ' ', m.last_name), g.code FROM employees e \
+             LEFT JOIN departments d ON d.id = e.department_id \
+             LEFT JOIN employees m ON m.id = e.manager_id \
+             LEFT JOIN salary_grades g ON g.id = e.salary_grade_id \
+             WHERE e.id = ?",
+            (id,),
+        )?;
+        let (department_name, manager_name, salary_grade_code) = names.unwrap_or_default();
+        let headed_departments = conn.exec(
+            "SELECT name FROM departments WHERE head_id = ? ORDER BY name",
+            (id,),
+        )?;
+
+        Ok(PersonalData {
+            employee,
+            department_name,
+            manager_name,
+            salary_grade_code,
+            headed_departments,
+            account: None,
+            api_keys: Vec::new(),
+        })
+    }
+
+    fn anonymize(&self, id: &str) -> Result<Employee, ApiError> {
+        let mut conn = self.pool.get_conn()?;
+        let mut tx = conn.start_transaction(TxOpts::default())?;
+        lock_by_id(&mut tx, &EMPLOYEES, id, true)?;
+        anonymize_locked(&mut tx, id)?;
+
+        let employee = lock_by_id(&mut tx, &EMPLOYEES, id, true)?;
+        tx.commit()?;
+        Ok(employee)
+    }
+
+    fn expired(&self, deleted_before: &str, mode: PurgeMode) -> Result<Vec<String>, ApiError> {
+        let not_anonymized = match mode {
+            PurgeMode::Anonymize => " AND anonymized_at IS NULL",
+            PurgeMode::Delete => "",
+        };
+        let query = format!(
+            "SELECT id FROM employees \
+             WHERE active = FALSE AND deleted_at < STR_TO_DATE(?, '%Y-%m-%dT%H:%i:%sZ'){} \
+             ORDER BY deleted_at, id",
+            not_anonymized
+        );
+
+        Ok(self.pool.get_conn()?.exec(query, (deleted_before,))?)
+    }
+
+    fn purge(
+        &self,
+        id: &str,
+        mode: PurgeMode,
+        deleted_before: &str,
+    ) -> Result<Option<PurgedReferences>, ApiError> {
+        let mut conn = self.pool.get_conn()?;
+        let mut tx = conn.start_transaction(TxOpts::default())?;
+        let employee = lock_by_id(&mut tx, &EMPLOYEES, id, true)?;
+        if !purge_due(&employee, deleted_before, mode) {
+            return Ok(None);
+        }
+
+        let new_manager: Option<String> = match employee.manager_id {
+            Some(ref manager_id) => tx.exec_first(
+                "SELECT id FROM employees WHERE id = ? AND active FOR SHARE",
+                (manager_id,),
+            )?,
+            None => None,
+        };
+
+        let mut purged = PurgedReferences::default();
+        if let Some(ref manager_id) = new_manager {
+            // Except the new manager itself, it would end up managing itself
+            tx.exec_drop(
+                "UPDATE employees SET manager_id = ? WHERE manager_id = ? AND id <> ?",
+                (manager_id, id, manager_id),
+            )?;
+            purged.reassigned_reports = tx.affected_rows();
+        }
+        tx.exec_drop(
+            "UPDATE employees SET manager_id = NULL WHERE manager_id = ?",
+            (id,),
+        )?;
+        purged.unassigned_reports = tx.affected_rows();
+        tx.exec_drop(
+            "UPDATE departments SET head_id = NULL WHERE head_id = ?",
+            (id,),
+        )?;
+        purged.cleared_heads = tx.affected_rows();
+
+        match mode {
+            PurgeMode::Anonymize => anonymize_locked(&mut tx, id)?,
+            // The login account goes with the row through fk_user_employee
+            PurgeMode::Delete => tx.exec_drop("DELETE FROM employees WHERE id = ?", (id,))?,
+        }
+        tx.commit()?;
+        Ok(Some(purged))
+    }
+
+    fn assign_manager(
+        &self,
+        id: &str,
+        manager_id: &str,
+        caller: &AuthenticatedUser,
+    ) -> Result<Employee, ApiError> {
+        update_by_id(
+            &self.pool,
+            &EMPLOYEES,
+            id,
+            vec!["manager_id = ?"],
+            vec![manager_id.into()],
+            |tx| {
+                let employee = lock_by_id(tx, &EMPLOYEES, id, false)?;
+                caller.ensure_can_change(&employee)?;
+                check_manager_scope_locked(tx, caller, manager_id)?;
+                check_not_anonymized(&employee)?;
+                check_manager_locked(tx, id, manager_id)
+            },
+        )
+    }
+
+    fn assign_salary_grade(
+        &self,
+        id: &str,
+        salary_grade_id: &str,
+        caller: &AuthenticatedUser,
+    ) -> Result<Employee, ApiError> {
+        update_by_id(
+            &self.pool,
+            &EMPLOYEES,
+            id,
+            vec!["salary_grade_id = ?"],
+            vec![salary_grade_id.into()],
+            |tx| {
+                let employee = lock_by_id(tx, &EMPLOYEES, id, false)?;
+                caller.ensure_can_change(&employee)?;
+                check_not_anonymized(&employee)?;
+                check_employee_references(tx, None, Some(salary_grade_id))
+            },
+        )
+    }
+}
+
+pub struct MysqlDepartmentRepository {
+    pool: DbPool,
+}
+
+impl MysqlDepartmentRepository {
+    pub fn new(pool: DbPool) -> Self {
+        MysqlDepartmentRepository { pool }
+    }
+}
+
+impl DepartmentRepository for MysqlDepartmentRepository {
+    fn list(&self, include_deleted: bool) -> Result<Vec<Department>, ApiError> {
+        let filter = if include_deleted {
+            ""
+        } else {
+            " WHERE deleted_at IS NULL"
+        };
+        let query = format!("SELECT {} FROM departments{}", DEPARTMENT_COLUMNS, filter);
+
+        let rows: Vec<Row> = self.pool.get_conn()?.query(query)?;
+        Ok(rows.into_iter().map(department_from_row).collect())
+    }
+
+    fn find_by_id(&self, id: &str) -> Result<Option<Department>, ApiError> {
+        let query = format!(
+            "SELECT {} FROM departments WHERE id = ?",
+            DEPARTMENT_COLUMNS
+        );
+
+        let row: Option<Row> = self.pool.get_conn()?.exec_first(query, (id,))?;
+        Ok(row.map(department_from_row))
+    }
+
+    fn subdepartments(&self, id: &str) -> Result<Vec<Department>, ApiError> {
+        let query = format!(
+            "SELECT {} FROM departments WHERE parent_id = ? AND deleted_at IS NULL ORDER BY name, id",
+            DEPARTMENT_COLUMNS
+        );
+
+        let rows: Vec<Row> = self.pool.get_conn()?.exec(query, (id,))?;
+        Ok(rows.into_iter().map(department_from_row).collect())
+    }
+
+    fn create(&self, department: &CreateDepartmentRequest) -> Result<String, ApiError> {
+        let id = Uuid::new_v4().to_string();
+
+        // A new department has nothing below it yet, fk_dept_parent covers an unknown parent.
+        // The head is set up after the insert, moving them in needs the department to exist.
+        let mut conn = self.pool.get_conn()?;
+        let mut tx = conn.start_transaction(TxOpts::default())?;
+        if let Some(ref parent_id) = department.parent_id {
+            check_not_deleted(&mut tx, &DEPARTMENTS, "parent_id", parent_id)?;
+        }
+        tx.exec_drop(
+            "INSERT INTO departments (id, name, head_id, parent_id) VALUES (?, ?, ?, ?)",
+            (
+                &id,
+                &department.name,
+                &department.head_id,
+                &department.parent_id,
+            ),
+        )?;
+        if let Some(ref head_id) = department.head_id {
+            let promote = department.promote_head.unwrap_or(false);
+            set_head_locked(&mut tx, &id, head_id, promote)?;
+        }
+        tx.commit()?;
+        Ok(id)
+    }
+
+    fn update(&self, id: &str, changes: &UpdateDepartmentRequest) -> Result<Department, ApiError> {
+        let mut updates = Vec::new();
+        let mut params: Vec<Value> = Vec::new();
+
+        if let Some(ref name) = changes.name {
+            updates.push("name = ?");
+            params.push(name.clone().into());
+        }
+        if let Some(ref head_id) = changes.head_id {
+            updates.push("head_id = ?");
+            params.push(head_id.clone().into());
+        }
+        if let Some(ref parent_id) = changes.parent_id {
+            updates.push("parent_id = ?");
+            params.push(parent_id.clone().into());
+        }
+
+        update_by_id(&self.pool, &DEPARTMENTS, id, updates, params, |tx| {
+            if let Some(ref parent_id) = changes.parent_id {
+                check_parent_locked(tx, id, parent_id)?;
+            }
+            match changes.head_id {
+                Some(ref head_id) => {
+                    set_head_locked(tx, id, head_id, changes.promote_head.unwrap_or(false))
+                }
+                None => Ok(()),
+            }
+        })
+    }
+
+    fn delete(
+        &self,
+        id: &str,
+        employees: DepartmentEmployees,
+    ) -> Result<DepartmentDeletion, ApiError> {
+        let mut conn = self.pool.get_conn()?;
+        let mut tx = conn.start_transaction(TxOpts::default())?;
+        let department = lock_by_id(&mut tx, &DEPARTMENTS, id, false)?;
+
+        // The lock on the department holds back new employees, fk_emp_department checks it
+        let active_employees: Option<u64> = tx.exec_first(
+            "SELECT COUNT(*) FROM employees WHERE department_id = ? AND active = TRUE",
+            (id,),
+        )?;
+        let target = match employees {
+            DepartmentEmployees::ReassignTo(target_id) => {
+                let query = format!(
+                    "SELECT {} FROM departments WHERE id = ? AND deleted_at IS NULL FOR SHARE",
+                    DEPARTMENT_COLUMNS
+                );
+                let row: Option<Row> = tx.exec_first(query, (target_id,))?;
+                row.map(department_from_row)
+            }
+            _ => None,
+        };
+        check_department_deletion(
+            id,
+            employees,
+            active_employees.unwrap_or(0),
+            target.as_ref(),
+        )?;
+
+        let mut deletion = DepartmentDeletion {
+            department,
+            reassigned_employees: 0,
+            unassigned_employees: 0,
+            detached_subdepartments: 0,
+        };
+        match employees {
+            DepartmentEmployees::ReassignTo(target_id) => {
+                tx.exec_drop(
+                    "UPDATE employees SET department_id = ? WHERE department_id = ? AND active = TRUE",
+                    (target_id, id),
+                )?;
+                deletion.reassigned_employees = tx.affected_rows();
+            }
+            _ => {
+                tx.exec_drop(
+                    "UPDATE employees SET department_id = NULL WHERE department_id = ? AND active = TRUE",
+                    (id,),
+                )?;
+                deletion.unassigned_employees = tx.affected_rows();
+            }
+        }
+        tx.exec_drop(
+            "UPDATE departments SET parent_id = NULL WHERE parent_id = ? AND deleted_at IS NULL",
+            (id,),
+        )?;
+        deletion.detached_subdepartments = tx.affected_rows();
+
+        tx.exec_drop(
+            "UPDATE departments SET deleted_at = NOW() WHERE id = ?",
+            (id,),
+        )?;
+        deletion.department = lock_by_id(&mut tx, &DEPARTMENTS, id, true)?;
+        tx.commit()?;
+        Ok(deletion)
+    }
+
+    fn restore(&self, id: &str) -> Result<Department, ApiError> {
+        restore_by_id(&self.pool, &DEPARTMENTS, id, |tx, department| {
+            let Some(ref parent_id) = department.parent_id else {
+                return Ok(());
+            };
+            let query = "SELECT deleted_at IS NULL FROM departments WHERE id = ? FOR SHARE";
+            let parent_live: Option<bool> = tx.exec_first(query, (parent_id,))?;
+            if parent_live != Some(true) {
+                tx.exec_drop(
+                    "UPDATE departments SET parent_id = NULL WHERE id = ?",
+                    (id,),
+                )?;
+            }
+            Ok(())
+        })
+    }
+}
+
+pub struct MysqlSalaryGradeRepository {
+    pool: DbPool,
+}
+
+impl MysqlSalaryGradeRepository {
+    pub fn new(pool: DbPool) -> Self {
+        MysqlSalaryGradeRepository { pool }
+    }
+}
+
+impl SalaryGradeRepository for MysqlSalaryGradeRepository {
+    fn list(&self, include_deleted: bool) -> Result<Vec<SalaryGrade>, ApiError> {
+        let filter = if include_deleted {
+            ""
+        } else {
+            " WHERE deleted_at IS NULL"
+        };
+        let query = format!(
+            "SELECT {} FROM salary_grades{}",
+            SALARY_GRADE_COLUMNS, filter
+        );
+
+        let rows: Vec<Row> = self.pool.get_conn()?.query(query)?;
+        Ok(rows.into_iter().map(salary_grade_from_row).collect())
+    }
+
+    fn find_by_id(&self, id: &str) -> Result<Option<SalaryGrade>, ApiError> {
+        let query = format!(
+            "SELECT {} FROM salary_grades WHERE id = ?",
+            SALARY_GRADE_COLUMNS
+        );
+
+        let row: Option<Row> = self.pool.get_conn()?.exec_first(query, (id,))?;
+        Ok(row.map(salary_grade_from_row))
+    }
+
+    fn create(&self, grade: &CreateSalaryGradeRequest) -> Result<String, ApiError> {
+        let id = Uuid::new_v4().to_string();
+
+        self.pool.get_conn()?.exec_drop(
+            "INSERT INTO salary_grades (id, code, base_salary, description) VALUES (?, ?, ?, ?)",
+            (&id, &grade.code, grade.base_salary, &grade.description),
+        )?;
+        Ok(id)
+    }
+
+    fn update(
+        &self,
+        id: &str,
+        changes: &UpdateSalaryGradeRequest,
+    ) -> Result<SalaryGrade, ApiError> {
+        let mut updates = Vec::new();
+        let mut params: Vec<Value> = Vec::new();
+
+        if let Some(ref code) = changes.code {
+            updates.push("code = ?");
+            params.push(code.clone().into());
+        }
+        if let Some(base_salary) = changes.base_salary {
+            updates.push("base_salary = ?");
+            params.push(base_salary.into());
+        }
+        if let Some(ref description) = changes.description {
+            updates.push("description = ?");
+            params.push(description.clone().into());
+        }
+
+        update_by_id(&self.pool, &SALARY_GRADES, id, updates, params, |_| Ok(()))
+    }
+
+    fn delete(&self, id: &str) -> Result<SalaryGrade, ApiError> {
+        update_by_id(
+            &self.pool,
+            &SALARY_GRADES,
+            id,
+            vec!["deleted_at = NOW()"],
+            Vec::new(),
+            |_| Ok(()),
+        )
+    }
+
+    fn restore(&self, id: &str) -> Result<SalaryGrade, ApiError> {
+        restore_by_id(&self.pool, &SALARY_GRADES, id, |_, _| Ok(()))
+    }
+}
@@ -0,0 +1,124 @@
+// Retention of terminated employees - once the retention period after `deleted_at` is over an
+// employee is anonymized or deleted for good, by `backend purge` or periodically in the server
+
+use crate::db::blocking;
+use crate::error::ApiError;
+use crate::repository::{EmployeeRepository, PurgeMode};
+use actix_web::web;
+use chrono::{DateTime, Months, Utc};
+use std::fmt;
+use std::time::Duration;
+
+pub const DEFAULT_RETENTION_YEARS: u32 = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetentionPolicy {
+    /// Years an employee is kept after `deleted_at`
+    pub retention_years: u32,
+    pub mode: PurgeMode,
+}
+
+impl Default for RetentionPolicy {
+    fn default() -> Self {
+        RetentionPolicy {
+            retention_years: DEFAULT_RETENTION_YEARS,
+            mode: PurgeMode::default(),
+        }
+    }
+}
+
+impl RetentionPolicy {
+    /// Employees deleted before this point are due, formatted like `deleted_at`
+    pub fn cutoff(&self, now: DateTime<Utc>) -> String {
+        now.checked_sub_months(Months::new(self.retention_years.saturating_mul(12)))
+            .unwrap_or(DateTime::<Utc>::MIN_UTC)
+            .format("%Y-%m-%dT%H:%M:%SZ")
+            .to_string()
+    }
+}
+
+/// What one purge run did, written to the log when it is done
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PurgeSummary {
+    pub mode: PurgeMode,
+    pub cutoff: String,
+    /// Employees anonymized or deleted
+    pub purged: u64,
+    /// Employees that could not be purged, they are tried again on the next run
+    pub failed: u64,
+    /// Employees restored or otherwise no longer due by the time they were purged
+    pub skipped: u64,
+    pub reassigned_reports: u64,
+    pub unassigned_reports: u64,
+    pub cleared_heads: u64,
+}
+
+impl fmt::Display for PurgeSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let action = match self.mode {
+            PurgeMode::Anonymize => "anonymized",
+            PurgeMode::Delete => "deleted",
+        };
+        write!(
+            f,
+            "Retention purge: {} employees deleted before {} {}, {} failed, {} no longer due; {} reports reassigned, {} left without manager, {} department heads cleared",
+            self.purged,
+            self.cutoff,
+            action,
+            self.failed,
+            self.skipped,
+            self.reassigned_reports,
+            self.unassigned_reports,
+            self.cleared_heads
+        )
+    }
+}
+
+/// Purges every employee whose retention period is over at `now`. An employee that fails is
+/// logged and counted, the others are still purged.
+pub fn purge_expired(
+    employees: &dyn EmployeeRepository,
+    policy: &RetentionPolicy,
+    now: DateTime<Utc>,
+) -> Result<PurgeSummary, ApiError> {
+    let mut summary = PurgeSummary {
+        mode: policy.mode,
+        cutoff: policy.cutoff(now),
+        ..PurgeSummary::default()
+    };
+
+    for id in employees.expired(&summary.cutoff, policy.mode)? {
+        match employees.purge(&id, policy.mode, &summary.cutoff) {
+            Ok(None) => summary.skipped += 1,
+            Ok(Some(references)) => {
+                summary.purged += 1;
+                summary.reassigned_reports += references.reassigned_reports;
+                summary.unassigned_reports += references.unassigned_reports;
+                summary.cleared_heads += references.cleared_heads;
+            }
+            Err(err) => {
+                eprintln!("Retention purge of employee {} failed: {}", id, err);
+                summary.failed += 1;
+            }
+        }
+    }
+    Ok(summary)
+}
+
+/// Runs the purge right away and then every `interval`, for as long as the server is up
+pub async fn run_periodically(
+    employees: web::Data<dyn EmployeeRepository>,
+    policy: RetentionPolicy,
+    interval: Duration,
+) {
+    let mut ticks = actix_web::rt::time::interval(interval);
+    loop {
+        ticks.tick().await;
+
+        let employees = employees.clone();
+        match blocking(move || purge_expired(employees.get_ref(), &policy, Utc::now())).await {
+            Ok(summary) => println!("{}", summary),
+            Err(err) => eprintln!("Retention purge failed: {}", err),
+        }
+    }
+}
@@ -0,0 +1,53 @@
+// Sample data for development and demos - loaded by `backend seed`, never by `migrate up`,
+// so a production database only ever gets the schema
+
+use crate::auth::account::generate_secret_token;
+use crate::auth::password::hash_password;
+use crate::db::DbPool;
+use crate::error::ApiError;
+use crate::migrations::split_statements;
+use mysql::TxOpts;
+use mysql::prelude::*;
+
+pub const SAMPLE_DATA: &str = include_str!("../seeds/sample_data.sql");
+pub const REMOVE_SAMPLE_DATA: &str = include_str!("../seeds/remove_sample_data.sql");
+
+/// Alice Johnson, the admin of the sample data
+pub const SAMPLE_ADMIN_ID: &str = "750e8400-e29b-41d4-a716-446655440001";
+pub const SAMPLE_ADMIN_EMAIL: &str = "alice.johnson@company.com";
+
+// 96 random bits, short enough to type in once
+const PASSWORD_LENGTH: usize = 24;
+
+fn run_script(tx: &mut mysql::Transaction<'_>, script: &str) -> Result<(), ApiError> {
+    for statement in split_statements(script) {
+        tx.query_drop(&statement)?;
+    }
+    Ok(())
+}
+
+/// Loads the sample rows and a login for the sample admin in one transaction. The password is
+/// generated on every run and returned so it can be shown once, only its hash is stored.
+pub fn load(pool: &DbPool) -> Result<String, ApiError> {
+    let password = generate_secret_token()[..PASSWORD_LENGTH].to_string();
+    let password_hash = hash_password(&password)?;
+
+    let mut conn = pool.get_conn()?;
+    let mut tx = conn.start_transaction(TxOpts::default())?;
+    run_script(&mut tx, SAMPLE_DATA)?;
+    tx.exec_drop(
+        "INSERT INTO users (employee_id, name, email, password_hash) VALUES (?, 'Alice Johnson', ?, ?)",
+        (SAMPLE_ADMIN_ID, SAMPLE_ADMIN_EMAIL, password_hash),
+    )?;
+    tx.commit()?;
+    Ok(password)
+}
+
+/// Deletes the sample rows and the sample admin's login, data created through the API is kept
+pub fn remove(pool: &DbPool) -> Result<(), ApiError> {
+    let mut conn = pool.get_conn()?;
+    let mut tx = conn.start_transaction(TxOpts::default())?;
+    run_script(&mut tx, REMOVE_SAMPLE_DATA)?;
+    tx.commit()?;
+    Ok(())
+}
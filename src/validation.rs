@@ -0,0 +1,32 @@
+// Custom validation rules used by the request models, the simple ones (length, email, range)
+// come straight from the validator derive
+
+use chrono::NaiveDate;
+use std::borrow::Cow;
+use validator::ValidationError;
+
+// Largest value that fits into a DECIMAL(12,2) column
+pub const MAX_SALARY: f64 = 9_999_999_999.99;
+
+fn error(code: &'static str, message: &'static str) -> ValidationError {
+    ValidationError::new(code).with_message(Cow::Borrowed(message))
+}
+
+pub fn not_blank(value: &str) -> Result<(), ValidationError> {
+    if value.trim().is_empty() {
+        return Err(error("blank", "must not be blank"));
+    }
+    Ok(())
+}
+
+pub fn uuid(value: &str) -> Result<(), ValidationError> {
+    uuid::Uuid::parse_str(value)
+        .map(|_| ())
+        .map_err(|_| error("uuid", "must be a valid UUID"))
+}
+
+pub fn iso_date(value: &str) -> Result<(), ValidationError> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map(|_| ())
+        .map_err(|_| error("date", "must be a valid date in YYYY-MM-DD format"))
+}
@@ -0,0 +1,139 @@
+// Tests for user accounts: secret free serialization, password rules and reset tokens
+
+use actix_web::http::StatusCode;
+use actix_web::test as actix_test;
+use actix_web::{App, HttpResponse, middleware, web};
+use backend::auth::account::*;
+use backend::auth::{JwtConfig, require_auth};
+use backend::models::*;
+use validator::Validate;
+
+#[test]
+fn test_user_serializes_without_secrets() {
+    let user = User {
+        id: 1,
+        name: "Alice Johnson".to_string(),
+        email: "alice.johnson@company.com".to_string(),
+        employee_id: Some("750e8400-e29b-41d4-a716-446655440001".to_string()),
+        failed_login_attempts: 2,
+        locked_until: None,
+    };
+
+    let json = serde_json::to_value(&user).unwrap();
+    let mut keys: Vec<&str> = json
+        .as_object()
+        .unwrap()
+        .keys()
+        .map(|k| k.as_str())
+        .collect();
+    keys.sort();
+
+    assert_eq!(
+        keys,
+        vec![
+            "email",
+            "employee_id",
+            "failed_login_attempts",
+            "id",
+            "locked_until",
+            "name"
+        ]
+    );
+}
+
+#[test]
+fn test_create_user_password_is_optional_but_checked() {
+    let mut req = CreateUserRequest {
+        name: "Bob Smith".to_string(),
+        email: "bob.smith@company.com".to_string(),
+        employee_id: Some("750e8400-e29b-41d4-a716-446655440002".to_string()),
+        password: None,
+    };
+    assert!(req.validate().is_ok());
+
+    req.password = Some("short".to_string());
+    let errors = req.validate().unwrap_err();
+    assert!(errors.field_errors().contains_key("password"));
+
+    req.password = Some("long enough".to_string());
+    req.employee_id = Some("emp-2".to_string());
+    let errors = req.validate().unwrap_err();
+    assert!(errors.field_errors().contains_key("employee_id"));
+}
+
+#[test]
+fn test_new_password_length_rules() {
+    let change = ChangePasswordRequest {
+        current_password: "admin123".to_string(),
+        new_password: "x".repeat(129),
+    };
+    let reset = ResetPasswordRequest {
+        reset_token: "token".to_string(),
+        new_password: "1234567".to_string(),
+    };
+
+    assert!(change.validate().is_err());
+    assert!(reset.validate().is_err());
+}
+
+#[test]
+fn test_secret_tokens_are_random_hex() {
+    let first = generate_secret_token();
+    let second = generate_secret_token();
+
+    assert_eq!(first.len(), 64);
+    assert!(first.chars().all(|c| c.is_ascii_hexdigit()));
+    assert_ne!(first, second);
+}
+
+#[test]
+fn test_secret_token_hash_is_stable_and_not_the_token() {
+    let token = generate_secret_token();
+
+    assert_eq!(hash_secret_token(&token), hash_secret_token(&token));
+    assert_ne!(hash_secret_token(&token), token);
+    assert_eq!(hash_secret_token(&token).len(), 64);
+}
+
+#[test]
+fn test_default_account_policy() {
+    let policy = AccountPolicy::default();
+
+    assert_eq!(policy.max_failed_logins, 5);
+    assert_eq!(policy.lockout_seconds, 900);
+    assert_eq!(policy.reset_token_ttl_seconds, 3600);
+}
+
+#[actix_web::test]
+async fn test_reset_password_is_public() {
+    let app = actix_test::init_service(
+        App::new()
+            .app_data(web::Data::new(JwtConfig::new("test-secret")))
+            .wrap(middleware::from_fn(require_auth))
+            .route(
+                "/api/auth/reset-password",
+                web::post().to(|| async { HttpResponse::Ok().finish() }),
+            )
+            .route(
+                "/api/users/1/password",
+                web::post().to(|| async { HttpResponse::Ok().finish() }),
+            ),
+    )
+    .await;
+
+    let reset = actix_test::TestRequest::post()
+        .uri("/api/auth/reset-password")
+        .to_request();
+    let change = actix_test::TestRequest::post()
+        .uri("/api/users/1/password")
+        .to_request();
+
+    assert_eq!(
+        actix_test::call_service(&app, reset).await.status(),
+        StatusCode::OK
+    );
+    assert_eq!(
+        actix_test::call_service(&app, change).await.status(),
+        StatusCode::UNAUTHORIZED
+    );
+}
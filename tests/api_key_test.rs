@@ -0,0 +1,118 @@
+// Tests for API key scopes, key format and the scope required per endpoint
+
+use actix_web::http::Method;
+use backend::auth::api_key::*;
+use backend::models::*;
+use validator::Validate;
+
+#[test]
+fn test_scope_serializes_as_resource_and_access() {
+    for scope in ApiScope::ALL {
+        let json = serde_json::to_string(&scope).unwrap();
+        assert_eq!(json, format!("\"{}\"", scope.as_str()));
+        assert_eq!(scope.as_str().parse::<ApiScope>().unwrap(), scope);
+    }
+    assert_eq!(ApiScope::SalaryGradesWrite.as_str(), "salary_grades:write");
+}
+
+#[test]
+fn test_unknown_scope_rejected() {
+    let json = r#"{"name":"payroll","scopes":["employees:delete"]}"#;
+
+    assert!(serde_json::from_str::<CreateApiKeyRequest>(json).is_err());
+    assert!("admin".parse::<ApiScope>().is_err());
+}
+
+#[test]
+fn test_create_request_needs_name_and_scope() {
+    let req = CreateApiKeyRequest {
+        name: " ".to_string(),
+        scopes: vec![],
+    };
+
+    let errors = req.validate().unwrap_err();
+    let fields = errors.field_errors();
+    assert!(fields.contains_key("name"));
+    assert!(fields.contains_key("scopes"));
+}
+
+#[test]
+fn test_scopes_column_roundtrip() {
+    let scopes = vec![ApiScope::EmployeesRead, ApiScope::SalaryGradesWrite];
+
+    let column = scopes_to_column(&scopes);
+
+    assert_eq!(column, "employees:read,salary_grades:write");
+    assert_eq!(scopes_from_column(&column), scopes);
+    assert_eq!(
+        scopes_from_column("employees:read,reports:read"),
+        vec![ApiScope::EmployeesRead]
+    );
+}
+
+#[test]
+fn test_required_scope_per_endpoint() {
+    let cases = [
+        (Method::GET, "/api/employees", Some(ApiScope::EmployeesRead)),
+        (
+            Method::PUT,
+            "/api/employees/1/salary-grade",
+            Some(ApiScope::EmployeesWrite),
+        ),
+        (
+            Method::GET,
+            "/api/departments/1/employees",
+            Some(ApiScope::EmployeesRead),
+        ),
+        (Method::GET, "/api/org-chart", Some(ApiScope::EmployeesRead)),
+        (
+            Method::GET,
+            "/api/departments",
+            Some(ApiScope::DepartmentsRead),
+        ),
+        (
+            Method::DELETE,
+            "/api/departments/1",
+            Some(ApiScope::DepartmentsWrite),
+        ),
+        (
+            Method::POST,
+            "/api/salary-grades",
+            Some(ApiScope::SalaryGradesWrite),
+        ),
+        (Method::GET, "/api/employees/1/personal-data", None),
+        (Method::POST, "/api/employees/1/anonymize", None),
+        (Method::GET, "/api/users", None),
+        (Method::POST, "/api/api-keys", None),
+        (Method::GET, "/api/employeesX", None),
+    ];
+
+    for (method, path, expected) in cases {
+        assert_eq!(
+            required_scope(&method, path),
+            expected,
+            "{} {}",
+            method,
+            path
+        );
+    }
+}
+
+#[test]
+fn test_generated_keys_and_prefix() {
+    let key = generate_api_key();
+
+    assert!(key.starts_with("pmk_"));
+    assert_eq!(key.len(), 4 + 64);
+    assert_ne!(key, generate_api_key());
+    assert_eq!(key_prefix(&key), key[..12]);
+}
+
+#[test]
+fn test_service_user_acts_as_admin() {
+    let user = service_user("key-1");
+
+    assert_eq!(user.role, Role::Admin);
+    assert_eq!(user.employee_id, "api-key:key-1");
+    assert!(user.department_id.is_none());
+}
@@ -0,0 +1,216 @@
+// Tests for password hashing, JWT issuing/validation and the auth middleware
+
+use actix_web::http::{StatusCode, header};
+use actix_web::test as actix_test;
+use actix_web::{App, HttpResponse, middleware, web};
+use backend::auth::password::{hash_password, verify_password, verify_password_or_dummy};
+use backend::auth::*;
+use backend::error::problem_instance;
+use backend::models::Role;
+
+const SECRET: &str = "test-secret";
+const ALICE_ID: &str = "750e8400-e29b-41d4-a716-446655440001";
+
+fn jwt() -> JwtConfig {
+    JwtConfig::new(SECRET)
+}
+
+fn alice() -> AuthenticatedUser {
+    AuthenticatedUser {
+        employee_id: ALICE_ID.to_string(),
+        role: Role::Admin,
+        department_id: None,
+    }
+}
+
+#[test]
+fn test_password_hash_roundtrip() {
+    let hash = hash_password("s3cret!").unwrap();
+
+    assert!(hash.starts_with("$argon2id$"));
+    assert!(verify_password("s3cret!", &hash));
+    assert!(!verify_password("wrong", &hash));
+}
+
+#[test]
+fn test_password_hash_is_salted() {
+    assert_ne!(
+        hash_password("same").unwrap(),
+        hash_password("same").unwrap()
+    );
+}
+
+#[test]
+fn test_verify_rejects_malformed_hash() {
+    assert!(!verify_password("admin123", "not-a-hash"));
+}
+
+#[test]
+fn test_verify_without_account_always_fails() {
+    let hash = hash_password("s3cret!").unwrap();
+
+    assert!(verify_password_or_dummy("s3cret!", Some(&hash)));
+    assert!(!verify_password_or_dummy("wrong", Some(&hash)));
+    assert!(!verify_password_or_dummy("s3cret!", None));
+    assert!(!verify_password_or_dummy("", None));
+}
+
+#[test]
+fn test_issue_and_verify_access_token() {
+    let config = jwt();
+    let token = config.issue(&alice(), TokenType::Access).unwrap();
+
+    let claims = config.verify(&token, TokenType::Access).unwrap();
+
+    assert_eq!(claims.sub, ALICE_ID);
+    assert_eq!(claims.role, Role::Admin);
+    assert_eq!(claims.department_id, None);
+    assert_eq!(claims.exp - claims.iat, config.access_ttl_seconds);
+}
+
+#[test]
+fn test_refresh_token_not_accepted_as_access_token() {
+    let config = jwt();
+    let refresh = config.issue(&alice(), TokenType::Refresh).unwrap();
+
+    assert!(config.verify(&refresh, TokenType::Access).is_err());
+    assert!(config.verify(&refresh, TokenType::Refresh).is_ok());
+}
+
+#[test]
+fn test_token_signed_with_other_secret_rejected() {
+    let token = JwtConfig::new("other-secret")
+        .issue(&alice(), TokenType::Access)
+        .unwrap();
+
+    assert!(jwt().verify(&token, TokenType::Access).is_err());
+}
+
+#[test]
+fn test_expired_token_rejected() {
+    let config = jwt().with_ttls(-10, -10);
+    let token = config.issue(&alice(), TokenType::Access).unwrap();
+
+    assert!(config.verify(&token, TokenType::Access).is_err());
+}
+
+async fn whoami(user: AuthenticatedUser) -> HttpResponse {
+    HttpResponse::Ok().body(format!("{}:{}", user.employee_id, user.role))
+}
+
+macro_rules! protected_app {
+    () => {
+        actix_test::init_service(
+            App::new()
+                .app_data(web::Data::new(jwt()))
+                .wrap(middleware::from_fn(require_auth))
+                .wrap(middleware::from_fn(problem_instance))
+                .route("/api/me", web::get().to(whoami))
+                .route(
+                    "/api/auth/login",
+                    web::post().to(|| async { HttpResponse::Ok().finish() }),
+                )
+                .route(
+                    "/health",
+                    web::get().to(|| async { HttpResponse::Ok().finish() }),
+                ),
+        )
+        .await
+    };
+}
+
+#[actix_web::test]
+async fn test_missing_token_is_unauthorized_problem() {
+    let app = protected_app!();
+
+    let req = actix_test::TestRequest::get().uri("/api/me").to_request();
+    let resp = actix_test::call_service(&app, req).await;
+
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+    assert_eq!(
+        resp.headers().get(header::WWW_AUTHENTICATE).unwrap(),
+        "Bearer"
+    );
+    let body: serde_json::Value = actix_test::read_body_json(resp).await;
+    assert_eq!(body["code"], "unauthorized");
+    assert_eq!(body["instance"], "/api/me");
+}
+
+#[actix_web::test]
+async fn test_invalid_token_is_unauthorized() {
+    let app = protected_app!();
+
+    let req = actix_test::TestRequest::get()
+        .uri("/api/me")
+        .insert_header((header::AUTHORIZATION, "Bearer garbage"))
+        .to_request();
+    let resp = actix_test::call_service(&app, req).await;
+
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[actix_web::test]
+async fn test_valid_token_reaches_handler() {
+    let app = protected_app!();
+    let token = jwt().issue(&alice(), TokenType::Access).unwrap();
+
+    let req = actix_test::TestRequest::get()
+        .uri("/api/me")
+        .insert_header((header::AUTHORIZATION, format!("Bearer {}", token)))
+        .to_request();
+    let resp = actix_test::call_service(&app, req).await;
+
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body = actix_test::read_body(resp).await;
+    assert_eq!(body, format!("{}:Admin", ALICE_ID).as_bytes());
+}
+
+#[actix_web::test]
+async fn test_login_and_health_are_public() {
+    let app = protected_app!();
+
+    let login = actix_test::TestRequest::post()
+        .uri("/api/auth/login")
+        .to_request();
+    let health = actix_test::TestRequest::get().uri("/health").to_request();
+
+    assert_eq!(
+        actix_test::call_service(&app, login).await.status(),
+        StatusCode::OK
+    );
+    assert_eq!(
+        actix_test::call_service(&app, health).await.status(),
+        StatusCode::OK
+    );
+}
+
+#[actix_web::test]
+async fn test_accounts_without_mysql_are_unavailable_problems() {
+    // Like STORAGE=memory: no DbPool is registered
+    let app = actix_test::init_service(
+        App::new()
+            .app_data(web::Data::new(jwt()))
+            .wrap(middleware::from_fn(require_auth))
+            .wrap(middleware::from_fn(problem_instance))
+            .service(backend::handlers::login)
+            .route("/api/me", web::get().to(whoami)),
+    )
+    .await;
+
+    let login = actix_test::TestRequest::post()
+        .uri("/api/auth/login")
+        .set_json(serde_json::json!({"email": "alice@company.com", "password": "secret"}))
+        .to_request();
+    let api_key = actix_test::TestRequest::get()
+        .uri("/api/me")
+        .insert_header(("X-Api-Key", "some-key"))
+        .to_request();
+
+    for req in [login, api_key] {
+        let resp = actix_test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::SERVICE_UNAVAILABLE);
+        let body: serde_json::Value = actix_test::read_body_json(resp).await;
+        assert_eq!(body["code"], "service_unavailable");
+        assert!(body["detail"].as_str().unwrap().contains("MySQL"));
+    }
+}
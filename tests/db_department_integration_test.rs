@@ -5,15 +5,15 @@ use common::*;
 
 #[test]
 fn test_get_departments_with_database() {
-    let pool = setup_test_db().unwrap();
+    let storage = setup_test_storage();
 
-    let initial_count = count_departments(&pool).unwrap();
+    let initial_count = count_departments(&storage).unwrap();
 
     // Create test departments
-    let dept1_id = create_test_department(&pool, "IT Department").unwrap();
-    let dept2_id = create_test_department(&pool, "HR Department").unwrap();
+    let dept1_id = create_test_department(&storage, "IT Department").unwrap();
+    let dept2_id = create_test_department(&storage, "HR Department").unwrap();
 
-    let final_count = count_departments(&pool).unwrap();
+    let final_count = count_departments(&storage).unwrap();
     assert_eq!(
         final_count,
         initial_count + 2,
@@ -21,35 +21,35 @@ fn test_get_departments_with_database() {
     );
 
     // Cleanup
-    delete_test_department(&pool, &dept1_id).ok();
-    delete_test_department(&pool, &dept2_id).ok();
+    delete_test_department(&storage, &dept1_id).ok();
+    delete_test_department(&storage, &dept2_id).ok();
 }
 
 #[test]
 fn test_create_department_in_database() {
-    let pool = setup_test_db().unwrap();
+    let storage = setup_test_storage();
 
-    let dept_id = create_test_department(&pool, "Finance Department").unwrap();
+    let dept_id = create_test_department(&storage, "Finance Department").unwrap();
 
     assert!(
-        department_exists(&pool, &dept_id).unwrap(),
+        department_exists(&storage, &dept_id).unwrap(),
         "Department should exist in database"
     );
 
     // Cleanup
-    delete_test_department(&pool, &dept_id).ok();
+    delete_test_department(&storage, &dept_id).ok();
 }
 
 #[test]
 fn test_department_with_employees() {
-    let pool = setup_test_db().unwrap();
+    let storage = setup_test_storage();
 
     // Create a department
-    let dept_id = create_test_department(&pool, "Engineering").unwrap();
+    let dept_id = create_test_department(&storage, "Engineering").unwrap();
 
     // Create multiple employees in this department
     let emp1_id = create_test_employee(
-        &pool,
+        &storage,
         "Alice",
         "Smith",
         &format!("alice_{}@test.com", uuid::Uuid::new_v4()),
@@ -59,7 +59,7 @@ fn test_department_with_employees() {
     .unwrap();
 
     let emp2_id = create_test_employee(
-        &pool,
+        &storage,
         "Bob",
         "Johnson",
         &format!("bob_{}@test.com", uuid::Uuid::new_v4()),
@@ -69,43 +69,43 @@ fn test_department_with_employees() {
     .unwrap();
 
     // Verify department and employees exist
-    assert!(department_exists(&pool, &dept_id).unwrap());
-    assert!(employee_exists(&pool, &emp1_id).unwrap());
-    assert!(employee_exists(&pool, &emp2_id).unwrap());
+    assert!(department_exists(&storage, &dept_id).unwrap());
+    assert!(employee_exists(&storage, &emp1_id).unwrap());
+    assert!(employee_exists(&storage, &emp2_id).unwrap());
 
     // Cleanup
-    delete_test_employee(&pool, &emp1_id).ok();
-    delete_test_employee(&pool, &emp2_id).ok();
-    delete_test_department(&pool, &dept_id).ok();
+    delete_test_employee(&storage, &emp1_id).ok();
+    delete_test_employee(&storage, &emp2_id).ok();
+    delete_test_department(&storage, &dept_id).ok();
 }
 
 #[test]
 fn test_department_without_head() {
-    let pool = setup_test_db().unwrap();
+    let storage = setup_test_storage();
 
-    let dept_id = create_test_department(&pool, "Marketing").unwrap();
+    let dept_id = create_test_department(&storage, "Marketing").unwrap();
 
     assert!(
-        department_exists(&pool, &dept_id).unwrap(),
+        department_exists(&storage, &dept_id).unwrap(),
         "Department without head should exist"
     );
 
     // Cleanup
-    delete_test_department(&pool, &dept_id).ok();
+    delete_test_department(&storage, &dept_id).ok();
 }
 
 #[test]
 fn test_multiple_departments() {
-    let pool = setup_test_db().unwrap();
+    let storage = setup_test_storage();
 
-    let initial_count = count_departments(&pool).unwrap();
+    let initial_count = count_departments(&storage).unwrap();
 
     // Create multiple departments
     let dept_ids: Vec<String> = (0..5)
-        .map(|i| create_test_department(&pool, &format!("Dept_{}", i)).unwrap())
+        .map(|i| create_test_department(&storage, &format!("Dept_{}", i)).unwrap())
         .collect();
 
-    let final_count = count_departments(&pool).unwrap();
+    let final_count = count_departments(&storage).unwrap();
     assert_eq!(
         final_count,
         initial_count + 5,
@@ -115,7 +115,7 @@ fn test_multiple_departments() {
     // Verify all exist
     for dept_id in &dept_ids {
         assert!(
-            department_exists(&pool, dept_id).unwrap(),
+            department_exists(&storage, dept_id).unwrap(),
             "Department {} should exist",
             dept_id
         );
@@ -123,7 +123,7 @@ fn test_multiple_departments() {
 
     // Cleanup
     for dept_id in dept_ids {
-        delete_test_department(&pool, &dept_id).ok();
+        delete_test_department(&storage, &dept_id).ok();
     }
 }
 
@@ -133,6 +133,8 @@ fn test_create_department_request_validation() {
     let request = CreateDepartmentRequest {
         name: "Test Department".to_string(),
         head_id: None,
+        parent_id: None,
+        promote_head: None,
     };
 
     assert_eq!(request.name, "Test Department");
@@ -145,6 +147,8 @@ fn test_update_department_request_structure() {
     let request = UpdateDepartmentRequest {
         name: Some("Updated Department".to_string()),
         head_id: Some("some-head-id".to_string()),
+        parent_id: None,
+        promote_head: None,
     };
 
     assert!(request.name.is_some());
@@ -153,19 +157,19 @@ fn test_update_department_request_structure() {
 
 #[test]
 fn test_department_crud_operations() {
-    let pool = setup_test_db().unwrap();
+    let storage = setup_test_storage();
 
-    let initial_count = count_departments(&pool).unwrap();
+    let initial_count = count_departments(&storage).unwrap();
 
     // CREATE
-    let dept_id = create_test_department(&pool, "Operations").unwrap();
+    let dept_id = create_test_department(&storage, "Operations").unwrap();
     assert!(
-        department_exists(&pool, &dept_id).unwrap(),
+        department_exists(&storage, &dept_id).unwrap(),
         "Department should be created"
     );
 
     // READ (verify count increased)
-    let after_create_count = count_departments(&pool).unwrap();
+    let after_create_count = count_departments(&storage).unwrap();
     assert_eq!(
         after_create_count,
         initial_count + 1,
@@ -173,14 +177,14 @@ fn test_department_crud_operations() {
     );
 
     // DELETE
-    delete_test_department(&pool, &dept_id).ok();
+    delete_test_department(&storage, &dept_id).ok();
     assert!(
-        !department_exists(&pool, &dept_id).unwrap(),
+        !department_exists(&storage, &dept_id).unwrap(),
         "Department should be deleted"
     );
 
     // Verify count returned to initial
-    let after_delete_count = count_departments(&pool).unwrap();
+    let after_delete_count = count_departments(&storage).unwrap();
     assert_eq!(
         after_delete_count, initial_count,
         "Count should return to initial"
@@ -189,21 +193,21 @@ fn test_department_crud_operations() {
 
 #[test]
 fn test_department_count_tracking() {
-    let pool = setup_test_db().unwrap();
+    let storage = setup_test_storage();
 
-    let initial_count = count_departments(&pool).unwrap();
+    let initial_count = count_departments(&storage).unwrap();
 
     // Add departments
-    let dept1_id = create_test_department(&pool, "Logistics").unwrap();
-    assert_eq!(count_departments(&pool).unwrap(), initial_count + 1);
+    let dept1_id = create_test_department(&storage, "Logistics").unwrap();
+    assert_eq!(count_departments(&storage).unwrap(), initial_count + 1);
 
-    let dept2_id = create_test_department(&pool, "Legal").unwrap();
-    assert_eq!(count_departments(&pool).unwrap(), initial_count + 2);
+    let dept2_id = create_test_department(&storage, "Legal").unwrap();
+    assert_eq!(count_departments(&storage).unwrap(), initial_count + 2);
 
     // Remove departments
-    delete_test_department(&pool, &dept1_id).ok();
-    assert_eq!(count_departments(&pool).unwrap(), initial_count + 1);
+    delete_test_department(&storage, &dept1_id).ok();
+    assert_eq!(count_departments(&storage).unwrap(), initial_count + 1);
 
-    delete_test_department(&pool, &dept2_id).ok();
-    assert_eq!(count_departments(&pool).unwrap(), initial_count);
+    delete_test_department(&storage, &dept2_id).ok();
+    assert_eq!(count_departments(&storage).unwrap(), initial_count);
 }
@@ -5,15 +5,15 @@ use common::*;
 
 #[test]
 fn test_get_salary_grades_with_database() {
-    let pool = setup_test_db().unwrap();
+    let storage = setup_test_storage();
 
-    let initial_count = count_salary_grades(&pool).unwrap();
+    let initial_count = count_salary_grades(&storage).unwrap();
 
     // Create test salary grades
-    let sg1_id = create_test_salary_grade(&pool, "SG1", 30000.0).unwrap();
-    let sg2_id = create_test_salary_grade(&pool, "SG2", 50000.0).unwrap();
+    let sg1_id = create_test_salary_grade(&storage, "SG1", 30000.0).unwrap();
+    let sg2_id = create_test_salary_grade(&storage, "SG2", 50000.0).unwrap();
 
-    let final_count = count_salary_grades(&pool).unwrap();
+    let final_count = count_salary_grades(&storage).unwrap();
     assert_eq!(
         final_count,
         initial_count + 2,
@@ -21,85 +21,85 @@ fn test_get_salary_grades_with_database() {
     );
 
     // Cleanup
-    delete_test_salary_grade(&pool, &sg1_id).ok();
-    delete_test_salary_grade(&pool, &sg2_id).ok();
+    delete_test_salary_grade(&storage, &sg1_id).ok();
+    delete_test_salary_grade(&storage, &sg2_id).ok();
 }
 
 #[test]
 fn test_create_salary_grade_in_database() {
-    let pool = setup_test_db().unwrap();
+    let storage = setup_test_storage();
 
-    let sg_id = create_test_salary_grade(&pool, "SG5", 80000.0).unwrap();
+    let sg_id = create_test_salary_grade(&storage, "SG5", 80000.0).unwrap();
 
     assert!(
-        salary_grade_exists(&pool, &sg_id).unwrap(),
+        salary_grade_exists(&storage, &sg_id).unwrap(),
         "Salary grade should exist in database"
     );
 
     // Cleanup
-    delete_test_salary_grade(&pool, &sg_id).ok();
+    delete_test_salary_grade(&storage, &sg_id).ok();
 }
 
 #[test]
 fn test_salary_grade_with_different_codes() {
-    let pool = setup_test_db().unwrap();
+    let storage = setup_test_storage();
 
     let codes = vec!["A1", "B2", "C3", "D4", "E5"];
     let mut sg_ids = Vec::new();
 
     for code in &codes {
-        let sg_id = create_test_salary_grade(&pool, code, 40000.0).unwrap();
+        let sg_id = create_test_salary_grade(&storage, code, 40000.0).unwrap();
         sg_ids.push(sg_id);
     }
 
     // Verify all exist
     for sg_id in &sg_ids {
         assert!(
-            salary_grade_exists(&pool, sg_id).unwrap(),
+            salary_grade_exists(&storage, sg_id).unwrap(),
             "Salary grade should exist"
         );
     }
 
     // Cleanup
     for sg_id in sg_ids {
-        delete_test_salary_grade(&pool, &sg_id).ok();
+        delete_test_salary_grade(&storage, &sg_id).ok();
     }
 }
 
 #[test]
 fn test_salary_grade_with_various_base_salaries() {
-    let pool = setup_test_db().unwrap();
+    let storage = setup_test_storage();
 
     let salaries = [25000.0, 50000.0, 75000.0, 100000.0, 150000.0];
     let mut sg_ids = Vec::new();
 
     for (i, salary) in salaries.iter().enumerate() {
-        let sg_id = create_test_salary_grade(&pool, &format!("SAL{}", i), *salary).unwrap();
+        let sg_id = create_test_salary_grade(&storage, &format!("SAL{}", i), *salary).unwrap();
         sg_ids.push(sg_id);
     }
 
     // Verify all exist
     for sg_id in &sg_ids {
-        assert!(salary_grade_exists(&pool, sg_id).unwrap());
+        assert!(salary_grade_exists(&storage, sg_id).unwrap());
     }
 
     // Cleanup
     for sg_id in sg_ids {
-        delete_test_salary_grade(&pool, &sg_id).ok();
+        delete_test_salary_grade(&storage, &sg_id).ok();
     }
 }
 
 #[test]
 fn test_multiple_salary_grades() {
-    let pool = setup_test_db().unwrap();
+    let storage = setup_test_storage();
 
-    let initial_count = count_salary_grades(&pool).unwrap();
+    let initial_count = count_salary_grades(&storage).unwrap();
 
     // Create multiple salary grades
     let sg_ids: Vec<String> = (0..5)
         .map(|i| {
             create_test_salary_grade(
-                &pool,
+                &storage,
                 &format!("GRADE{}", i),
                 30000.0 + (i as f64 * 10000.0),
             )
@@ -107,7 +107,7 @@ fn test_multiple_salary_grades() {
         })
         .collect();
 
-    let final_count = count_salary_grades(&pool).unwrap();
+    let final_count = count_salary_grades(&storage).unwrap();
     assert_eq!(
         final_count,
         initial_count + 5,
@@ -117,7 +117,7 @@ fn test_multiple_salary_grades() {
     // Verify all exist
     for sg_id in &sg_ids {
         assert!(
-            salary_grade_exists(&pool, sg_id).unwrap(),
+            salary_grade_exists(&storage, sg_id).unwrap(),
             "Salary grade {} should exist",
             sg_id
         );
@@ -125,7 +125,7 @@ fn test_multiple_salary_grades() {
 
     // Cleanup
     for sg_id in sg_ids {
-        delete_test_salary_grade(&pool, &sg_id).ok();
+        delete_test_salary_grade(&storage, &sg_id).ok();
     }
 }
 
@@ -159,19 +159,19 @@ fn test_update_salary_grade_request_structure() {
 
 #[test]
 fn test_salary_grade_crud_operations() {
-    let pool = setup_test_db().unwrap();
+    let storage = setup_test_storage();
 
-    let initial_count = count_salary_grades(&pool).unwrap();
+    let initial_count = count_salary_grades(&storage).unwrap();
 
     // CREATE
-    let sg_id = create_test_salary_grade(&pool, "MANAGER", 90000.0).unwrap();
+    let sg_id = create_test_salary_grade(&storage, "MANAGER", 90000.0).unwrap();
     assert!(
-        salary_grade_exists(&pool, &sg_id).unwrap(),
+        salary_grade_exists(&storage, &sg_id).unwrap(),
         "Salary grade should be created"
     );
 
     // READ (verify count increased)
-    let after_create_count = count_salary_grades(&pool).unwrap();
+    let after_create_count = count_salary_grades(&storage).unwrap();
     assert_eq!(
         after_create_count,
         initial_count + 1,
@@ -179,14 +179,14 @@ fn test_salary_grade_crud_operations() {
     );
 
     // DELETE
-    delete_test_salary_grade(&pool, &sg_id).ok();
+    delete_test_salary_grade(&storage, &sg_id).ok();
     assert!(
-        !salary_grade_exists(&pool, &sg_id).unwrap(),
+        !salary_grade_exists(&storage, &sg_id).unwrap(),
         "Salary grade should be deleted"
     );
 
     // Verify count returned to initial
-    let after_delete_count = count_salary_grades(&pool).unwrap();
+    let after_delete_count = count_salary_grades(&storage).unwrap();
     assert_eq!(
         after_delete_count, initial_count,
         "Count should return to initial"
@@ -195,38 +195,38 @@ fn test_salary_grade_crud_operations() {
 
 #[test]
 fn test_salary_grade_count_tracking() {
-    let pool = setup_test_db().unwrap();
+    let storage = setup_test_storage();
 
-    let initial_count = count_salary_grades(&pool).unwrap();
+    let initial_count = count_salary_grades(&storage).unwrap();
 
     // Add salary grades
-    let sg1_id = create_test_salary_grade(&pool, "JUNIOR", 35000.0).unwrap();
-    assert_eq!(count_salary_grades(&pool).unwrap(), initial_count + 1);
+    let sg1_id = create_test_salary_grade(&storage, "JUNIOR", 35000.0).unwrap();
+    assert_eq!(count_salary_grades(&storage).unwrap(), initial_count + 1);
 
-    let sg2_id = create_test_salary_grade(&pool, "SENIOR", 85000.0).unwrap();
-    assert_eq!(count_salary_grades(&pool).unwrap(), initial_count + 2);
+    let sg2_id = create_test_salary_grade(&storage, "SENIOR", 85000.0).unwrap();
+    assert_eq!(count_salary_grades(&storage).unwrap(), initial_count + 2);
 
     // Remove salary grades
-    delete_test_salary_grade(&pool, &sg1_id).ok();
-    assert_eq!(count_salary_grades(&pool).unwrap(), initial_count + 1);
+    delete_test_salary_grade(&storage, &sg1_id).ok();
+    assert_eq!(count_salary_grades(&storage).unwrap(), initial_count + 1);
 
-    delete_test_salary_grade(&pool, &sg2_id).ok();
-    assert_eq!(count_salary_grades(&pool).unwrap(), initial_count);
+    delete_test_salary_grade(&storage, &sg2_id).ok();
+    assert_eq!(count_salary_grades(&storage).unwrap(), initial_count);
 }
 
 #[test]
 fn test_salary_grade_with_employees() {
-    let pool = setup_test_db().unwrap();
+    let storage = setup_test_storage();
 
     // Create a salary grade
-    let sg_id = create_test_salary_grade(&pool, "LEAD", 70000.0).unwrap();
+    let sg_id = create_test_salary_grade(&storage, "LEAD", 70000.0).unwrap();
 
     // Create a department for employees
-    let dept_id = create_test_department(&pool, "Engineering Dept").unwrap();
+    let dept_id = create_test_department(&storage, "Engineering Dept").unwrap();
 
     // Create multiple employees with this salary grade
     let emp1_id = create_test_employee(
-        &pool,
+        &storage,
         "Charlie",
         "Brown",
         &format!("charlie_{}@test.com", uuid::Uuid::new_v4()),
@@ -236,7 +236,7 @@ fn test_salary_grade_with_employees() {
     .unwrap();
 
     let emp2_id = create_test_employee(
-        &pool,
+        &storage,
         "Diana",
         "Prince",
         &format!("diana_{}@test.com", uuid::Uuid::new_v4()),
@@ -246,13 +246,13 @@ fn test_salary_grade_with_employees() {
     .unwrap();
 
     // Verify salary grade and employees exist
-    assert!(salary_grade_exists(&pool, &sg_id).unwrap());
-    assert!(employee_exists(&pool, &emp1_id).unwrap());
-    assert!(employee_exists(&pool, &emp2_id).unwrap());
+    assert!(salary_grade_exists(&storage, &sg_id).unwrap());
+    assert!(employee_exists(&storage, &emp1_id).unwrap());
+    assert!(employee_exists(&storage, &emp2_id).unwrap());
 
     // Cleanup
-    delete_test_employee(&pool, &emp1_id).ok();
-    delete_test_employee(&pool, &emp2_id).ok();
-    delete_test_department(&pool, &dept_id).ok();
-    delete_test_salary_grade(&pool, &sg_id).ok();
+    delete_test_employee(&storage, &emp1_id).ok();
+    delete_test_employee(&storage, &emp2_id).ok();
+    delete_test_department(&storage, &dept_id).ok();
+    delete_test_salary_grade(&storage, &sg_id).ok();
 }
@@ -19,6 +19,7 @@ fn test_user_creation() {
         id: 42,
         name: "Test User".to_string(),
         email: "test@example.com".to_string(),
+        ..Default::default()
     };
 
     assert_eq!(user.id, 42);
@@ -31,6 +32,8 @@ fn test_create_user_request() {
     let request = CreateUserRequest {
         name: "New User".to_string(),
         email: "new@example.com".to_string(),
+        employee_id: None,
+        password: None,
     };
 
     assert_eq!(request.name, "New User");
@@ -0,0 +1,258 @@
+// Tests for the crate wide ApiError type, its MySQL error mapping and problem+json responses
+
+use actix_web::body::to_bytes;
+use actix_web::http::{StatusCode, header};
+use actix_web::test as actix_test;
+use actix_web::{App, HttpResponse, ResponseError, middleware, web};
+use backend::error::*;
+use backend::models::FieldError;
+
+fn mysql_error(code: u16, message: &str) -> mysql::Error {
+    mysql::Error::MySqlError(mysql::MySqlError {
+        state: "23000".to_string(),
+        message: message.to_string(),
+        code,
+    })
+}
+
+async fn body_json(err: &ApiError) -> serde_json::Value {
+    let body = to_bytes(err.error_response().into_body()).await.unwrap();
+    serde_json::from_slice(&body).unwrap()
+}
+
+#[test]
+fn test_status_codes() {
+    let cases = vec![
+        (ApiError::BadRequest("x".into()), StatusCode::BAD_REQUEST),
+        (ApiError::Unauthorized("x".into()), StatusCode::UNAUTHORIZED),
+        (ApiError::Forbidden("x".into()), StatusCode::FORBIDDEN),
+        (ApiError::NotFound("x".into()), StatusCode::NOT_FOUND),
+        (ApiError::Conflict("x".into()), StatusCode::CONFLICT),
+        (
+            ApiError::invalid_field("x", "y"),
+            StatusCode::UNPROCESSABLE_ENTITY,
+        ),
+        (
+            ApiError::Unprocessable("x".into()),
+            StatusCode::UNPROCESSABLE_ENTITY,
+        ),
+        (
+            ApiError::Internal("x".into()),
+            StatusCode::INTERNAL_SERVER_ERROR,
+        ),
+    ];
+
+    for (err, status) in cases {
+        assert_eq!(err.status_code(), status, "{:?}", err);
+    }
+}
+
+#[actix_web::test]
+async fn test_error_body_is_problem_details() {
+    let body = body_json(&ApiError::NotFound("Employee not found".into())).await;
+
+    assert_eq!(body["type"], "/problems/not-found");
+    assert_eq!(body["title"], "Not Found");
+    assert_eq!(body["status"], 404);
+    assert_eq!(body["detail"], "Employee not found");
+    assert_eq!(body["code"], "not_found");
+    assert!(body.get("errors").is_none());
+}
+
+#[test]
+fn test_error_response_content_type() {
+    let resp = ApiError::Conflict("x".into()).error_response();
+
+    assert_eq!(
+        resp.headers().get(header::CONTENT_TYPE).unwrap(),
+        PROBLEM_JSON
+    );
+}
+
+#[actix_web::test]
+async fn test_validation_error_lists_every_field() {
+    let err = ApiError::Validation(vec![
+        FieldError {
+            field: "email".into(),
+            message: "must be a valid email address".into(),
+        },
+        FieldError {
+            field: "first_name".into(),
+            message: "must not be empty".into(),
+        },
+    ]);
+
+    let body = body_json(&err).await;
+
+    assert_eq!(body["status"], 422);
+    assert_eq!(body["detail"], "2 fields failed validation");
+    assert_eq!(body["errors"].as_array().unwrap().len(), 2);
+    assert_eq!(body["errors"][0]["field"], "email");
+}
+
+#[test]
+fn test_duplicate_email_maps_to_conflict() {
+    let err: ApiError = mysql_error(
+        1062,
+        "Duplicate entry 'alice.johnson@company.com' for key 'employees.email'",
+    )
+    .into();
+
+    assert_eq!(err.status_code(), StatusCode::CONFLICT);
+    assert_eq!(err.message(), "An employee with this email already exists");
+}
+
+#[test]
+fn test_duplicate_department_name_and_grade_code_map_to_conflict() {
+    let dept: ApiError =
+        mysql_error(1062, "Duplicate entry 'Sales' for key 'departments.name'").into();
+    let grade: ApiError =
+        mysql_error(1062, "Duplicate entry 'E1' for key 'salary_grades.code'").into();
+
+    assert_eq!(dept.code(), "conflict");
+    assert_eq!(dept.message(), "A department with this name already exists");
+    assert_eq!(grade.code(), "conflict");
+    assert_eq!(
+        grade.message(),
+        "A salary grade with this code already exists"
+    );
+}
+
+#[test]
+fn test_foreign_key_violation_maps_to_unprocessable() {
+    let err: ApiError = mysql_error(
+        1452,
+        "Cannot add or update a child row: a foreign key constraint fails (`mydb`.`employees`, CONSTRAINT `fk_emp_manager` FOREIGN KEY (`manager_id`) REFERENCES `employees` (`id`) ON DELETE SET NULL)",
+    )
+    .into();
+
+    assert_eq!(err.status_code(), StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(err.code(), "unprocessable_entity");
+    assert!(err.message().contains("manager_id"));
+}
+
+#[test]
+fn test_invalid_enum_value_maps_to_validation() {
+    let err: ApiError = mysql_error(1265, "Data truncated for column 'role' at row 1").into();
+
+    assert_eq!(err.code(), "validation_failed");
+    assert_eq!(err.message(), "role: Invalid value");
+}
+
+#[actix_web::test]
+async fn test_unknown_mysql_error_does_not_leak_details() {
+    let err: ApiError = mysql_error(1146, "Table 'mydb.secret_table' doesn't exist").into();
+
+    assert_eq!(err.status_code(), StatusCode::INTERNAL_SERVER_ERROR);
+
+    let body = body_json(&err).await;
+    assert_eq!(body["code"], "database_error");
+    assert!(!body.to_string().contains("secret_table"));
+}
+
+#[test]
+fn test_pool_and_query_timeouts_map_to_service_unavailable() {
+    let acquire: ApiError = mysql::Error::DriverError(mysql::DriverError::Timeout).into();
+    let query: ApiError = mysql_error(
+        3024,
+        "Query execution was interrupted, maximum statement execution time exceeded",
+    )
+    .into();
+
+    for err in [acquire, query] {
+        assert_eq!(err.status_code(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(err.code(), "service_unavailable");
+    }
+}
+
+#[test]
+fn test_lock_conflicts_map_to_conflict() {
+    let deadlock: ApiError = mysql_error(
+        1213,
+        "Deadlock found when trying to get lock; try restarting transaction",
+    )
+    .into();
+    let wait: ApiError = mysql_error(
+        1205,
+        "Lock wait timeout exceeded; try restarting transaction",
+    )
+    .into();
+
+    for err in [deadlock, wait] {
+        assert_eq!(err.status_code(), StatusCode::CONFLICT);
+        assert!(err.message().contains("retry"));
+    }
+}
+
+#[actix_web::test]
+async fn test_middleware_sets_instance_to_request_path() {
+    let app = actix_test::init_service(
+        App::new()
+            .wrap(middleware::from_fn(problem_instance))
+            .route(
+                "/api/things/{id}",
+                web::get().to(|| async {
+                    Err::<HttpResponse, _>(ApiError::NotFound("Thing not found".into()))
+                }),
+            ),
+    )
+    .await;
+
+    let req = actix_test::TestRequest::get()
+        .uri("/api/things/42")
+        .to_request();
+    let resp = actix_test::call_service(&app, req).await;
+
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    let body: serde_json::Value = actix_test::read_body_json(resp).await;
+    assert_eq!(body["instance"], "/api/things/42");
+}
+
+#[actix_web::test]
+async fn test_malformed_json_body_is_problem_json() {
+    let app = actix_test::init_service(
+        App::new()
+            .app_data(web::JsonConfig::default().error_handler(json_error_handler))
+            .wrap(middleware::from_fn(problem_instance))
+            .route(
+                "/api/things",
+                web::post().to(|_body: web::Json<serde_json::Value>| async {
+                    HttpResponse::Ok().finish()
+                }),
+            ),
+    )
+    .await;
+
+    let req = actix_test::TestRequest::post()
+        .uri("/api/things")
+        .insert_header((header::CONTENT_TYPE, "application/json"))
+        .set_payload("{not json")
+        .to_request();
+    let resp = actix_test::call_service(&app, req).await;
+
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(
+        resp.headers().get(header::CONTENT_TYPE).unwrap(),
+        PROBLEM_JSON
+    );
+    let body: serde_json::Value = actix_test::read_body_json(resp).await;
+    assert_eq!(body["code"], "bad_request");
+    assert_eq!(body["instance"], "/api/things");
+}
+
+#[actix_web::test]
+async fn test_unknown_route_is_problem_json() {
+    let app = actix_test::init_service(
+        App::new()
+            .wrap(middleware::from_fn(problem_instance))
+            .default_service(web::to(route_not_found)),
+    )
+    .await;
+
+    let req = actix_test::TestRequest::get().uri("/api/nope").to_request();
+    let resp = actix_test::call_service(&app, req).await;
+
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    let body: serde_json::Value = actix_test::read_body_json(resp).await;
+    assert_eq!(body["instance"], "/api/nope");
+}
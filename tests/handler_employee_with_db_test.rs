@@ -1,23 +1,23 @@
 // Integration tests that actually call HTTP handlers with database
 mod common;
 
-use actix_web::{App, test, web};
+use actix_web::{App, test};
 use backend::handlers::employee::*;
 use backend::models::*;
 use common::*;
 
 #[actix_web::test]
 async fn test_get_employees_handler_with_db() {
-    let pool = setup_test_db().unwrap();
+    let storage = setup_test_storage();
 
     let app = test::init_service(
         App::new()
-            .app_data(web::Data::new(pool.clone()))
+            .configure(|cfg| storage.register(cfg))
             .service(get_employees),
     )
     .await;
 
-    let req = test::TestRequest::get().uri("/api/employees").to_request();
+    let req = as_admin(test::TestRequest::get().uri("/api/employees").to_request());
 
     let resp = test::call_service(&app, req).await;
 
@@ -30,15 +30,15 @@ async fn test_get_employees_handler_with_db() {
 
 #[actix_web::test]
 async fn test_create_employee_handler_with_db() {
-    let pool = setup_test_db().unwrap();
+    let storage = setup_test_storage();
 
     // Create dependencies first
-    let dept_id = create_test_department(&pool, "Test Dept").unwrap();
-    let grade_id = create_test_salary_grade(&pool, "E1", 50000.0).unwrap();
+    let dept_id = create_test_department(&storage, "Test Dept").unwrap();
+    let grade_id = create_test_salary_grade(&storage, "E1", 50000.0).unwrap();
 
     let app = test::init_service(
         App::new()
-            .app_data(web::Data::new(pool.clone()))
+            .configure(|cfg| storage.register(cfg))
             .service(create_employee),
     )
     .await;
@@ -51,14 +51,16 @@ async fn test_create_employee_handler_with_db() {
         department_id: Some(dept_id.clone()),
         salary_grade_id: Some(grade_id.clone()),
         manager_id: None,
-        role: Some("Employee".to_string()),
+        role: Some(Role::Employee),
         hire_date: Some("2024-01-01".to_string()),
     };
 
-    let req = test::TestRequest::post()
-        .uri("/api/employees")
-        .set_json(&employee_req)
-        .to_request();
+    let req = as_admin(
+        test::TestRequest::post()
+            .uri("/api/employees")
+            .set_json(&employee_req)
+            .to_request(),
+    );
 
     let resp = test::call_service(&app, req).await;
 
@@ -67,74 +69,84 @@ async fn test_create_employee_handler_with_db() {
         resp.status().is_success(),
         "POST /api/employees should succeed"
     );
+    // The full employee comes back, no follow-up GET needed
+    let created: Employee = test::read_body_json(resp).await;
+    assert_eq!(created.email, unique_email);
+    assert_eq!(created.department_id, Some(dept_id.clone()));
+    assert_eq!(created.salary_grade_id, Some(grade_id.clone()));
+    assert!(created.active);
 
     // Cleanup
-    if let Some(emp_id) = get_employee_by_email(&pool, &unique_email).unwrap() {
-        delete_test_employee(&pool, &emp_id).ok();
+    if let Some(emp_id) = get_employee_by_email(&storage, &unique_email).unwrap() {
+        delete_test_employee(&storage, &emp_id).ok();
     }
-    delete_test_department(&pool, &dept_id).ok();
-    delete_test_salary_grade(&pool, &grade_id).ok();
+    delete_test_department(&storage, &dept_id).ok();
+    delete_test_salary_grade(&storage, &grade_id).ok();
 }
 
-// Disabled due to timestamp conversion bugs in handlers
-/*
 #[actix_web::test]
 async fn test_get_employee_by_id_handler() {
-    let pool = setup_test_db().unwrap();
+    let storage = setup_test_storage();
 
     // Create test employee
-    let dept_id = create_test_department(&pool, "Test Dept").unwrap();
+    let dept_id = create_test_department(&storage, "Test Dept").unwrap();
     let emp_id = create_test_employee(
-        &pool,
+        &storage,
         "John",
         "Doe",
         &format!("john_{}@test.com", uuid::Uuid::new_v4()),
         Some(&dept_id),
-        None
-    ).unwrap();
+        None,
+    )
+    .unwrap();
 
     let app = test::init_service(
         App::new()
-            .app_data(web::Data::new(pool.clone()))
-            .service(get_employee_by_id)
-    ).await;
+            .configure(|cfg| storage.register(cfg))
+            .service(get_employee_by_id),
+    )
+    .await;
 
-    let req = test::TestRequest::get()
-        .uri(&format!("/api/employees/{}", emp_id))
-        .to_request();
+    let req = as_admin(
+        test::TestRequest::get()
+            .uri(&format!("/api/employees/{}", emp_id))
+            .to_request(),
+    );
 
     let resp = test::call_service(&app, req).await;
 
-    assert!(resp.status().is_success(), "GET /api/employees/:id should succeed");
+    assert!(
+        resp.status().is_success(),
+        "GET /api/employees/:id should succeed"
+    );
 
     // Cleanup
-    delete_test_employee(&pool, &emp_id).ok();
-    delete_test_department(&pool, &dept_id).ok();
+    delete_test_employee(&storage, &emp_id).ok();
+    delete_test_department(&storage, &dept_id).ok();
 }
-*/
 
-// Disabled due to timestamp conversion bugs in handlers
-/*
 #[actix_web::test]
 async fn test_update_employee_handler() {
-    let pool = setup_test_db().unwrap();
+    let storage = setup_test_storage();
 
     // Create test employee
-    let dept_id = create_test_department(&pool, "Test Dept").unwrap();
+    let dept_id = create_test_department(&storage, "Test Dept").unwrap();
     let emp_id = create_test_employee(
-        &pool,
+        &storage,
         "Jane",
         "Smith",
         &format!("jane_{}@test.com", uuid::Uuid::new_v4()),
         Some(&dept_id),
-        None
-    ).unwrap();
+        None,
+    )
+    .unwrap();
 
     let app = test::init_service(
         App::new()
-            .app_data(web::Data::new(pool.clone()))
-            .service(update_employee)
-    ).await;
+            .configure(|cfg| storage.register(cfg))
+            .service(update_employee),
+    )
+    .await;
 
     let update_req = UpdateEmployeeRequest {
         first_name: Some("Janet".to_string()),
@@ -148,56 +160,734 @@ async fn test_update_employee_handler() {
         active: None,
     };
 
-    let req = test::TestRequest::put()
-        .uri(&format!("/api/employees/{}", emp_id))
-        .set_json(&update_req)
-        .to_request();
+    let req = as_admin(
+        test::TestRequest::put()
+            .uri(&format!("/api/employees/{}", emp_id))
+            .set_json(&update_req)
+            .to_request(),
+    );
 
     let resp = test::call_service(&app, req).await;
 
-    assert!(resp.status().is_success(), "PUT /api/employees/:id should succeed");
+    assert!(
+        resp.status().is_success(),
+        "PUT /api/employees/:id should succeed"
+    );
+    let updated: Employee = test::read_body_json(resp).await;
+    assert_eq!(updated.id, emp_id);
+    assert_eq!(updated.first_name, "Janet");
+
+    let req = as_admin(
+        test::TestRequest::put()
+            .uri("/api/employees/00000000-0000-0000-0000-000000000000")
+            .set_json(&update_req)
+            .to_request(),
+    );
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 404, "Unknown employees are not found");
 
     // Cleanup
-    delete_test_employee(&pool, &emp_id).ok();
-    delete_test_department(&pool, &dept_id).ok();
+    delete_test_employee(&storage, &emp_id).ok();
+    delete_test_department(&storage, &dept_id).ok();
 }
-*/
 
-// Disabled - delete is soft delete, not hard delete
-/*
 #[actix_web::test]
 async fn test_delete_employee_handler() {
-    let pool = setup_test_db().unwrap();
+    let storage = setup_test_storage();
 
     // Create test employee
-    let dept_id = create_test_department(&pool, "Test Dept").unwrap();
+    let dept_id = create_test_department(&storage, "Test Dept").unwrap();
     let emp_id = create_test_employee(
-        &pool,
+        &storage,
         "Delete",
         "Me",
         &format!("delete_{}@test.com", uuid::Uuid::new_v4()),
         Some(&dept_id),
-        None
-    ).unwrap();
+        None,
+    )
+    .unwrap();
 
     let app = test::init_service(
         App::new()
-            .app_data(web::Data::new(pool.clone()))
-            .service(delete_employee)
-    ).await;
+            .configure(|cfg| storage.register(cfg))
+            .service(delete_employee),
+    )
+    .await;
 
-    let req = test::TestRequest::delete()
-        .uri(&format!("/api/employees/{}", emp_id))
-        .to_request();
+    let req = as_admin(
+        test::TestRequest::delete()
+            .uri(&format!("/api/employees/{}", emp_id))
+            .to_request(),
+    );
+
+    let resp = test::call_service(&app, req).await;
+
+    assert!(
+        resp.status().is_success(),
+        "DELETE /api/employees/:id should succeed"
+    );
+    let deleted: Employee = test::read_body_json(resp).await;
+    assert!(!deleted.active);
 
+    let req = as_admin(
+        test::TestRequest::delete()
+            .uri("/api/employees/00000000-0000-0000-0000-000000000000")
+            .to_request(),
+    );
     let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 404, "Unknown employees are not found");
+
+    // Verify soft deleted, the row stays but is inactive
+    let employee = storage.employees.find_by_id(&emp_id).unwrap().unwrap();
+    assert!(!employee.active, "Employee should be inactive");
+    assert!(employee.deleted_at.is_some(), "deleted_at should be set");
+
+    // Cleanup
+    delete_test_employee(&storage, &emp_id).ok();
+    delete_test_department(&storage, &dept_id).ok();
+}
+
+#[actix_web::test]
+async fn test_get_employees_pages_sorts_and_filters() {
+    let storage = setup_test_storage();
+
+    let dept_id =
+        create_test_department(&storage, &format!("Paging Dept {}", uuid::Uuid::new_v4())).unwrap();
+    let mut ids = Vec::new();
+    for (last_name, hire_date) in [
+        ("Able", "2020-01-15"),
+        ("Baker", "2022-06-01"),
+        ("Cole", "2021-03-10"),
+    ] {
+        let id = create_test_employee(
+            &storage,
+            "Paged",
+            last_name,
+            &format!("paged_{}@test.com", uuid::Uuid::new_v4()),
+            Some(&dept_id),
+            None,
+        )
+        .unwrap();
+        let changes = UpdateEmployeeRequest {
+            first_name: None,
+            last_name: None,
+            email: None,
+            department_id: None,
+            salary_grade_id: None,
+            manager_id: None,
+            role: None,
+            hire_date: Some(hire_date.to_string()),
+            active: None,
+        };
+        storage.employees.update(&id, &changes, &admin()).unwrap();
+        ids.push(id);
+    }
+
+    let app = test::init_service(
+        App::new()
+            .configure(|cfg| storage.register(cfg))
+            .service(get_employees),
+    )
+    .await;
+
+    let uri = format!(
+        "/api/employees?department_id={}&sort=-hire_date&per_page=2",
+        dept_id
+    );
+    let resp = test::call_service(
+        &app,
+        as_admin(test::TestRequest::get().uri(&uri).to_request()),
+    )
+    .await;
+    assert_eq!(resp.status(), 200);
+    assert_eq!(resp.headers().get("X-Total-Count").unwrap(), "3");
+    let link = resp
+        .headers()
+        .get("Link")
+        .unwrap()
+        .to_str()
+        .unwrap()
+        .to_string();
+    assert!(link.contains("page=2>; rel=\"next\""), "{}", link);
+    let body: Vec<Employee> = test::read_body_json(resp).await;
+    let names: Vec<&str> = body.iter().map(|e| e.last_name.as_str()).collect();
+    assert_eq!(names, ["Baker", "Cole"]);
+
+    let uri = format!(
+        "/api/employees?department_id={}&hired_from=2021-01-01&hired_to=2021-12-31",
+        dept_id
+    );
+    let resp = test::call_service(
+        &app,
+        as_admin(test::TestRequest::get().uri(&uri).to_request()),
+    )
+    .await;
+    let body: Vec<Employee> = test::read_body_json(resp).await;
+    assert_eq!(body.len(), 1);
+    assert_eq!(body[0].last_name, "Cole");
+
+    let resp = test::call_service(
+        &app,
+        as_admin(
+            test::TestRequest::get()
+                .uri("/api/employees?sort=salary")
+                .to_request(),
+        ),
+    )
+    .await;
+    assert_eq!(resp.status(), 422);
+
+    // Cleanup
+    for id in ids {
+        delete_test_employee(&storage, &id).ok();
+    }
+    delete_test_department(&storage, &dept_id).ok();
+}
+
+#[actix_web::test]
+async fn test_search_employees_handler() {
+    let storage = setup_test_storage();
+
+    let marker = format!("Srch{}", &uuid::Uuid::new_v4().simple().to_string()[..8]);
+    let emp_id = create_test_employee(
+        &storage,
+        "Zébulon",
+        &marker,
+        &format!("{}@test.com", marker.to_lowercase()),
+        None,
+        None,
+    )
+    .unwrap();
+
+    let app = test::init_service(
+        App::new()
+            .configure(|cfg| storage.register(cfg))
+            .service(search_employees)
+            .service(get_employee_by_id),
+    )
+    .await;
+
+    let uri = format!("/api/employees/search?q=zebu%20{}", marker.to_uppercase());
+    let resp = test::call_service(
+        &app,
+        as_admin(test::TestRequest::get().uri(&uri).to_request()),
+    )
+    .await;
+    assert_eq!(resp.status(), 200);
+    assert_eq!(resp.headers().get("X-Total-Count").unwrap(), "1");
+    let body: Vec<Employee> = test::read_body_json(resp).await;
+    assert_eq!(body[0].id, emp_id);
+
+    let resp = test::call_service(
+        &app,
+        as_admin(
+            test::TestRequest::get()
+                .uri("/api/employees/search")
+                .to_request(),
+        ),
+    )
+    .await;
+    assert_eq!(resp.status(), 400);
+
+    let resp = test::call_service(
+        &app,
+        as_admin(
+            test::TestRequest::get()
+                .uri("/api/employees/search?q=%20")
+                .to_request(),
+        ),
+    )
+    .await;
+    assert_eq!(resp.status(), 422);
+
+    // Cleanup
+    delete_test_employee(&storage, &emp_id).ok();
+}
+
+#[actix_web::test]
+async fn test_org_chart_handlers() {
+    let storage = setup_test_storage();
+
+    let suffix = uuid::Uuid::new_v4();
+    let boss = create_test_employee(
+        &storage,
+        "Org",
+        "Boss",
+        &format!("boss_{}@test.com", suffix),
+        None,
+        None,
+    )
+    .unwrap();
+    let report = create_test_employee(
+        &storage,
+        "Org",
+        "Report",
+        &format!("report_{}@test.com", suffix),
+        None,
+        None,
+    )
+    .unwrap();
+    storage
+        .employees
+        .assign_manager(&report, &boss, &admin())
+        .unwrap();
+
+    let app = test::init_service(
+        App::new()
+            .configure(|cfg| storage.register(cfg))
+            .service(get_employee_org_chart)
+            .service(get_org_chart),
+    )
+    .await;
+
+    let uri = format!("/api/employees/{}/org-chart", boss);
+    let resp = test::call_service(
+        &app,
+        as_admin(test::TestRequest::get().uri(&uri).to_request()),
+    )
+    .await;
+    assert_eq!(resp.status(), 200);
+    let chart: OrgChartNode = test::read_body_json(resp).await;
+    assert_eq!(chart.direct_reports, 1);
+    assert_eq!(chart.reports[0].id, report);
+
+    let uri = format!("/api/employees/{}/org-chart?depth=0", boss);
+    let resp = test::call_service(
+        &app,
+        as_admin(test::TestRequest::get().uri(&uri).to_request()),
+    )
+    .await;
+    let chart: OrgChartNode = test::read_body_json(resp).await;
+    assert!(chart.reports.is_empty());
+    assert_eq!(chart.total_reports, 1);
+
+    let resp = test::call_service(
+        &app,
+        as_admin(test::TestRequest::get().uri("/api/org-chart").to_request()),
+    )
+    .await;
+    let roots: Vec<OrgChartNode> = test::read_body_json(resp).await;
+    assert!(roots.iter().any(|root| root.id == boss));
+    assert!(!roots.iter().any(|root| root.id == report));
+
+    let resp = test::call_service(
+        &app,
+        as_admin(
+            test::TestRequest::get()
+                .uri("/api/employees/unknown/org-chart")
+                .to_request(),
+        ),
+    )
+    .await;
+    assert_eq!(resp.status(), 404);
+
+    // Cleanup
+    delete_test_employee(&storage, &report).ok();
+    delete_test_employee(&storage, &boss).ok();
+}
+
+#[actix_web::test]
+async fn test_reporting_chain_handler() {
+    let storage = setup_test_storage();
+
+    let suffix = uuid::Uuid::new_v4();
+    let top = create_test_employee(
+        &storage,
+        "Chain",
+        "Top",
+        &format!("top_{}@test.com", suffix),
+        None,
+        None,
+    )
+    .unwrap();
+    let middle = create_test_employee(
+        &storage,
+        "Chain",
+        "Middle",
+        &format!("middle_{}@test.com", suffix),
+        None,
+        None,
+    )
+    .unwrap();
+    let bottom = create_test_employee(
+        &storage,
+        "Chain",
+        "Bottom",
+        &format!("bottom_{}@test.com", suffix),
+        None,
+        None,
+    )
+    .unwrap();
+    storage
+        .employees
+        .assign_manager(&middle, &top, &admin())
+        .unwrap();
+    storage
+        .employees
+        .assign_manager(&bottom, &middle, &admin())
+        .unwrap();
+
+    let app = test::init_service(
+        App::new()
+            .configure(|cfg| storage.register(cfg))
+            .service(get_reporting_chain),
+    )
+    .await;
 
-    assert!(resp.status().is_success(), "DELETE /api/employees/:id should succeed");
+    let uri = format!("/api/employees/{}/chain", bottom);
+    let resp = test::call_service(
+        &app,
+        as_admin(test::TestRequest::get().uri(&uri).to_request()),
+    )
+    .await;
+    assert_eq!(resp.status(), 200);
+    let chain: ReportingChain = test::read_body_json(resp).await;
+    let ids: Vec<&str> = chain.managers.iter().map(|m| m.id.as_str()).collect();
+    assert_eq!(ids, [middle.as_str(), top.as_str()]);
+    assert!(chain.complete);
+
+    storage.employees.soft_delete(&top, &admin()).unwrap();
+    let resp = test::call_service(
+        &app,
+        as_user(
+            test::TestRequest::get().uri(&uri).to_request(),
+            &bottom,
+            Role::Employee,
+            None,
+        ),
+    )
+    .await;
+    let chain: ReportingChain = test::read_body_json(resp).await;
+    assert_eq!(chain.managers.len(), 1);
+    assert_eq!(chain.broken, Some(ChainBreak::InactiveManager));
+    assert_eq!(chain.broken_at.as_deref(), Some(top.as_str()));
+
+    // Plain employees only see their own chain
+    let uri = format!("/api/employees/{}/chain", middle);
+    let resp = test::call_service(
+        &app,
+        as_user(
+            test::TestRequest::get().uri(&uri).to_request(),
+            &bottom,
+            Role::Employee,
+            None,
+        ),
+    )
+    .await;
+    assert_eq!(resp.status(), 403);
+
+    // Cleanup
+    for id in [bottom, middle, top] {
+        delete_test_employee(&storage, &id).ok();
+    }
+}
+
+#[actix_web::test]
+async fn test_assign_manager_rejects_cycles() {
+    let storage = setup_test_storage();
+
+    let suffix = uuid::Uuid::new_v4();
+    let boss = create_test_employee(
+        &storage,
+        "Cycle",
+        "Boss",
+        &format!("boss_{}@test.com", suffix),
+        None,
+        None,
+    )
+    .unwrap();
+    let report = create_test_employee(
+        &storage,
+        "Cycle",
+        "Report",
+        &format!("report_{}@test.com", suffix),
+        None,
+        None,
+    )
+    .unwrap();
+    storage
+        .employees
+        .assign_manager(&report, &boss, &admin())
+        .unwrap();
+
+    let app = test::init_service(
+        App::new()
+            .configure(|cfg| storage.register(cfg))
+            .service(assign_manager),
+    )
+    .await;
+
+    let assign = |id: &str, manager_id: &str| {
+        as_admin(
+            test::TestRequest::put()
+                .uri(&format!("/api/employees/{}/manager", id))
+                .set_json(serde_json::json!({ "manager_id": manager_id }))
+                .to_request(),
+        )
+    };
+
+    let resp = test::call_service(&app, assign(&boss, &report)).await;
+    assert_eq!(resp.status(), 422);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert!(body["detail"].as_str().unwrap().contains("cycle"));
+
+    let resp = test::call_service(&app, assign(&boss, &boss)).await;
+    assert_eq!(resp.status(), 422);
+
+    let boss_row = storage.employees.find_by_id(&boss).unwrap().unwrap();
+    assert_eq!(boss_row.manager_id, None);
+
+    // Cleanup
+    delete_test_employee(&storage, &report).ok();
+    delete_test_employee(&storage, &boss).ok();
+}
 
-    // Verify deleted
-    assert!(!employee_exists(&pool, &emp_id).unwrap(), "Employee should be deleted");
+#[actix_web::test]
+async fn test_employees_by_department_recursive() {
+    let storage = setup_test_storage();
+
+    let division = create_test_department(&storage, "Test Division").unwrap();
+    let team = storage
+        .departments
+        .create(&CreateDepartmentRequest {
+            name: format!("Test Team_{}", uuid::Uuid::new_v4()),
+            head_id: None,
+            parent_id: Some(division.clone()),
+            promote_head: None,
+        })
+        .unwrap();
+    let suffix = uuid::Uuid::new_v4();
+    let lead = create_test_employee(
+        &storage,
+        "Division",
+        "Lead",
+        &format!("lead_{}@test.com", suffix),
+        Some(&division),
+        None,
+    )
+    .unwrap();
+    let dev = create_test_employee(
+        &storage,
+        "Team",
+        "Dev",
+        &format!("dev_{}@test.com", suffix),
+        Some(&team),
+        None,
+    )
+    .unwrap();
+
+    let app = test::init_service(
+        App::new()
+            .configure(|cfg| storage.register(cfg))
+            .service(get_employees_by_department),
+    )
+    .await;
+
+    for (query, expected) in [("", 1), ("?recursive=true", 2)] {
+        let req = as_admin(
+            test::TestRequest::get()
+                .uri(&format!("/api/departments/{}/employees{}", division, query))
+                .to_request(),
+        );
+        let employees: Vec<Employee> = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(employees.len(), expected, "query '{}'", query);
+    }
 
     // Cleanup
-    delete_test_department(&pool, &dept_id).ok();
+    delete_test_employee(&storage, &dev).ok();
+    delete_test_employee(&storage, &lead).ok();
+    delete_test_department(&storage, &team).ok();
+    delete_test_department(&storage, &division).ok();
+}
+
+#[actix_web::test]
+async fn test_restore_employee_handler() {
+    let storage = setup_test_storage();
+
+    let dept_id = create_test_department(&storage, "Test Dept").unwrap();
+    let emp_id = create_test_employee(
+        &storage,
+        "Restore",
+        "Me",
+        &format!("restore_{}@test.com", uuid::Uuid::new_v4()),
+        Some(&dept_id),
+        None,
+    )
+    .unwrap();
+    storage.employees.soft_delete(&emp_id, &admin()).unwrap();
+
+    let app = test::init_service(
+        App::new()
+            .configure(|cfg| storage.register(cfg))
+            .service(restore_employee),
+    )
+    .await;
+
+    let restore = |id: &str| {
+        as_admin(
+            test::TestRequest::post()
+                .uri(&format!("/api/employees/{}/restore", id))
+                .to_request(),
+        )
+    };
+
+    let restored: EmployeeRestore = test::call_and_read_body_json(&app, restore(&emp_id)).await;
+    assert!(restored.employee.active);
+    assert_eq!(restored.employee.deleted_at, None);
+    assert_eq!(
+        restored.employee.department_id.as_deref(),
+        Some(dept_id.as_str())
+    );
+    assert!(restored.nulled_references.is_empty());
+
+    let resp = test::call_service(&app, restore("00000000-0000-0000-0000-000000000000")).await;
+    assert_eq!(resp.status(), 404);
+
+    delete_test_employee(&storage, &emp_id).ok();
+    delete_test_department(&storage, &dept_id).ok();
+}
+
+#[actix_web::test]
+async fn test_personal_data_and_anonymize_handlers() {
+    let storage = setup_test_storage();
+
+    let dept_id = create_test_department(&storage, "Test Dept").unwrap();
+    let email = format!("gdpr_{}@test.com", uuid::Uuid::new_v4());
+    let emp_id =
+        create_test_employee(&storage, "Erase", "Me", &email, Some(&dept_id), None).unwrap();
+
+    let app = test::init_service(
+        App::new()
+            .configure(|cfg| storage.register(cfg))
+            .service(get_personal_data)
+            .service(anonymize_employee),
+    )
+    .await;
+
+    let export_uri = format!("/api/employees/{}/personal-data", emp_id);
+
+    // The employee may export their own data, but nobody else's
+    let req = as_user(
+        test::TestRequest::get().uri(&export_uri).to_request(),
+        &emp_id,
+        Role::Employee,
+        Some(&dept_id),
+    );
+    let data: PersonalData = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(data.employee.email, email);
+    assert!(data.department_name.is_some());
+
+    let req = as_user(
+        test::TestRequest::get().uri(&export_uri).to_request(),
+        ADMIN_ID,
+        Role::DepartmentHead,
+        Some(&dept_id),
+    );
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 403);
+
+    let req = as_admin(
+        test::TestRequest::post()
+            .uri(&format!("/api/employees/{}/anonymize", emp_id))
+            .to_request(),
+    );
+    let anonymized: Employee = test::call_and_read_body_json(&app, req).await;
+    assert_ne!(anonymized.email, email);
+    assert!(!anonymized.active);
+    assert!(anonymized.anonymized_at.is_some());
+
+    delete_test_employee(&storage, &emp_id).ok();
+    delete_test_department(&storage, &dept_id).ok();
+}
+
+#[actix_web::test]
+async fn test_deputy_cannot_change_head_or_pick_outside_manager() {
+    let storage = setup_test_storage();
+
+    let dept_id = create_test_department(&storage, "Test Dept").unwrap();
+    let other_dept_id = create_test_department(&storage, "Other Dept").unwrap();
+    let head_id = create_test_employee(
+        &storage,
+        "Head",
+        "Of",
+        &format!("head_{}@test.com", uuid::Uuid::new_v4()),
+        Some(&dept_id),
+        None,
+    )
+    .unwrap();
+    let mut promote = UpdateEmployeeRequest {
+        first_name: None,
+        last_name: None,
+        email: None,
+        department_id: None,
+        salary_grade_id: None,
+        manager_id: None,
+        role: Some(Role::DepartmentHead),
+        hire_date: None,
+        active: None,
+    };
+    storage
+        .employees
+        .update(&head_id, &promote, &admin())
+        .unwrap();
+    let emp_id = create_test_employee(
+        &storage,
+        "Plain",
+        "Employee",
+        &format!("plain_{}@test.com", uuid::Uuid::new_v4()),
+        Some(&dept_id),
+        None,
+    )
+    .unwrap();
+    let outsider_id = create_test_employee(
+        &storage,
+        "Out",
+        "Sider",
+        &format!("outsider_{}@test.com", uuid::Uuid::new_v4()),
+        Some(&other_dept_id),
+        None,
+    )
+    .unwrap();
+
+    let app = test::init_service(
+        App::new()
+            .configure(|cfg| storage.register(cfg))
+            .service(update_employee)
+            .service(assign_manager),
+    )
+    .await;
+
+    // Demoting the head of their own department is not up to a deputy
+    promote.role = Some(Role::Employee);
+    let req = as_user(
+        test::TestRequest::put()
+            .uri(&format!("/api/employees/{}", head_id))
+            .set_json(&promote)
+            .to_request(),
+        &emp_id,
+        Role::DeputyHead,
+        Some(&dept_id),
+    );
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 403);
+
+    let req = as_user(
+        test::TestRequest::put()
+            .uri(&format!("/api/employees/{}/manager", emp_id))
+            .set_json(AssignManagerRequest {
+                manager_id: outsider_id.clone(),
+            })
+            .to_request(),
+        &head_id,
+        Role::DepartmentHead,
+        Some(&dept_id),
+    );
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 403);
+
+    for id in [&emp_id, &outsider_id, &head_id] {
+        delete_test_employee(&storage, id).ok();
+    }
+    delete_test_department(&storage, &dept_id).ok();
+    delete_test_department(&storage, &other_dept_id).ok();
 }
-*/
@@ -13,7 +13,7 @@ mod tests {
             department_id: Some("dept-1".to_string()),
             salary_grade_id: Some("grade-1".to_string()),
             manager_id: None,
-            role: Some("Employee".to_string()),
+            role: Some(Role::Employee),
             hire_date: Some("2023-01-01".to_string()),
         };
 
@@ -45,6 +45,8 @@ mod tests {
         let dept_request = CreateDepartmentRequest {
             name: "Engineering".to_string(),
             head_id: Some("emp-123".to_string()),
+            parent_id: None,
+            promote_head: None,
         };
 
         assert_eq!(dept_request.name, "Engineering");
@@ -91,10 +93,11 @@ mod tests {
             department_id: None,
             salary_grade_id: None,
             manager_id: None,
-            role: "Employee".to_string(),
+            role: Role::Employee,
             hire_date: None,
             active: true,
             deleted_at: None,
+            anonymized_at: None,
             created_at: None,
             updated_at: None,
         };
@@ -116,10 +119,11 @@ mod tests {
                 department_id: None,
                 salary_grade_id: None,
                 manager_id: None,
-                role: role.to_string(),
+                role: role.parse().unwrap(),
                 hire_date: None,
                 active: true,
                 deleted_at: None,
+                anonymized_at: None,
                 created_at: None,
                 updated_at: None,
             };
@@ -136,6 +140,8 @@ mod tests {
             id: "dept-1".to_string(),
             name: "Engineering".to_string(),
             head_id: Some("head-1".to_string()),
+            parent_id: None,
+            deleted_at: None,
             created_at: None,
             updated_at: None,
         };
@@ -144,6 +150,8 @@ mod tests {
             id: "dept-2".to_string(),
             name: "HR".to_string(),
             head_id: None,
+            parent_id: None,
+            deleted_at: None,
             created_at: None,
             updated_at: None,
         };
@@ -159,6 +167,7 @@ mod tests {
             code: "E1".to_string(),
             base_salary: 45000.0,
             description: Some("Entry level position".to_string()),
+            deleted_at: None,
             created_at: None,
         };
 
@@ -196,10 +205,11 @@ mod tests {
             department_id: Some("dept-1".to_string()),
             salary_grade_id: Some("grade-1".to_string()),
             manager_id: Some("mgr-1".to_string()),
-            role: "Employee".to_string(),
+            role: Role::Employee,
             hire_date: Some("2023-01-01".to_string()),
             active: true,
             deleted_at: None,
+            anonymized_at: None,
             created_at: Some("2023-01-01T00:00:00".to_string()),
             updated_at: None,
         };
@@ -1,25 +1,27 @@
 // Integration tests that actually call HTTP handlers with database
 mod common;
 
-use actix_web::{App, test, web};
+use actix_web::{App, test};
 use backend::handlers::salary_grade::*;
 use backend::models::*;
 use common::*;
 
 #[actix_web::test]
 async fn test_get_salary_grades_handler_with_db() {
-    let pool = setup_test_db().unwrap();
+    let storage = setup_test_storage();
 
     let app = test::init_service(
         App::new()
-            .app_data(web::Data::new(pool.clone()))
+            .configure(|cfg| storage.register(cfg))
             .service(get_salary_grades),
     )
     .await;
 
-    let req = test::TestRequest::get()
-        .uri("/api/salary-grades")
-        .to_request();
+    let req = as_admin(
+        test::TestRequest::get()
+            .uri("/api/salary-grades")
+            .to_request(),
+    );
 
     let resp = test::call_service(&app, req).await;
 
@@ -31,11 +33,11 @@ async fn test_get_salary_grades_handler_with_db() {
 
 #[actix_web::test]
 async fn test_create_salary_grade_handler_with_db() {
-    let pool = setup_test_db().unwrap();
+    let storage = setup_test_storage();
 
     let app = test::init_service(
         App::new()
-            .app_data(web::Data::new(pool.clone()))
+            .configure(|cfg| storage.register(cfg))
             .service(create_salary_grade),
     )
     .await;
@@ -49,10 +51,12 @@ async fn test_create_salary_grade_handler_with_db() {
         description: Some("Test Grade".to_string()),
     };
 
-    let req = test::TestRequest::post()
-        .uri("/api/salary-grades")
-        .set_json(&sg_req)
-        .to_request();
+    let req = as_admin(
+        test::TestRequest::post()
+            .uri("/api/salary-grades")
+            .set_json(&sg_req)
+            .to_request(),
+    );
 
     let resp = test::call_service(&app, req).await;
 
@@ -62,41 +66,44 @@ async fn test_create_salary_grade_handler_with_db() {
     );
 }
 
-// Disabled due to timestamp conversion bug in handler
-/*
 #[actix_web::test]
 async fn test_get_salary_grade_by_id_handler() {
-    let pool = setup_test_db().unwrap();
+    let storage = setup_test_storage();
 
-    let sg_id = create_test_salary_grade(&pool, "TEST", 55000.0).unwrap();
+    let sg_id = create_test_salary_grade(&storage, "TEST", 55000.0).unwrap();
 
     let app = test::init_service(
         App::new()
-            .app_data(web::Data::new(pool.clone()))
-            .service(get_salary_grade_by_id)
-    ).await;
+            .configure(|cfg| storage.register(cfg))
+            .service(get_salary_grade_by_id),
+    )
+    .await;
 
-    let req = test::TestRequest::get()
-        .uri(&format!("/api/salary-grades/{}", sg_id))
-        .to_request();
+    let req = as_admin(
+        test::TestRequest::get()
+            .uri(&format!("/api/salary-grades/{}", sg_id))
+            .to_request(),
+    );
 
     let resp = test::call_service(&app, req).await;
 
-    assert!(resp.status().is_success(), "GET /api/salary-grades/:id should succeed");
+    assert!(
+        resp.status().is_success(),
+        "GET /api/salary-grades/:id should succeed"
+    );
 
-    delete_test_salary_grade(&pool, &sg_id).ok();
+    delete_test_salary_grade(&storage, &sg_id).ok();
 }
-*/
 
 #[actix_web::test]
 async fn test_update_salary_grade_handler() {
-    let pool = setup_test_db().unwrap();
+    let storage = setup_test_storage();
 
-    let sg_id = create_test_salary_grade(&pool, "OLD", 50000.0).unwrap();
+    let sg_id = create_test_salary_grade(&storage, "OLD", 50000.0).unwrap();
 
     let app = test::init_service(
         App::new()
-            .app_data(web::Data::new(pool.clone()))
+            .configure(|cfg| storage.register(cfg))
             .service(update_salary_grade),
     )
     .await;
@@ -107,10 +114,12 @@ async fn test_update_salary_grade_handler() {
         description: Some("Updated".to_string()),
     };
 
-    let req = test::TestRequest::put()
-        .uri(&format!("/api/salary-grades/{}", sg_id))
-        .set_json(&update_req)
-        .to_request();
+    let req = as_admin(
+        test::TestRequest::put()
+            .uri(&format!("/api/salary-grades/{}", sg_id))
+            .set_json(&update_req)
+            .to_request(),
+    );
 
     let resp = test::call_service(&app, req).await;
 
@@ -118,26 +127,40 @@ async fn test_update_salary_grade_handler() {
         resp.status().is_success(),
         "PUT /api/salary-grades/:id should succeed"
     );
+    let updated: SalaryGrade = test::read_body_json(resp).await;
+    assert_eq!(updated.base_salary, 75000.0);
+    assert_eq!(updated.description.as_deref(), Some("Updated"));
+
+    let req = as_admin(
+        test::TestRequest::put()
+            .uri("/api/salary-grades/00000000-0000-0000-0000-000000000000")
+            .set_json(&update_req)
+            .to_request(),
+    );
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 404, "Unknown salary grades are not found");
 
-    delete_test_salary_grade(&pool, &sg_id).ok();
+    delete_test_salary_grade(&storage, &sg_id).ok();
 }
 
 #[actix_web::test]
 async fn test_delete_salary_grade_handler() {
-    let pool = setup_test_db().unwrap();
+    let storage = setup_test_storage();
 
-    let sg_id = create_test_salary_grade(&pool, "DEL", 45000.0).unwrap();
+    let sg_id = create_test_salary_grade(&storage, "DEL", 45000.0).unwrap();
 
     let app = test::init_service(
         App::new()
-            .app_data(web::Data::new(pool.clone()))
+            .configure(|cfg| storage.register(cfg))
             .service(delete_salary_grade),
     )
     .await;
 
-    let req = test::TestRequest::delete()
-        .uri(&format!("/api/salary-grades/{}", sg_id))
-        .to_request();
+    let req = as_admin(
+        test::TestRequest::delete()
+            .uri(&format!("/api/salary-grades/{}", sg_id))
+            .to_request(),
+    );
 
     let resp = test::call_service(&app, req).await;
 
@@ -145,9 +168,55 @@ async fn test_delete_salary_grade_handler() {
         resp.status().is_success(),
         "DELETE /api/salary-grades/:id should succeed"
     );
+    let deleted: SalaryGrade = test::read_body_json(resp).await;
+    assert_eq!(deleted.id, sg_id);
 
     assert!(
-        !salary_grade_exists(&pool, &sg_id).unwrap(),
+        !salary_grade_exists(&storage, &sg_id).unwrap(),
         "Salary grade should be deleted"
     );
+
+    let req = as_admin(
+        test::TestRequest::delete()
+            .uri(&format!("/api/salary-grades/{}", sg_id))
+            .to_request(),
+    );
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 404, "Deleting twice finds nothing");
+}
+
+#[actix_web::test]
+async fn test_restore_salary_grade_handler() {
+    let storage = setup_test_storage();
+
+    let sg_id = create_test_salary_grade(&storage, "TEST_RESTORE", 52000.0).unwrap();
+    delete_test_salary_grade(&storage, &sg_id).unwrap();
+
+    let app = test::init_service(
+        App::new()
+            .configure(|cfg| storage.register(cfg))
+            .service(get_salary_grades)
+            .service(restore_salary_grade),
+    )
+    .await;
+
+    let list = |uri: &str| test::TestRequest::get().uri(uri).to_request();
+    let grades: Vec<SalaryGrade> =
+        test::call_and_read_body_json(&app, list("/api/salary-grades")).await;
+    assert!(grades.iter().all(|g| g.id != sg_id));
+    let grades: Vec<SalaryGrade> =
+        test::call_and_read_body_json(&app, list("/api/salary-grades?include_deleted=true")).await;
+    assert!(grades.iter().any(|g| g.id == sg_id));
+
+    let req = as_admin(
+        test::TestRequest::post()
+            .uri(&format!("/api/salary-grades/{}/restore", sg_id))
+            .to_request(),
+    );
+    let restored: SalaryGrade = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(restored.id, sg_id);
+    assert_eq!(restored.deleted_at, None);
+    assert!(salary_grade_exists(&storage, &sg_id).unwrap());
+
+    delete_test_salary_grade(&storage, &sg_id).ok();
 }
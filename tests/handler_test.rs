@@ -20,6 +20,8 @@ mod tests {
         let user_request = CreateUserRequest {
             name: "Test User".to_string(),
             email: "test@example.com".to_string(),
+            employee_id: None,
+            password: None,
         };
 
         assert!(!user_request.name.is_empty());
@@ -33,6 +35,7 @@ mod tests {
             id: 1,
             name: "John Doe".to_string(),
             email: "john@example.com".to_string(),
+            ..Default::default()
         };
 
         assert_eq!(user.id, 1);
@@ -46,6 +49,7 @@ mod tests {
             id: 42,
             name: "Jane Doe".to_string(),
             email: "jane@example.com".to_string(),
+            ..Default::default()
         };
 
         let json = serde_json::to_string(&user).unwrap();
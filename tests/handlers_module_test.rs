@@ -11,7 +11,7 @@ fn test_employee_request_models() {
         department_id: Some("dept-1".to_string()),
         salary_grade_id: Some("grade-1".to_string()),
         manager_id: None,
-        role: Some("Employee".to_string()),
+        role: Some(Role::Employee),
         hire_date: Some("2024-01-01".to_string()),
     };
 
@@ -62,6 +62,8 @@ fn test_department_request_models() {
     let create_req = CreateDepartmentRequest {
         name: "Engineering".to_string(),
         head_id: Some("head-1".to_string()),
+        parent_id: None,
+        promote_head: None,
     };
 
     assert_eq!(create_req.name, "Engineering");
@@ -73,6 +75,8 @@ fn test_update_department_request() {
     let update_req = UpdateDepartmentRequest {
         name: Some("Sales".to_string()),
         head_id: None,
+        parent_id: None,
+        promote_head: None,
     };
 
     assert_eq!(update_req.name.unwrap(), "Sales");
@@ -113,16 +117,17 @@ fn test_employee_model_fields() {
         department_id: Some("dept-1".to_string()),
         salary_grade_id: Some("grade-1".to_string()),
         manager_id: Some("mgr-1".to_string()),
-        role: "Employee".to_string(),
+        role: Role::Employee,
         hire_date: Some("2024-01-01".to_string()),
         active: true,
         deleted_at: None,
+        anonymized_at: None,
         created_at: Some("2024-01-01".to_string()),
         updated_at: Some("2024-01-01".to_string()),
     };
 
     assert_eq!(employee.first_name, "Alice");
-    assert_eq!(employee.role, "Employee");
+    assert_eq!(employee.role, Role::Employee);
     assert!(employee.active);
 }
 
@@ -132,6 +137,8 @@ fn test_department_model_fields() {
         id: "dept-1".to_string(),
         name: "Marketing".to_string(),
         head_id: Some("head-1".to_string()),
+        parent_id: None,
+        deleted_at: None,
         created_at: Some("2024-01-01".to_string()),
         updated_at: Some("2024-01-01".to_string()),
     };
@@ -147,6 +154,7 @@ fn test_salary_grade_model_fields() {
         code: "M1".to_string(),
         base_salary: 75000.0,
         description: Some("Mid-level".to_string()),
+        deleted_at: None,
         created_at: Some("2024-01-01".to_string()),
     };
 
@@ -164,10 +172,11 @@ fn test_employee_json_round_trip() {
         department_id: None,
         salary_grade_id: None,
         manager_id: None,
-        role: "Admin".to_string(),
+        role: Role::Admin,
         hire_date: None,
         active: true,
         deleted_at: None,
+        anonymized_at: None,
         created_at: None,
         updated_at: None,
     };
@@ -177,7 +186,7 @@ fn test_employee_json_round_trip() {
 
     assert_eq!(deserialized.first_name, "Bob");
     assert_eq!(deserialized.last_name, "Wilson");
-    assert_eq!(deserialized.role, "Admin");
+    assert_eq!(deserialized.role, Role::Admin);
 }
 
 #[test]
@@ -186,6 +195,8 @@ fn test_department_json_round_trip() {
         id: "dept-2".to_string(),
         name: "Finance".to_string(),
         head_id: None,
+        parent_id: None,
+        deleted_at: None,
         created_at: None,
         updated_at: None,
     };
@@ -203,6 +214,7 @@ fn test_salary_grade_json_round_trip() {
         code: "S1".to_string(),
         base_salary: 100000.0,
         description: None,
+        deleted_at: None,
         created_at: None,
     };
 
@@ -226,15 +238,16 @@ fn test_all_employee_roles() {
             department_id: None,
             salary_grade_id: None,
             manager_id: None,
-            role: role.to_string(),
+            role: role.parse().unwrap(),
             hire_date: None,
             active: true,
             deleted_at: None,
+            anonymized_at: None,
             created_at: None,
             updated_at: None,
         };
 
-        assert_eq!(&employee.role, role);
+        assert_eq!(employee.role.as_str(), *role);
     }
 }
 
@@ -248,10 +261,11 @@ fn test_employee_with_all_relationships() {
         department_id: Some("dept-1".to_string()),
         salary_grade_id: Some("grade-1".to_string()),
         manager_id: Some("mgr-1".to_string()),
-        role: "Employee".to_string(),
+        role: Role::Employee,
         hire_date: Some("2024-01-01".to_string()),
         active: true,
         deleted_at: None,
+        anonymized_at: None,
         created_at: Some("2024-01-01".to_string()),
         updated_at: Some("2024-01-01".to_string()),
     };
@@ -272,10 +286,11 @@ fn test_employee_without_relationships() {
         department_id: None,
         salary_grade_id: None,
         manager_id: None,
-        role: "Employee".to_string(),
+        role: Role::Employee,
         hire_date: None,
         active: true,
         deleted_at: None,
+        anonymized_at: None,
         created_at: None,
         updated_at: None,
     };
@@ -301,6 +316,7 @@ fn test_multiple_salary_grades() {
             code: code.to_string(),
             base_salary: salary,
             description: Some(format!("{} level", code)),
+            deleted_at: None,
             created_at: None,
         };
 
@@ -318,6 +334,8 @@ fn test_multiple_departments() {
             id: format!("dept-{}", dept_name.to_lowercase()),
             name: dept_name.to_string(),
             head_id: None,
+            parent_id: None,
+            deleted_at: None,
             created_at: None,
             updated_at: None,
         };
@@ -0,0 +1,201 @@
+// Tests for building org chart and department trees out of the flat hierarchy rows
+
+use backend::error::ApiError;
+use backend::hierarchy::*;
+use backend::models::{ChainBreak, Department, Employee, Role};
+
+fn row(id: &str, manager_id: Option<&str>, level: u32) -> OrgChartRow {
+    OrgChartRow {
+        id: id.to_string(),
+        first_name: id.to_uppercase(),
+        last_name: "Test".to_string(),
+        role: Role::Employee,
+        department_id: None,
+        department_name: None,
+        manager_id: manager_id.map(str::to_string),
+        active: true,
+        level,
+    }
+}
+
+// ceo -> (cto -> (dev1, dev2), cfo)
+fn company() -> Vec<OrgChartRow> {
+    vec![
+        row("ceo", None, 0),
+        row("cfo", Some("ceo"), 1),
+        row("cto", Some("ceo"), 1),
+        row("dev1", Some("cto"), 2),
+        row("dev2", Some("cto"), 2),
+    ]
+}
+
+#[test]
+fn test_build_org_chart_nests_reports_in_row_order() {
+    let chart = build_org_chart(company(), None);
+
+    assert_eq!(chart.len(), 1);
+    let ceo = &chart[0];
+    assert_eq!(ceo.direct_reports, 2);
+    assert_eq!(ceo.total_reports, 4);
+    let ids: Vec<&str> = ceo.reports.iter().map(|n| n.id.as_str()).collect();
+    assert_eq!(ids, ["cfo", "cto"]);
+    assert_eq!(ceo.reports[1].total_reports, 2);
+    assert!(ceo.reports[1].reports[0].reports.is_empty());
+}
+
+#[test]
+fn test_depth_limits_levels_but_not_counts() {
+    let chart = build_org_chart(company(), Some(1));
+    let cto = &chart[0].reports[1];
+    assert_eq!(cto.direct_reports, 2);
+    assert!(cto.reports.is_empty());
+
+    let chart = build_org_chart(company(), Some(0));
+    assert_eq!(chart[0].total_reports, 4);
+    assert!(chart[0].reports.is_empty());
+}
+
+#[test]
+fn test_every_root_becomes_a_tree() {
+    let mut rows = company();
+    rows.insert(1, row("orphan", Some("gone"), 0));
+
+    let chart = build_org_chart(rows, None);
+    let roots: Vec<&str> = chart.iter().map(|n| n.id.as_str()).collect();
+    assert_eq!(roots, ["ceo", "orphan"]);
+    assert_eq!(chart[1].total_reports, 0);
+}
+
+#[test]
+fn test_chain_reaches_the_top() {
+    let rows = vec![
+        row("dev1", Some("cto"), 0),
+        row("cto", Some("ceo"), 1),
+        row("ceo", None, 2),
+    ];
+
+    let chain = build_chain(rows).unwrap();
+    let ids: Vec<(&str, u32)> = chain
+        .managers
+        .iter()
+        .map(|m| (m.id.as_str(), m.level))
+        .collect();
+    assert_eq!(ids, [("cto", 1), ("ceo", 2)]);
+    assert!(chain.complete);
+    assert_eq!(chain.broken, None);
+
+    assert!(build_chain(vec![]).is_none());
+}
+
+#[test]
+fn test_chain_reports_where_it_breaks() {
+    let mut inactive = row("cto", Some("ceo"), 1);
+    inactive.active = false;
+    let chain = build_chain(vec![
+        row("dev1", Some("cto"), 0),
+        inactive,
+        row("ceo", None, 2),
+    ])
+    .unwrap();
+    assert!(chain.managers.is_empty());
+    assert_eq!(chain.broken, Some(ChainBreak::InactiveManager));
+    assert_eq!(chain.broken_at.as_deref(), Some("cto"));
+
+    let chain = build_chain(vec![row("dev1", Some("gone"), 0)]).unwrap();
+    assert_eq!(chain.broken, Some(ChainBreak::MissingManager));
+    assert!(!chain.complete);
+
+    // The query stops at the repeated id, so the rows end with the last new manager
+    let chain = build_chain(vec![row("a", Some("b"), 0), row("b", Some("a"), 1)]).unwrap();
+    assert_eq!(chain.managers.len(), 1);
+    assert_eq!(chain.broken, Some(ChainBreak::Cycle));
+    assert_eq!(chain.broken_at.as_deref(), Some("a"));
+}
+
+fn department(id: &str, name: &str, parent_id: Option<&str>) -> Department {
+    Department {
+        id: id.to_string(),
+        name: name.to_string(),
+        head_id: None,
+        parent_id: parent_id.map(str::to_string),
+        deleted_at: None,
+        created_at: None,
+        updated_at: None,
+    }
+}
+
+#[test]
+fn test_department_tree_nests_by_parent_sorted_by_name() {
+    let tree = build_department_tree(vec![
+        department("team", "Backend", Some("it")),
+        department("it", "IT", Some("ops")),
+        department("hr", "HR", None),
+        department("ops", "Operations", None),
+        department("lost", "Archive", Some("gone")),
+    ]);
+
+    let roots: Vec<&str> = tree.iter().map(|d| d.id.as_str()).collect();
+    assert_eq!(roots, ["lost", "hr", "ops"]);
+    assert_eq!(tree[2].subdepartments[0].id, "it");
+    assert_eq!(tree[2].subdepartments[0].subdepartments[0].id, "team");
+}
+
+#[test]
+fn test_parent_assignment_rejects_itself_and_descendants() {
+    let departments = [
+        department("ops", "Operations", None),
+        department("it", "IT", Some("ops")),
+        department("team", "Backend", Some("it")),
+    ];
+    let check = |id: &str, parent_id: &str| {
+        check_parent_assignment(id, parent_id, |id| {
+            Ok(departments.iter().find(|d| d.id == id).cloned())
+        })
+    };
+
+    assert!(check("team", "ops").is_ok());
+    for (id, parent_id) in [("ops", "ops"), ("ops", "team"), ("ops", "missing")] {
+        assert!(matches!(
+            check(id, parent_id),
+            Err(ApiError::Unprocessable(_))
+        ));
+    }
+}
+
+#[test]
+fn test_head_assignment_promotes_but_keeps_admins() {
+    let head = |role: Role, department_id: Option<&str>| Employee {
+        id: "head".to_string(),
+        first_name: "Head".to_string(),
+        last_name: "Test".to_string(),
+        email: "head@company.com".to_string(),
+        department_id: department_id.map(str::to_string),
+        salary_grade_id: None,
+        manager_id: None,
+        role,
+        hire_date: None,
+        active: true,
+        deleted_at: None,
+        anonymized_at: None,
+        created_at: None,
+        updated_at: None,
+    };
+
+    let promoted = check_head_assignment("it", Some(&head(Role::DeputyHead, None)), true);
+    assert_eq!(promoted.unwrap(), Role::DepartmentHead);
+    let admin = check_head_assignment("it", Some(&head(Role::Admin, None)), true);
+    assert_eq!(admin.unwrap(), Role::Admin);
+
+    let current = head(Role::DepartmentHead, Some("it"));
+    assert_eq!(
+        check_head_assignment("it", Some(&current), false).unwrap(),
+        Role::DepartmentHead
+    );
+    for (role, department_id) in [(Role::DepartmentHead, "ops"), (Role::DeputyHead, "it")] {
+        let candidate = head(role, Some(department_id));
+        assert!(matches!(
+            check_head_assignment("it", Some(&candidate), false),
+            Err(ApiError::Unprocessable(_))
+        ));
+    }
+}
@@ -39,6 +39,8 @@ async fn test_create_department_request_structure() {
     let dept_req = CreateDepartmentRequest {
         name: "Engineering".to_string(),
         head_id: Some("manager-uuid".to_string()),
+        parent_id: None,
+        promote_head: None,
     };
 
     assert_eq!(dept_req.name, "Engineering");
@@ -50,6 +52,8 @@ async fn test_update_department_request_partial() {
     let update_req = UpdateDepartmentRequest {
         name: Some("Updated Engineering".to_string()),
         head_id: None,
+        parent_id: None,
+        promote_head: None,
     };
 
     assert!(update_req.name.is_some());
@@ -1,9 +1,12 @@
 // Integration tests for employee handlers
 // Tests actual HTTP endpoints with a real test server
 
+mod common;
+
 use actix_web::{App, test};
 use backend::handlers::employee::*;
 use backend::models::*;
+use common::as_admin;
 
 #[actix_web::test]
 async fn test_health_endpoint() {
@@ -21,7 +24,7 @@ async fn test_health_endpoint() {
 async fn test_get_employees_endpoint() {
     let app = test::init_service(App::new().service(get_employees)).await;
 
-    let req = test::TestRequest::get().uri("/api/employees").to_request();
+    let req = as_admin(test::TestRequest::get().uri("/api/employees").to_request());
 
     let resp = test::call_service(&app, req).await;
 
@@ -34,9 +37,11 @@ async fn test_get_employees_endpoint() {
 async fn test_get_employees_with_inactive_query() {
     let app = test::init_service(App::new().service(get_employees)).await;
 
-    let req = test::TestRequest::get()
-        .uri("/api/employees?include_inactive=true")
-        .to_request();
+    let req = as_admin(
+        test::TestRequest::get()
+            .uri("/api/employees?include_inactive=true")
+            .to_request(),
+    );
 
     let resp = test::call_service(&app, req).await;
 
@@ -53,7 +58,7 @@ async fn test_create_employee_request_structure() {
         department_id: Some("dept-123".to_string()),
         salary_grade_id: Some("grade-456".to_string()),
         manager_id: None,
-        role: Some("Employee".to_string()),
+        role: Some(Role::Employee),
         hire_date: Some("2024-01-01".to_string()),
     };
 
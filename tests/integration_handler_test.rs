@@ -67,6 +67,8 @@ async fn test_create_user_endpoint() {
     let create_req = CreateUserRequest {
         name: "Test User".to_string(),
         email: "test@example.com".to_string(),
+        employee_id: None,
+        password: None,
     };
 
     let req = test::TestRequest::post()
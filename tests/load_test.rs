@@ -0,0 +1,241 @@
+// Load test: a slow query must not stall the other requests served by the same worker
+
+mod common;
+
+use actix_web::dev::Service;
+use actix_web::{App, HttpMessage, HttpServer, web};
+use backend::auth::AuthenticatedUser;
+use backend::error::ApiError;
+use backend::handlers::{get_employee_by_id, get_employees};
+use backend::hierarchy::OrgChartRow;
+use backend::models::*;
+use backend::pagination::{Page, PageRequest, SortKey};
+use backend::repository::{
+    EmployeeFilter, EmployeeRepository, EmployeeSortField, PurgeMode, PurgedReferences,
+};
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpStream};
+use std::sync::{Arc, mpsc};
+use std::thread;
+use std::time::{Duration, Instant};
+
+const SLOW_QUERY: Duration = Duration::from_secs(2);
+const FAST_REQUESTS: usize = 50;
+
+// Listing stands in for a slow query, looking up a single employee is instant
+struct SlowListEmployees;
+
+impl EmployeeRepository for SlowListEmployees {
+    fn list_page(
+        &self,
+        _filter: &EmployeeFilter,
+        _sort: &[SortKey<EmployeeSortField>],
+        _page: PageRequest,
+    ) -> Result<Page<Employee>, ApiError> {
+        thread::sleep(SLOW_QUERY);
+        Ok(Page {
+            items: vec![],
+            total: 0,
+        })
+    }
+
+    fn search(
+        &self,
+        _terms: &[String],
+        _include_inactive: bool,
+        page: PageRequest,
+    ) -> Result<Page<Employee>, ApiError> {
+        Ok(page.slice(vec![]))
+    }
+
+    fn org_chart(&self, _root_id: Option<&str>) -> Result<Vec<OrgChartRow>, ApiError> {
+        Ok(vec![])
+    }
+
+    fn management_chain(&self, _id: &str) -> Result<Vec<OrgChartRow>, ApiError> {
+        Ok(vec![])
+    }
+
+    fn list_by_department(
+        &self,
+        _department_id: &str,
+        _recursive: bool,
+    ) -> Result<Vec<Employee>, ApiError> {
+        Ok(vec![])
+    }
+
+    fn find_by_id(&self, id: &str) -> Result<Option<Employee>, ApiError> {
+        Ok(Some(Employee {
+            id: id.to_string(),
+            first_name: "Test".to_string(),
+            last_name: "Employee".to_string(),
+            email: "test@company.com".to_string(),
+            department_id: None,
+            salary_grade_id: None,
+            manager_id: None,
+            role: Role::Employee,
+            hire_date: None,
+            active: true,
+            deleted_at: None,
+            anonymized_at: None,
+            created_at: None,
+            updated_at: None,
+        }))
+    }
+
+    fn create(
+        &self,
+        _employee: &CreateEmployeeRequest,
+        _role: Role,
+        _caller: &AuthenticatedUser,
+    ) -> Result<String, ApiError> {
+        Ok("new-id".to_string())
+    }
+
+    fn update(
+        &self,
+        id: &str,
+        _changes: &UpdateEmployeeRequest,
+        _caller: &AuthenticatedUser,
+    ) -> Result<Employee, ApiError> {
+        Ok(self.find_by_id(id)?.unwrap())
+    }
+
+    fn soft_delete(&self, id: &str, _caller: &AuthenticatedUser) -> Result<Employee, ApiError> {
+        Ok(self.find_by_id(id)?.unwrap())
+    }
+
+    fn restore(&self, id: &str, _caller: &AuthenticatedUser) -> Result<EmployeeRestore, ApiError> {
+        Ok(EmployeeRestore {
+            employee: self.find_by_id(id)?.unwrap(),
+            nulled_references: vec![],
+        })
+    }
+
+    fn personal_data(&self, id: &str) -> Result<PersonalData, ApiError> {
+        Ok(PersonalData {
+            employee: self.find_by_id(id)?.unwrap(),
+            department_name: None,
+            manager_name: None,
+            salary_grade_code: None,
+            headed_departments: vec![],
+            account: None,
+            api_keys: vec![],
+        })
+    }
+
+    fn anonymize(&self, id: &str) -> Result<Employee, ApiError> {
+        Ok(self.find_by_id(id)?.unwrap())
+    }
+
+    fn expired(&self, _deleted_before: &str, _mode: PurgeMode) -> Result<Vec<String>, ApiError> {
+        Ok(vec![])
+    }
+
+    fn purge(
+        &self,
+        _id: &str,
+        _mode: PurgeMode,
+        _deleted_before: &str,
+    ) -> Result<Option<PurgedReferences>, ApiError> {
+        Ok(None)
+    }
+
+    fn assign_manager(
+        &self,
+        id: &str,
+        _manager_id: &str,
+        _caller: &AuthenticatedUser,
+    ) -> Result<Employee, ApiError> {
+        Ok(self.find_by_id(id)?.unwrap())
+    }
+
+    fn assign_salary_grade(
+        &self,
+        id: &str,
+        _salary_grade_id: &str,
+        _caller: &AuthenticatedUser,
+    ) -> Result<Employee, ApiError> {
+        Ok(self.find_by_id(id)?.unwrap())
+    }
+}
+
+// Real server with a single worker, so a blocked worker would hold up every request
+fn start_server() -> SocketAddr {
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        actix_web::rt::System::new().block_on(async move {
+            let repository: Arc<dyn EmployeeRepository> = Arc::new(SlowListEmployees);
+            let employees = web::Data::from(repository);
+            let server = HttpServer::new(move || {
+                App::new()
+                    .app_data(employees.clone())
+                    .wrap_fn(|req, srv| {
+                        req.extensions_mut().insert(AuthenticatedUser {
+                            employee_id: common::ADMIN_ID.to_string(),
+                            role: Role::Admin,
+                            department_id: None,
+                        });
+                        srv.call(req)
+                    })
+                    .service(get_employees)
+                    .service(get_employee_by_id)
+            })
+            .workers(1)
+            .bind(("127.0.0.1", 0))
+            .unwrap();
+            tx.send(server.addrs()[0]).unwrap();
+            server.run().await
+        })
+    });
+    rx.recv().unwrap()
+}
+
+// Plain HTTP/1.1 GET, returns the status code
+fn get(addr: SocketAddr, path: &str) -> u16 {
+    let mut stream = TcpStream::connect(addr).unwrap();
+    write!(
+        stream,
+        "GET {} HTTP/1.1\r\nHost: {}\r\nConnection: close\r\n\r\n",
+        path, addr
+    )
+    .unwrap();
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    response
+        .split_whitespace()
+        .nth(1)
+        .and_then(|status| status.parse().ok())
+        .unwrap_or(0)
+}
+
+#[test]
+fn test_slow_query_does_not_block_other_requests() {
+    let addr = start_server();
+
+    let slow = thread::spawn(move || get(addr, "/api/employees"));
+    // Give the slow request time to reach the repository
+    thread::sleep(Duration::from_millis(200));
+
+    let started = Instant::now();
+    let fast: Vec<_> = (0..FAST_REQUESTS)
+        .map(|i| thread::spawn(move || get(addr, &format!("/api/employees/{}", i))))
+        .collect();
+    for request in fast {
+        assert_eq!(request.join().unwrap(), 200);
+    }
+    let elapsed = started.elapsed();
+
+    assert!(
+        elapsed < SLOW_QUERY / 2,
+        "{} requests took {:?} while a slow query was running",
+        FAST_REQUESTS,
+        elapsed
+    );
+    assert!(
+        !slow.is_finished(),
+        "slow query finished before the fast requests"
+    );
+    assert_eq!(slow.join().unwrap(), 200);
+}
@@ -0,0 +1,1098 @@
+// Tests for the in-memory backend: unique keys, foreign keys and ON DELETE behaviour
+
+use backend::auth::AuthenticatedUser;
+use backend::error::ApiError;
+use backend::models::*;
+use backend::pagination::{PageRequest, parse_sort};
+use backend::repository::{
+    DepartmentEmployees, EmployeeFilter, PurgeMode, Repositories, Storage, search_terms,
+};
+
+fn admin() -> AuthenticatedUser {
+    AuthenticatedUser {
+        employee_id: "admin".to_string(),
+        role: Role::Admin,
+        department_id: None,
+    }
+}
+
+fn employee_request(email: &str) -> CreateEmployeeRequest {
+    CreateEmployeeRequest {
+        first_name: "Test".to_string(),
+        last_name: "Employee".to_string(),
+        email: email.to_string(),
+        department_id: None,
+        salary_grade_id: None,
+        manager_id: None,
+        role: None,
+        hire_date: None,
+    }
+}
+
+fn department(storage: &Repositories, name: &str) -> String {
+    storage
+        .departments
+        .create(&CreateDepartmentRequest {
+            name: name.to_string(),
+            head_id: None,
+            parent_id: None,
+            promote_head: None,
+        })
+        .unwrap()
+}
+
+fn empty_update() -> UpdateEmployeeRequest {
+    UpdateEmployeeRequest {
+        first_name: None,
+        last_name: None,
+        email: None,
+        department_id: None,
+        salary_grade_id: None,
+        manager_id: None,
+        role: None,
+        hire_date: None,
+        active: None,
+    }
+}
+
+#[test]
+fn test_storage_parses_from_env_value() {
+    assert_eq!("memory".parse::<Storage>().unwrap(), Storage::Memory);
+    assert_eq!("MySQL".parse::<Storage>().unwrap(), Storage::Mysql);
+    assert_eq!(Storage::default(), Storage::Mysql);
+    assert!("postgres".parse::<Storage>().is_err());
+}
+
+#[test]
+fn test_duplicate_keys_conflict_case_insensitively() {
+    let storage = Repositories::memory();
+    storage
+        .employees
+        .create(
+            &employee_request("jane@company.com"),
+            Role::Employee,
+            &admin(),
+        )
+        .unwrap();
+    department(&storage, "Engineering");
+
+    match storage.employees.create(
+        &employee_request("Jane@Company.com"),
+        Role::Employee,
+        &admin(),
+    ) {
+        Err(ApiError::Conflict(msg)) => {
+            assert_eq!(msg, "An employee with this email already exists")
+        }
+        other => panic!("expected conflict, got {:?}", other),
+    }
+    assert!(matches!(
+        storage.departments.create(&CreateDepartmentRequest {
+            name: "ENGINEERING".to_string(),
+            head_id: None,
+            parent_id: None,
+            promote_head: None,
+        }),
+        Err(ApiError::Conflict(_))
+    ));
+}
+
+#[test]
+fn test_update_keeps_own_email_but_not_others() {
+    let storage = Repositories::memory();
+    let jane = storage
+        .employees
+        .create(
+            &employee_request("jane@company.com"),
+            Role::Employee,
+            &admin(),
+        )
+        .unwrap();
+    storage
+        .employees
+        .create(
+            &employee_request("john@company.com"),
+            Role::Employee,
+            &admin(),
+        )
+        .unwrap();
+
+    let mut changes = empty_update();
+    changes.email = Some("jane@company.com".to_string());
+    assert!(storage.employees.update(&jane, &changes, &admin()).is_ok());
+
+    changes.email = Some("john@company.com".to_string());
+    assert!(matches!(
+        storage.employees.update(&jane, &changes, &admin()),
+        Err(ApiError::Conflict(_))
+    ));
+}
+
+#[test]
+fn test_unknown_references_are_unprocessable() {
+    let storage = Repositories::memory();
+
+    let mut request = employee_request("jane@company.com");
+    request.department_id = Some("650e8400-e29b-41d4-a716-446655440099".to_string());
+    match storage.employees.create(&request, Role::Employee, &admin()) {
+        Err(ApiError::Unprocessable(msg)) => assert_eq!(
+            msg,
+            "department_id does not reference an existing department"
+        ),
+        other => panic!("expected unprocessable, got {:?}", other),
+    }
+
+    let jane = storage
+        .employees
+        .create(
+            &employee_request("jane@company.com"),
+            Role::Employee,
+            &admin(),
+        )
+        .unwrap();
+    assert!(matches!(
+        storage.employees.assign_manager(&jane, "missing", &admin()),
+        Err(ApiError::Unprocessable(_))
+    ));
+    assert!(matches!(
+        storage
+            .employees
+            .assign_salary_grade(&jane, "missing", &admin()),
+        Err(ApiError::Unprocessable(_))
+    ));
+}
+
+#[test]
+fn test_deletes_null_out_references() {
+    let storage = Repositories::memory();
+    let dept = department(&storage, "Engineering");
+    let grade = storage
+        .salary_grades
+        .create(&CreateSalaryGradeRequest {
+            code: "E1".to_string(),
+            base_salary: 45000.0,
+            description: None,
+        })
+        .unwrap();
+
+    let mut request = employee_request("boss@company.com");
+    request.department_id = Some(dept.clone());
+    let boss = storage
+        .employees
+        .create(&request, Role::Employee, &admin())
+        .unwrap();
+
+    let mut request = employee_request("jane@company.com");
+    request.department_id = Some(dept.clone());
+    request.salary_grade_id = Some(grade.clone());
+    request.manager_id = Some(boss.clone());
+    let jane = storage
+        .employees
+        .create(&request, Role::Employee, &admin())
+        .unwrap();
+
+    storage
+        .departments
+        .delete(&dept, DepartmentEmployees::Unassign)
+        .unwrap();
+    storage.salary_grades.delete(&grade).unwrap();
+    storage.employees.soft_delete(&boss, &admin()).unwrap();
+    storage
+        .employees
+        .purge(&boss, PurgeMode::Delete, "9999-12-31T23:59:59Z")
+        .unwrap();
+
+    let jane = storage.employees.find_by_id(&jane).unwrap().unwrap();
+    assert_eq!(jane.department_id, None);
+    // A soft deleted salary grade stays assigned, it can come back with a restore
+    assert_eq!(jane.salary_grade_id, Some(grade));
+    assert_eq!(jane.manager_id, None);
+}
+
+#[test]
+fn test_soft_delete_hides_employee_from_active_lists() {
+    let storage = Repositories::memory();
+    let dept = department(&storage, "Engineering");
+    let mut request = employee_request("jane@company.com");
+    request.department_id = Some(dept.clone());
+    let jane = storage
+        .employees
+        .create(&request, Role::Employee, &admin())
+        .unwrap();
+
+    let deleted = storage.employees.soft_delete(&jane, &admin()).unwrap();
+
+    // Deleting again keeps the first deleted_at, timestamps have whole seconds
+    std::thread::sleep(std::time::Duration::from_millis(1100));
+    let again = storage.employees.soft_delete(&jane, &admin()).unwrap();
+    assert_eq!(again.deleted_at, deleted.deleted_at);
+
+    let count = |active| {
+        let filter = EmployeeFilter {
+            active,
+            ..Default::default()
+        };
+        let page = storage
+            .employees
+            .list_page(&filter, &[], PageRequest::default());
+        page.unwrap().total
+    };
+    assert_eq!(count(Some(true)), 0);
+    assert!(
+        storage
+            .employees
+            .list_by_department(&dept, false)
+            .unwrap()
+            .is_empty()
+    );
+    assert_eq!(count(None), 1);
+}
+
+#[test]
+fn test_changes_are_checked_against_the_caller_on_the_current_row() {
+    let storage = Repositories::memory();
+    let sales = department(&storage, "Sales");
+    let support = department(&storage, "Support");
+    let head = AuthenticatedUser {
+        employee_id: "head".to_string(),
+        role: Role::DepartmentHead,
+        department_id: Some(sales.clone()),
+    };
+    let mut request = employee_request("jane@company.com");
+    request.department_id = Some(sales.clone());
+    let jane = storage
+        .employees
+        .create(&request, Role::Employee, &admin())
+        .unwrap();
+    request = employee_request("joe@company.com");
+    request.department_id = Some(support.clone());
+    let joe = storage
+        .employees
+        .create(&request, Role::Employee, &admin())
+        .unwrap();
+
+    // A manager from another department is refused
+    assert!(matches!(
+        storage.employees.assign_manager(&jane, &joe, &head),
+        Err(ApiError::Forbidden(_))
+    ));
+
+    // Whatever the caller saw before, an employee moved to another department is out of reach
+    let mut changes = empty_update();
+    changes.department_id = Some(support.clone());
+    storage.employees.update(&jane, &changes, &admin()).unwrap();
+    assert!(matches!(
+        storage.employees.soft_delete(&jane, &head),
+        Err(ApiError::Forbidden(_))
+    ));
+    assert!(storage.employees.find_by_id(&jane).unwrap().unwrap().active);
+
+    // And so is one that was promoted to the caller's rank
+    request = employee_request("sam@company.com");
+    request.department_id = Some(sales.clone());
+    let sam = storage
+        .employees
+        .create(&request, Role::Employee, &admin())
+        .unwrap();
+    changes = empty_update();
+    changes.role = Some(Role::DepartmentHead);
+    storage.employees.update(&sam, &changes, &admin()).unwrap();
+    changes = empty_update();
+    changes.first_name = Some("Samuel".to_string());
+    assert!(matches!(
+        storage.employees.update(&sam, &changes, &head),
+        Err(ApiError::Forbidden(_))
+    ));
+}
+
+#[test]
+fn test_update_without_fields_is_rejected() {
+    let storage = Repositories::memory();
+
+    assert!(matches!(
+        storage.employees.update("any", &empty_update(), &admin()),
+        Err(ApiError::BadRequest(_))
+    ));
+    assert!(matches!(
+        storage.departments.update(
+            "any",
+            &UpdateDepartmentRequest {
+                name: None,
+                head_id: None,
+                parent_id: None,
+                promote_head: None,
+            }
+        ),
+        Err(ApiError::BadRequest(_))
+    ));
+}
+
+#[test]
+fn test_list_page_filters_sorts_and_pages() {
+    let storage = Repositories::memory();
+    let dept = department(&storage, "Engineering");
+    for (email, last_name, role) in [
+        ("b@company.com", "brown", Role::Employee),
+        ("a@company.com", "Adams", Role::DeputyHead),
+        ("c@company.com", "Clark", Role::Employee),
+        ("d@company.com", "Diaz", Role::Employee),
+    ] {
+        let mut request = employee_request(email);
+        request.last_name = last_name.to_string();
+        request.department_id = Some(dept.clone());
+        storage.employees.create(&request, role, &admin()).unwrap();
+    }
+    let outsider = storage
+        .employees
+        .create(&employee_request("e@company.com"), Role::Employee, &admin())
+        .unwrap();
+    storage.employees.soft_delete(&outsider, &admin()).unwrap();
+
+    let filter = EmployeeFilter {
+        department_id: Some(dept.clone()),
+        role: Some(Role::Employee),
+        active: Some(true),
+        ..Default::default()
+    };
+    let sort = parse_sort("-last_name").unwrap();
+    let page = storage
+        .employees
+        .list_page(&filter, &sort, PageRequest::new(Some(1), Some(2)))
+        .unwrap();
+    let names: Vec<&str> = page.items.iter().map(|e| e.last_name.as_str()).collect();
+    assert_eq!(page.total, 3);
+    assert_eq!(names, ["Diaz", "Clark"]);
+
+    // Text sorts ignore case like the MySQL collation
+    let everyone = storage
+        .employees
+        .list_page(
+            &EmployeeFilter::default(),
+            &parse_sort("last_name").unwrap(),
+            PageRequest::default(),
+        )
+        .unwrap();
+    let names: Vec<&str> = everyone
+        .items
+        .iter()
+        .map(|e| e.last_name.as_str())
+        .collect();
+    assert_eq!(names, ["Adams", "brown", "Clark", "Diaz", "Employee"]);
+}
+
+#[test]
+fn test_search_ignores_case_and_accents_and_ranks_matches() {
+    let storage = Repositories::memory();
+    for (first_name, last_name, email) in [
+        ("Anna", "Smith", "mullan@company.com"),
+        ("José", "Müller", "jose.mueller@company.com"),
+        ("Mull", "Baker", "mb@company.com"),
+        ("Zoe", "Xu", "zx@company.com"),
+    ] {
+        let mut request = employee_request(email);
+        request.first_name = first_name.to_string();
+        request.last_name = last_name.to_string();
+        storage
+            .employees
+            .create(&request, Role::Employee, &admin())
+            .unwrap();
+    }
+    let search = |query: &str| {
+        storage
+            .employees
+            .search(&search_terms(query), false, PageRequest::default())
+            .unwrap()
+    };
+
+    // Whole first name, then last name prefix, then email prefix
+    let found = search("MULL");
+    let names: Vec<&str> = found.items.iter().map(|e| e.last_name.as_str()).collect();
+    assert_eq!(found.total, 3);
+    assert_eq!(names, ["Baker", "Müller", "Smith"]);
+
+    let found = search("jose mül");
+    assert_eq!(found.total, 1);
+    assert_eq!(found.items[0].first_name, "José");
+
+    assert_eq!(search("mull%").total, 0);
+}
+
+#[test]
+fn test_org_chart_follows_active_reports() {
+    let storage = Repositories::memory();
+    let dept = department(&storage, "Engineering");
+    let create = |email: &str, manager_id: Option<&str>| {
+        let mut request = employee_request(email);
+        request.department_id = Some(dept.clone());
+        request.manager_id = manager_id.map(str::to_string);
+        storage
+            .employees
+            .create(&request, Role::Employee, &admin())
+            .unwrap()
+    };
+    let boss = create("boss@company.com", None);
+    let lead = create("lead@company.com", Some(&boss));
+    let dev = create("dev@company.com", Some(&lead));
+    let left = create("left@company.com", Some(&boss));
+    let orphan = create("orphan@company.com", Some(&left));
+    storage.employees.soft_delete(&left, &admin()).unwrap();
+
+    let rows = storage.employees.org_chart(Some(&boss)).unwrap();
+    let ids: Vec<(&str, u32)> = rows.iter().map(|r| (r.id.as_str(), r.level)).collect();
+    assert_eq!(
+        ids,
+        [(boss.as_str(), 0), (lead.as_str(), 1), (dev.as_str(), 2)]
+    );
+    assert_eq!(rows[0].department_name.as_deref(), Some("Engineering"));
+
+    // Reports of an inactive manager show up as roots of their own
+    let roots: Vec<String> = storage
+        .employees
+        .org_chart(None)
+        .unwrap()
+        .into_iter()
+        .filter(|r| r.level == 0)
+        .map(|r| r.id)
+        .collect();
+    assert_eq!(roots.len(), 2);
+    assert!(roots.contains(&boss) && roots.contains(&orphan));
+}
+
+#[test]
+fn test_management_chain_includes_inactive_managers() {
+    let storage = Repositories::memory();
+    let create = |email: &str, manager_id: Option<&str>| {
+        let mut request = employee_request(email);
+        request.manager_id = manager_id.map(str::to_string);
+        storage
+            .employees
+            .create(&request, Role::Employee, &admin())
+            .unwrap()
+    };
+    let boss = create("boss@company.com", None);
+    let lead = create("lead@company.com", Some(&boss));
+    let dev = create("dev@company.com", Some(&lead));
+    storage.employees.soft_delete(&lead, &admin()).unwrap();
+
+    let rows = storage.employees.management_chain(&dev).unwrap();
+    let ids: Vec<(&str, bool)> = rows.iter().map(|r| (r.id.as_str(), r.active)).collect();
+    assert_eq!(
+        ids,
+        [
+            (dev.as_str(), true),
+            (lead.as_str(), false),
+            (boss.as_str(), true)
+        ]
+    );
+
+    assert!(
+        storage
+            .employees
+            .management_chain("unknown")
+            .unwrap()
+            .is_empty()
+    );
+}
+
+#[test]
+fn test_manager_assignment_rejects_cycles_and_inactive_managers() {
+    let storage = Repositories::memory();
+    let create = |email: &str, manager_id: Option<&str>| {
+        let mut request = employee_request(email);
+        request.manager_id = manager_id.map(str::to_string);
+        storage.employees.create(&request, Role::Employee, &admin())
+    };
+    let boss = create("boss@company.com", None).unwrap();
+    let lead = create("lead@company.com", Some(&boss)).unwrap();
+    let dev = create("dev@company.com", Some(&lead)).unwrap();
+    let left = create("left@company.com", None).unwrap();
+    storage.employees.soft_delete(&left, &admin()).unwrap();
+
+    let refused = |result: Result<Employee, ApiError>| match result {
+        Err(ApiError::Unprocessable(msg)) => msg,
+        other => panic!("expected unprocessable, got {:?}", other),
+    };
+    assert!(
+        refused(storage.employees.assign_manager(&boss, &boss, &admin())).contains("own manager")
+    );
+    assert!(refused(storage.employees.assign_manager(&boss, &dev, &admin())).contains("cycle"));
+    assert!(refused(storage.employees.assign_manager(&dev, &left, &admin())).contains("inactive"));
+
+    let changes = UpdateEmployeeRequest {
+        first_name: None,
+        last_name: None,
+        email: None,
+        department_id: None,
+        salary_grade_id: None,
+        manager_id: Some(lead.clone()),
+        role: None,
+        hire_date: None,
+        active: None,
+    };
+    assert!(refused(storage.employees.update(&boss, &changes, &admin())).contains("cycle"));
+    assert!(matches!(
+        create("new@company.com", Some(&left)),
+        Err(ApiError::Unprocessable(_))
+    ));
+
+    // Nothing was written, and moving a report sideways is still fine
+    let found = storage.employees.find_by_id(&boss).unwrap().unwrap();
+    assert_eq!(found.manager_id, None);
+    storage
+        .employees
+        .assign_manager(&dev, &boss, &admin())
+        .unwrap();
+}
+
+#[test]
+fn test_changes_to_unknown_ids_are_not_found() {
+    let storage = Repositories::memory();
+    let jane = storage
+        .employees
+        .create(
+            &employee_request("jane@company.com"),
+            Role::Employee,
+            &admin(),
+        )
+        .unwrap();
+
+    let mut changes = empty_update();
+    changes.first_name = Some("Janet".to_string());
+    assert_eq!(
+        storage
+            .employees
+            .update(&jane, &changes, &admin())
+            .unwrap()
+            .first_name,
+        "Janet"
+    );
+
+    let not_found = |result: Result<Employee, ApiError>| matches!(result, Err(ApiError::NotFound(msg)) if msg == "Employee not found");
+    assert!(not_found(storage.employees.update(
+        "missing",
+        &changes,
+        &admin()
+    )));
+    assert!(not_found(
+        storage.employees.soft_delete("missing", &admin())
+    ));
+    assert!(not_found(storage.employees.assign_manager(
+        "missing",
+        &jane,
+        &admin()
+    )));
+    assert!(not_found(storage.employees.assign_salary_grade(
+        "missing",
+        "any",
+        &admin()
+    )));
+    assert!(matches!(
+        storage
+            .departments
+            .delete("missing", DepartmentEmployees::Refuse),
+        Err(ApiError::NotFound(_))
+    ));
+    assert!(matches!(
+        storage.salary_grades.delete("missing"),
+        Err(ApiError::NotFound(_))
+    ));
+}
+
+#[test]
+fn test_department_hierarchy() {
+    let storage = Repositories::memory();
+    let create = |name: &str, parent_id: Option<&str>| {
+        storage
+            .departments
+            .create(&CreateDepartmentRequest {
+                name: name.to_string(),
+                head_id: None,
+                parent_id: parent_id.map(str::to_string),
+                promote_head: None,
+            })
+            .unwrap()
+    };
+    let ops = create("Operations", None);
+    let it = create("IT", Some(&ops));
+    let backend = create("Backend", Some(&it));
+    let frontend = create("Frontend", Some(&it));
+    for (email, dept) in [("ops@company.com", &ops), ("dev@company.com", &backend)] {
+        let mut request = employee_request(email);
+        request.department_id = Some(dept.clone());
+        storage
+            .employees
+            .create(&request, Role::Employee, &admin())
+            .unwrap();
+    }
+
+    let children: Vec<String> = storage
+        .departments
+        .subdepartments(&it)
+        .unwrap()
+        .into_iter()
+        .map(|d| d.id)
+        .collect();
+    assert_eq!(children, [backend.clone(), frontend]);
+
+    let list = |recursive| {
+        storage
+            .employees
+            .list_by_department(&ops, recursive)
+            .unwrap()
+    };
+    assert_eq!(list(false).len(), 1);
+    assert_eq!(list(true).len(), 2);
+
+    let move_under = |parent_id: &str| {
+        storage.departments.update(
+            &ops,
+            &UpdateDepartmentRequest {
+                name: None,
+                head_id: None,
+                parent_id: Some(parent_id.to_string()),
+                promote_head: None,
+            },
+        )
+    };
+    assert!(matches!(
+        move_under(&backend),
+        Err(ApiError::Unprocessable(_))
+    ));
+    assert!(matches!(move_under(&ops), Err(ApiError::Unprocessable(_))));
+
+    // fk_dept_parent is ON DELETE SET NULL
+    storage
+        .departments
+        .delete(&it, DepartmentEmployees::Refuse)
+        .unwrap();
+    let backend = storage.departments.find_by_id(&backend).unwrap().unwrap();
+    assert_eq!(backend.parent_id, None);
+}
+
+// A department led by a promoted head, returns (department, head)
+fn headed_department(storage: &Repositories) -> (String, String) {
+    let sales = department(storage, "Sales");
+    let head = storage
+        .employees
+        .create(
+            &employee_request("head@company.com"),
+            Role::Employee,
+            &admin(),
+        )
+        .unwrap();
+    storage
+        .departments
+        .update(
+            &sales,
+            &UpdateDepartmentRequest {
+                name: None,
+                head_id: Some(head.clone()),
+                parent_id: None,
+                promote_head: Some(true),
+            },
+        )
+        .unwrap();
+    (sales, head)
+}
+
+fn head_of(storage: &Repositories, department_id: &str) -> Option<String> {
+    let department = storage.departments.find_by_id(department_id).unwrap();
+    department.unwrap().head_id
+}
+
+#[test]
+fn test_deactivated_head_leaves_the_department_headless() {
+    let storage = Repositories::memory();
+    let (sales, head) = headed_department(&storage);
+
+    let mut changes = empty_update();
+    changes.active = Some(false);
+    storage.employees.update(&head, &changes, &admin()).unwrap();
+    assert_eq!(head_of(&storage, &sales), None);
+}
+
+#[test]
+fn test_soft_deleted_head_leaves_the_department_headless() {
+    let storage = Repositories::memory();
+    let (sales, head) = headed_department(&storage);
+
+    storage.employees.soft_delete(&head, &admin()).unwrap();
+    assert_eq!(head_of(&storage, &sales), None);
+}
+
+#[test]
+fn test_head_moved_to_another_department_leaves_the_old_one_headless() {
+    let storage = Repositories::memory();
+    let (sales, head) = headed_department(&storage);
+    let support = department(&storage, "Support");
+
+    // Other changes keep the head in place
+    let mut changes = empty_update();
+    changes.first_name = Some("Renamed".to_string());
+    storage.employees.update(&head, &changes, &admin()).unwrap();
+    assert_eq!(head_of(&storage, &sales), Some(head.clone()));
+
+    let mut changes = empty_update();
+    changes.department_id = Some(support);
+    storage.employees.update(&head, &changes, &admin()).unwrap();
+    assert_eq!(head_of(&storage, &sales), None);
+}
+
+#[test]
+fn test_demoted_head_leaves_the_department_headless() {
+    let storage = Repositories::memory();
+    let (sales, head) = headed_department(&storage);
+
+    let mut changes = empty_update();
+    changes.role = Some(Role::Employee);
+    storage.employees.update(&head, &changes, &admin()).unwrap();
+    assert_eq!(head_of(&storage, &sales), None);
+}
+
+#[test]
+fn test_anonymized_head_leaves_the_department_headless() {
+    let storage = Repositories::memory();
+    let (sales, head) = headed_department(&storage);
+
+    storage.employees.anonymize(&head).unwrap();
+    assert_eq!(head_of(&storage, &sales), None);
+}
+
+#[test]
+fn test_department_head_is_checked_promoted_and_replaced() {
+    let storage = Repositories::memory();
+    let sales = department(&storage, "Sales");
+    let support = department(&storage, "Support");
+    let alice = storage
+        .employees
+        .create(
+            &employee_request("alice@company.com"),
+            Role::Employee,
+            &admin(),
+        )
+        .unwrap();
+    let bob = storage
+        .employees
+        .create(
+            &employee_request("bob@company.com"),
+            Role::Employee,
+            &admin(),
+        )
+        .unwrap();
+    let set_head = |department_id: &str, head_id: &str, promote_head: Option<bool>| {
+        storage.departments.update(
+            department_id,
+            &UpdateDepartmentRequest {
+                name: None,
+                head_id: Some(head_id.to_string()),
+                parent_id: None,
+                promote_head,
+            },
+        )
+    };
+    let employee = |id: &str| storage.employees.find_by_id(id).unwrap().unwrap();
+
+    // Without promote_head the head has to be a DepartmentHead of the department already
+    assert!(matches!(
+        set_head(&sales, &alice, None),
+        Err(ApiError::Unprocessable(_))
+    ));
+    assert!(matches!(
+        set_head(&sales, "00000000-0000-0000-0000-000000000000", Some(true)),
+        Err(ApiError::Unprocessable(_))
+    ));
+
+    let updated = set_head(&sales, &alice, Some(true)).unwrap();
+    assert_eq!(updated.head_id.as_deref(), Some(alice.as_str()));
+    assert_eq!(employee(&alice).role, Role::DepartmentHead);
+    assert_eq!(
+        employee(&alice).department_id.as_deref(),
+        Some(sales.as_str())
+    );
+    set_head(&sales, &alice, None).unwrap();
+
+    // The replaced head steps down
+    set_head(&sales, &bob, Some(true)).unwrap();
+    assert_eq!(employee(&alice).role, Role::Employee);
+    assert_eq!(employee(&bob).role, Role::DepartmentHead);
+
+    // Heading another department leaves the first one without a head
+    set_head(&support, &bob, Some(true)).unwrap();
+    let sales_head = storage.departments.find_by_id(&sales).unwrap().unwrap();
+    assert_eq!(sales_head.head_id, None);
+    assert_eq!(
+        employee(&bob).department_id.as_deref(),
+        Some(support.as_str())
+    );
+
+    storage.employees.soft_delete(&alice, &admin()).unwrap();
+    assert!(matches!(
+        set_head(&sales, &alice, Some(true)),
+        Err(ApiError::Unprocessable(_))
+    ));
+
+    // A head that is deleted leaves the department without one
+    storage.employees.soft_delete(&bob, &admin()).unwrap();
+    let support = storage.departments.find_by_id(&support).unwrap().unwrap();
+    assert_eq!(support.head_id, None);
+}
+
+#[test]
+fn test_department_delete_refuses_to_orphan_active_employees() {
+    let storage = Repositories::memory();
+    let sales = department(&storage, "Sales");
+    let support = department(&storage, "Support");
+    let mut request = employee_request("jane@company.com");
+    request.department_id = Some(sales.clone());
+    let jane = storage
+        .employees
+        .create(&request, Role::Employee, &admin())
+        .unwrap();
+    request = employee_request("joe@company.com");
+    request.department_id = Some(support.clone());
+    let joe = storage
+        .employees
+        .create(&request, Role::Employee, &admin())
+        .unwrap();
+
+    assert!(matches!(
+        storage
+            .departments
+            .delete(&sales, DepartmentEmployees::Refuse),
+        Err(ApiError::Conflict(_))
+    ));
+    assert!(matches!(
+        storage
+            .departments
+            .delete(&sales, DepartmentEmployees::ReassignTo("missing")),
+        Err(ApiError::Unprocessable(_))
+    ));
+
+    let deletion = storage
+        .departments
+        .delete(&sales, DepartmentEmployees::Unassign)
+        .unwrap();
+    assert_eq!(deletion.unassigned_employees, 1);
+    let jane = storage.employees.find_by_id(&jane).unwrap().unwrap();
+    assert_eq!(jane.department_id, None);
+
+    // Inactive employees don't hold a department back and keep it as the one they left from
+    storage.employees.soft_delete(&joe, &admin()).unwrap();
+    let deletion = storage
+        .departments
+        .delete(&support, DepartmentEmployees::Refuse)
+        .unwrap();
+    assert_eq!(deletion.unassigned_employees, 0);
+    let joe = storage.employees.find_by_id(&joe).unwrap().unwrap();
+    assert_eq!(joe.department_id, Some(support));
+}
+
+#[test]
+fn test_deleted_departments_and_grades_are_kept_and_restored() {
+    let storage = Repositories::memory();
+    let ops = department(&storage, "Operations");
+    let it = storage
+        .departments
+        .create(&CreateDepartmentRequest {
+            name: "IT".to_string(),
+            head_id: None,
+            parent_id: Some(ops.clone()),
+            promote_head: None,
+        })
+        .unwrap();
+    let grade = storage
+        .salary_grades
+        .create(&CreateSalaryGradeRequest {
+            code: "E1".to_string(),
+            base_salary: 45000.0,
+            description: None,
+        })
+        .unwrap();
+    let mut request = employee_request("jane@company.com");
+    request.salary_grade_id = Some(grade.clone());
+    let jane = storage
+        .employees
+        .create(&request, Role::Employee, &admin())
+        .unwrap();
+
+    let deleted = storage
+        .departments
+        .delete(&it, DepartmentEmployees::Refuse)
+        .unwrap();
+    assert!(deleted.department.deleted_at.is_some());
+    assert_eq!(storage.departments.list(false).unwrap().len(), 1);
+    assert_eq!(storage.departments.list(true).unwrap().len(), 2);
+    assert!(storage.departments.subdepartments(&ops).unwrap().is_empty());
+    assert!(matches!(
+        storage.departments.delete(&it, DepartmentEmployees::Refuse),
+        Err(ApiError::NotFound(_))
+    ));
+
+    // Nothing new may point at a deleted department or salary grade
+    storage.salary_grades.delete(&grade).unwrap();
+    let jane_now = storage.employees.find_by_id(&jane).unwrap().unwrap();
+    assert_eq!(jane_now.salary_grade_id, Some(grade.clone()));
+    assert!(storage.salary_grades.list(false).unwrap().is_empty());
+    assert!(matches!(
+        storage
+            .employees
+            .assign_salary_grade(&jane, &grade, &admin()),
+        Err(ApiError::Unprocessable(_))
+    ));
+    let mut request = employee_request("joe@company.com");
+    request.department_id = Some(it.clone());
+    assert!(matches!(
+        storage.employees.create(&request, Role::Employee, &admin()),
+        Err(ApiError::Unprocessable(_))
+    ));
+
+    let restored = storage.salary_grades.restore(&grade).unwrap();
+    assert_eq!(restored.deleted_at, None);
+    storage
+        .employees
+        .assign_salary_grade(&jane, &grade, &admin())
+        .unwrap();
+
+    // A restored department goes back below its parent, unless that is deleted by now
+    storage.departments.restore(&it).unwrap();
+    assert_eq!(storage.departments.subdepartments(&ops).unwrap().len(), 1);
+    storage
+        .departments
+        .delete(&it, DepartmentEmployees::Refuse)
+        .unwrap();
+    storage
+        .departments
+        .delete(&ops, DepartmentEmployees::Refuse)
+        .unwrap();
+    let restored = storage.departments.restore(&it).unwrap();
+    assert_eq!(restored.deleted_at, None);
+    assert_eq!(restored.parent_id, None);
+}
+
+#[test]
+fn test_restore_reactivates_employee_and_clears_stale_references() {
+    let storage = Repositories::memory();
+    let sales = department(&storage, "Sales");
+    let boss = storage
+        .employees
+        .create(
+            &employee_request("boss@company.com"),
+            Role::Employee,
+            &admin(),
+        )
+        .unwrap();
+    let mut request = employee_request("jane@company.com");
+    request.department_id = Some(sales.clone());
+    request.manager_id = Some(boss.clone());
+    let jane = storage
+        .employees
+        .create(&request, Role::Employee, &admin())
+        .unwrap();
+
+    storage.employees.soft_delete(&jane, &admin()).unwrap();
+    let restored = storage.employees.restore(&jane, &admin()).unwrap();
+    assert!(restored.employee.active);
+    assert_eq!(restored.employee.deleted_at, None);
+    assert!(restored.nulled_references.is_empty());
+
+    // The manager left in the meantime
+    storage.employees.soft_delete(&jane, &admin()).unwrap();
+    storage.employees.soft_delete(&boss, &admin()).unwrap();
+    let restored = storage.employees.restore(&jane, &admin()).unwrap();
+    assert_eq!(restored.nulled_references, ["manager_id"]);
+    assert_eq!(restored.employee.manager_id, None);
+    assert_eq!(restored.employee.department_id, Some(sales));
+
+    // Reactivating through an update keeps deleted_at in step
+    let mut changes = empty_update();
+    changes.active = Some(true);
+    let boss = storage.employees.update(&boss, &changes, &admin()).unwrap();
+    assert_eq!(boss.deleted_at, None);
+
+    assert!(matches!(
+        storage.employees.restore("missing", &admin()),
+        Err(ApiError::NotFound(_))
+    ));
+}
+
+#[test]
+fn test_personal_data_and_anonymize() {
+    let storage = Repositories::memory();
+    let sales = department(&storage, "Sales");
+    let boss = storage
+        .employees
+        .create(
+            &employee_request("boss@company.com"),
+            Role::Employee,
+            &admin(),
+        )
+        .unwrap();
+    let mut request = employee_request("jane@company.com");
+    request.department_id = Some(sales.clone());
+    request.manager_id = Some(boss.clone());
+    let jane = storage
+        .employees
+        .create(&request, Role::Employee, &admin())
+        .unwrap();
+
+    let data = storage.employees.personal_data(&jane).unwrap();
+    assert_eq!(data.employee.email, "jane@company.com");
+    assert_eq!(data.department_name.as_deref(), Some("Sales"));
+    assert_eq!(data.manager_name.as_deref(), Some("Test Employee"));
+    assert!(data.headed_departments.is_empty());
+
+    // The boss keeps the anonymized row as report, only the names are gone
+    let anonymized = storage.employees.anonymize(&boss).unwrap();
+    assert!(!anonymized.active);
+    assert!(anonymized.deleted_at.is_some());
+    assert!(anonymized.anonymized_at.is_some());
+    assert!(!anonymized.email.contains("boss"));
+    let jane_after = storage.employees.find_by_id(&jane).unwrap().unwrap();
+    assert_eq!(jane_after.manager_id, Some(boss.clone()));
+
+    // Anonymizing again changes nothing, restoring or editing is refused
+    let again = storage.employees.anonymize(&boss).unwrap();
+    assert_eq!(again.anonymized_at, anonymized.anonymized_at);
+    assert!(matches!(
+        storage.employees.restore(&boss, &admin()),
+        Err(ApiError::Conflict(_))
+    ));
+    let mut changes = empty_update();
+    changes.email = Some("boss@company.com".to_string());
+    assert!(matches!(
+        storage.employees.update(&boss, &changes, &admin()),
+        Err(ApiError::Conflict(_))
+    ));
+    assert!(matches!(
+        storage.employees.assign_manager(&boss, &jane, &admin()),
+        Err(ApiError::Conflict(_))
+    ));
+    let grade = storage
+        .salary_grades
+        .create(&CreateSalaryGradeRequest {
+            code: "E1".to_string(),
+            base_salary: 45000.0,
+            description: None,
+        })
+        .unwrap();
+    assert!(matches!(
+        storage
+            .employees
+            .assign_salary_grade(&boss, &grade, &admin()),
+        Err(ApiError::Conflict(_))
+    ));
+
+    // The old email is free again
+    storage
+        .employees
+        .create(
+            &employee_request("boss@company.com"),
+            Role::Employee,
+            &admin(),
+        )
+        .unwrap();
+    assert!(matches!(
+        storage.employees.personal_data("missing"),
+        Err(ApiError::NotFound(_))
+    ));
+}
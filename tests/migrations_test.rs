@@ -0,0 +1,110 @@
+// Tests for the embedded migrations: ordering, file pairing and statement splitting
+
+use backend::migrations::*;
+use backend::seed::{REMOVE_SAMPLE_DATA, SAMPLE_DATA};
+
+#[test]
+fn test_versions_are_increasing_and_match_file_names() {
+    let mut previous = 0;
+    for migration in MIGRATIONS {
+        // No gaps, a version in schema_migrations that isn't listed is a real unknown
+        assert_eq!(migration.version, previous + 1, "{}", migration.name);
+        assert!(
+            migration
+                .name
+                .starts_with(&format!("{:04}_", migration.version)),
+            "{}",
+            migration.name
+        );
+        previous = migration.version;
+    }
+}
+
+#[test]
+fn test_every_migration_has_up_and_down_statements() {
+    for migration in MIGRATIONS {
+        assert!(
+            !split_statements(migration.up).is_empty(),
+            "{}",
+            migration.name
+        );
+        assert!(
+            !split_statements(migration.down).is_empty(),
+            "{}",
+            migration.name
+        );
+    }
+}
+
+#[test]
+fn test_sample_data_is_not_a_migration() {
+    for migration in MIGRATIONS {
+        assert!(!migration.up.contains("INSERT INTO"), "{}", migration.name);
+    }
+
+    assert!(split_statements(SAMPLE_DATA).len() > 1);
+    assert!(!split_statements(REMOVE_SAMPLE_DATA).is_empty());
+    // The admin login is created by `backend seed` with a random password
+    assert!(!SAMPLE_DATA.contains("INSERT INTO users"));
+    assert!(!SAMPLE_DATA.contains("$argon2"));
+}
+
+#[test]
+fn test_schema_no_longer_drops_tables_on_the_way_up() {
+    for migration in MIGRATIONS {
+        assert!(!migration.up.contains("DROP TABLE"), "{}", migration.name);
+    }
+}
+
+#[test]
+fn test_split_statements_skips_comments_and_keeps_multiline_statements() {
+    let sql = "-- create things\nCREATE TABLE a (\n  id INT\n);\n\n-- and fill them\nINSERT INTO a VALUES (1), (2);\nDELETE FROM a";
+
+    assert_eq!(
+        split_statements(sql),
+        vec![
+            "CREATE TABLE a (\n  id INT\n)",
+            "INSERT INTO a VALUES (1), (2)",
+            "DELETE FROM a"
+        ]
+    );
+}
+
+#[test]
+fn test_split_statements_keeps_semicolons_inside_lines() {
+    let sql = "INSERT INTO a (note) VALUES ('x; y');";
+
+    assert_eq!(
+        split_statements(sql),
+        vec!["INSERT INTO a (note) VALUES ('x; y')"]
+    );
+}
+
+#[test]
+fn test_pending_skips_applied_versions() {
+    let versions: Vec<u32> = pending(&[1, 4]).iter().map(|m| m.version).collect();
+
+    assert_eq!(versions[0], 2);
+    assert!(!versions.contains(&1) && !versions.contains(&4));
+    assert_eq!(versions.len(), MIGRATIONS.len() - 2);
+    assert_eq!(pending(&[]).len(), MIGRATIONS.len());
+}
+
+#[test]
+fn test_roll_back_newest_first() {
+    let versions: Vec<u32> = to_roll_back(&[1, 4, 2], 2)
+        .unwrap()
+        .iter()
+        .map(|m| m.version)
+        .collect();
+
+    assert_eq!(versions, vec![4, 2]);
+}
+
+#[test]
+fn test_roll_back_of_unknown_version_fails() {
+    assert!(matches!(
+        to_roll_back(&[1, 99], 1),
+        Err(MigrationError::UnknownVersion(99))
+    ));
+}
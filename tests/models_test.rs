@@ -22,6 +22,8 @@ mod tests {
             id: "test-id".to_string(),
             name: "Engineering".to_string(),
             head_id: Some("head-id".to_string()),
+            parent_id: None,
+            deleted_at: None,
             created_at: None,
             updated_at: None,
         };
@@ -37,6 +39,8 @@ mod tests {
             id: "test-id".to_string(),
             name: "Engineering".to_string(),
             head_id: None,
+            parent_id: None,
+            deleted_at: None,
             created_at: None,
             updated_at: None,
         };
@@ -61,6 +65,7 @@ mod tests {
             code: "E1".to_string(),
             base_salary: 45000.0,
             description: Some("Entry level".to_string()),
+            deleted_at: None,
             created_at: None,
         };
 
@@ -88,10 +93,11 @@ mod tests {
             department_id: Some("dept-1".to_string()),
             salary_grade_id: Some("grade-1".to_string()),
             manager_id: None,
-            role: "Employee".to_string(),
+            role: Role::Employee,
             hire_date: Some("2023-01-15".to_string()),
             active: true,
             deleted_at: None,
+            anonymized_at: None,
             created_at: None,
             updated_at: None,
         };
@@ -99,7 +105,7 @@ mod tests {
         assert_eq!(employee.first_name, "John");
         assert_eq!(employee.last_name, "Doe");
         assert!(employee.active);
-        assert_eq!(employee.role, "Employee");
+        assert_eq!(employee.role, Role::Employee);
     }
 
     #[test]
@@ -119,7 +125,7 @@ mod tests {
 
         assert_eq!(request.first_name, "Jane");
         assert_eq!(request.last_name, "Smith");
-        assert_eq!(request.role, Some("DepartmentHead".to_string()));
+        assert_eq!(request.role, Some(Role::DepartmentHead));
     }
 
     #[test]
@@ -161,10 +167,11 @@ mod tests {
                 department_id: None,
                 salary_grade_id: None,
                 manager_id: None,
-                role: role.to_string(),
+                role: role.parse().unwrap(),
                 hire_date: None,
                 active: true,
                 deleted_at: None,
+                anonymized_at: None,
                 created_at: None,
                 updated_at: None,
             };
@@ -0,0 +1,87 @@
+// Tests for page clamping, sort parsing and the paging headers
+
+use actix_web::test as actix_test;
+use backend::error::ApiError;
+use backend::pagination::*;
+use backend::repository::EmployeeSortField;
+
+#[test]
+fn test_page_request_clamps_values() {
+    assert_eq!(PageRequest::new(None, None), PageRequest::default());
+    assert_eq!(
+        PageRequest::new(Some(0), Some(1000)),
+        PageRequest {
+            page: 1,
+            per_page: MAX_PER_PAGE
+        }
+    );
+    assert_eq!(PageRequest::new(Some(3), Some(10)).offset(), 20);
+}
+
+#[test]
+fn test_slice_reports_total_of_all_pages() {
+    let page = PageRequest::new(Some(2), Some(2)).slice(vec![1, 2, 3, 4, 5]);
+    assert_eq!(page.items, vec![3, 4]);
+    assert_eq!(page.total, 5);
+
+    assert!(
+        PageRequest::new(Some(9), Some(2))
+            .slice(vec![1])
+            .items
+            .is_empty()
+    );
+    assert_eq!(PageRequest::new(None, Some(2)).last_page(0), 1);
+}
+
+#[test]
+fn test_parse_sort_reads_directions() {
+    let keys = parse_sort::<EmployeeSortField>("last_name, -hire_date,").unwrap();
+    assert_eq!(
+        keys,
+        vec![
+            SortKey {
+                field: EmployeeSortField::LastName,
+                descending: false
+            },
+            SortKey {
+                field: EmployeeSortField::HireDate,
+                descending: true
+            },
+        ]
+    );
+
+    match parse_sort::<EmployeeSortField>("last_name,password_hash") {
+        Err(ApiError::Validation(errors)) => {
+            assert_eq!(errors[0].field, "sort");
+            assert!(errors[0].message.contains("password_hash"));
+        }
+        other => panic!("expected validation error, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_paged_response_sets_headers_and_links() {
+    let req = actix_test::TestRequest::get()
+        .uri("/api/employees?role=Employee&page=2&per_page=2")
+        .to_http_request();
+    let page = PageRequest::new(Some(2), Some(2));
+
+    let resp = paged_response(
+        &req,
+        page,
+        Page {
+            items: vec!["c", "d"],
+            total: 5,
+        },
+    );
+
+    let header = |name: &str| resp.headers().get(name).unwrap().to_str().unwrap();
+    assert_eq!(header(TOTAL_COUNT_HEADER), "5");
+    assert_eq!(header(PAGE_HEADER), "2");
+    assert_eq!(header(PER_PAGE_HEADER), "2");
+    let link = header("Link");
+    assert!(link.contains("</api/employees?role=Employee&per_page=2&page=1>; rel=\"first\""));
+    assert!(link.contains("page=3>; rel=\"last\""));
+    assert!(link.contains("page=1>; rel=\"prev\""));
+    assert!(link.contains("page=3>; rel=\"next\""));
+}
@@ -0,0 +1,194 @@
+// Tests for role based authorization: policy extractor and department scope rules
+
+mod common;
+
+use actix_web::http::StatusCode;
+use actix_web::test as actix_test;
+use actix_web::{App, HttpResponse, web};
+use backend::auth::{AdminOnly, AuthenticatedUser, DepartmentManager, RequireRole};
+use backend::error::ApiError;
+use backend::handlers::delete_department;
+use backend::models::Role;
+use common::as_user;
+
+const ENGINEERING: &str = "650e8400-e29b-41d4-a716-446655440001";
+const HR: &str = "650e8400-e29b-41d4-a716-446655440002";
+const BOB_ID: &str = "750e8400-e29b-41d4-a716-446655440002";
+
+fn user(role: Role, department_id: Option<&str>) -> AuthenticatedUser {
+    AuthenticatedUser {
+        employee_id: BOB_ID.to_string(),
+        role,
+        department_id: department_id.map(str::to_string),
+    }
+}
+
+fn is_forbidden(result: Result<(), ApiError>) -> bool {
+    matches!(result, Err(ApiError::Forbidden(_)))
+}
+
+async fn admin_only(caller: RequireRole<AdminOnly>) -> HttpResponse {
+    HttpResponse::Ok().body(caller.employee_id.clone())
+}
+
+async fn managers(_caller: RequireRole<DepartmentManager>) -> HttpResponse {
+    HttpResponse::Ok().finish()
+}
+
+#[actix_web::test]
+async fn test_require_role_allows_listed_roles() {
+    let app = actix_test::init_service(App::new().route("/admin", web::get().to(admin_only))).await;
+
+    let req = as_user(
+        actix_test::TestRequest::get().uri("/admin").to_request(),
+        BOB_ID,
+        Role::Admin,
+        None,
+    );
+    let resp = actix_test::call_service(&app, req).await;
+
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(actix_test::read_body(resp).await, BOB_ID.as_bytes());
+}
+
+#[actix_web::test]
+async fn test_require_role_rejects_other_roles_with_reason() {
+    let app = actix_test::init_service(App::new().route("/admin", web::get().to(admin_only))).await;
+
+    let req = as_user(
+        actix_test::TestRequest::get().uri("/admin").to_request(),
+        BOB_ID,
+        Role::DepartmentHead,
+        Some(ENGINEERING),
+    );
+    let resp = actix_test::call_service(&app, req).await;
+
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+    let body: serde_json::Value = actix_test::read_body_json(resp).await;
+    assert_eq!(body["code"], "forbidden");
+    assert_eq!(body["detail"], "Only admins may perform this action");
+}
+
+#[actix_web::test]
+async fn test_require_role_without_identity_is_unauthorized() {
+    let app = actix_test::init_service(App::new().route("/mgr", web::get().to(managers))).await;
+
+    let req = actix_test::TestRequest::get().uri("/mgr").to_request();
+    let resp = actix_test::call_service(&app, req).await;
+
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[actix_web::test]
+async fn test_department_manager_policy() {
+    let app = actix_test::init_service(App::new().route("/mgr", web::get().to(managers))).await;
+
+    for (role, status) in [
+        (Role::Admin, StatusCode::OK),
+        (Role::DepartmentHead, StatusCode::OK),
+        (Role::DeputyHead, StatusCode::OK),
+        (Role::Employee, StatusCode::FORBIDDEN),
+    ] {
+        let req = as_user(
+            actix_test::TestRequest::get().uri("/mgr").to_request(),
+            BOB_ID,
+            role,
+            Some(ENGINEERING),
+        );
+        assert_eq!(
+            actix_test::call_service(&app, req).await.status(),
+            status,
+            "{}",
+            role
+        );
+    }
+}
+
+#[test]
+fn test_department_scope() {
+    let head = user(Role::DepartmentHead, Some(ENGINEERING));
+    let deputy = user(Role::DeputyHead, Some(HR));
+
+    assert!(head.ensure_department(Some(ENGINEERING)).is_ok());
+    assert!(is_forbidden(head.ensure_department(Some(HR))));
+    assert!(is_forbidden(head.ensure_department(None)));
+    assert!(deputy.ensure_department(Some(HR)).is_ok());
+    assert!(user(Role::Admin, None).ensure_department(Some(HR)).is_ok());
+}
+
+#[test]
+fn test_head_without_department_manages_nothing() {
+    let head = user(Role::DepartmentHead, None);
+
+    assert!(is_forbidden(head.ensure_department(None)));
+    assert!(is_forbidden(head.ensure_department(Some(ENGINEERING))));
+}
+
+#[test]
+fn test_only_admins_grant_admin_and_head_roles() {
+    let head = user(Role::DepartmentHead, Some(ENGINEERING));
+
+    assert!(head.ensure_can_grant(Role::Employee).is_ok());
+    assert!(head.ensure_can_grant(Role::DeputyHead).is_ok());
+    assert!(is_forbidden(head.ensure_can_grant(Role::DepartmentHead)));
+    assert!(is_forbidden(head.ensure_can_grant(Role::Admin)));
+    assert!(
+        user(Role::Admin, None)
+            .ensure_can_grant(Role::Admin)
+            .is_ok()
+    );
+}
+
+#[test]
+fn test_only_higher_roles_change_lower_ones() {
+    let head = user(Role::DepartmentHead, Some(ENGINEERING));
+    let deputy = user(Role::DeputyHead, Some(ENGINEERING));
+
+    assert!(head.ensure_outranks(Role::DeputyHead).is_ok());
+    assert!(is_forbidden(head.ensure_outranks(Role::DepartmentHead)));
+    assert!(deputy.ensure_outranks(Role::Employee).is_ok());
+    assert!(is_forbidden(deputy.ensure_outranks(Role::DeputyHead)));
+    assert!(is_forbidden(deputy.ensure_outranks(Role::DepartmentHead)));
+    assert!(user(Role::Admin, None).ensure_outranks(Role::Admin).is_ok());
+}
+
+#[test]
+fn test_employee_cannot_read_other_records() {
+    let employee = user(Role::Employee, Some(ENGINEERING));
+
+    match employee.ensure_can_read_employee(common::ADMIN_ID) {
+        Err(ApiError::Forbidden(reason)) => {
+            assert_eq!(reason, "Employees can only view their own record")
+        }
+        other => panic!("expected forbidden, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_employee_can_read_own_record() {
+    let employee = user(Role::Employee, Some(ENGINEERING));
+
+    assert!(employee.ensure_can_read_employee(BOB_ID).is_ok());
+    assert!(
+        user(Role::DeputyHead, Some(HR))
+            .ensure_can_read_employee(common::ADMIN_ID)
+            .is_ok()
+    );
+}
+
+#[actix_web::test]
+async fn test_department_head_cannot_delete_departments() {
+    let app = actix_test::init_service(App::new().service(delete_department)).await;
+
+    let req = as_user(
+        actix_test::TestRequest::delete()
+            .uri(&format!("/api/departments/{}", ENGINEERING))
+            .to_request(),
+        BOB_ID,
+        Role::DepartmentHead,
+        Some(ENGINEERING),
+    );
+    let resp = actix_test::call_service(&app, req).await;
+
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+}
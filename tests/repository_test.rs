@@ -0,0 +1,411 @@
+// Tests for handler logic on top of stub repositories, no database needed
+
+mod common;
+
+use actix_web::http::StatusCode;
+use actix_web::test as actix_test;
+use actix_web::{App, web};
+use backend::auth::AuthenticatedUser;
+use backend::error::ApiError;
+use backend::handlers::{
+    delete_employee, get_departments, get_employee_by_id, get_salary_grade_by_id, update_employee,
+};
+use backend::hierarchy::OrgChartRow;
+use backend::models::*;
+use backend::pagination::{Page, PageRequest, SortKey};
+use backend::repository::{
+    DepartmentEmployees, DepartmentRepository, EmployeeFilter, EmployeeRepository,
+    EmployeeSortField, PurgeMode, PurgedReferences, SalaryGradeRepository,
+};
+use common::{as_admin, as_user};
+use std::sync::{Arc, Mutex};
+
+const ENGINEERING: &str = "650e8400-e29b-41d4-a716-446655440001";
+const HR: &str = "650e8400-e29b-41d4-a716-446655440002";
+const BOB_ID: &str = "750e8400-e29b-41d4-a716-446655440002";
+const IRIS_ID: &str = "750e8400-e29b-41d4-a716-446655440009";
+const MIA_ID: &str = "750e8400-e29b-41d4-a716-446655440013";
+
+fn employee(id: &str, department_id: &str, role: Role) -> Employee {
+    Employee {
+        id: id.to_string(),
+        first_name: "Test".to_string(),
+        last_name: "Employee".to_string(),
+        email: format!("{}@company.com", id),
+        department_id: Some(department_id.to_string()),
+        salary_grade_id: None,
+        manager_id: None,
+        role,
+        hire_date: None,
+        active: true,
+        deleted_at: None,
+        anonymized_at: None,
+        created_at: None,
+        updated_at: None,
+    }
+}
+
+// Serves a fixed set of employees and records which ones were deleted
+#[derive(Default)]
+struct StubEmployees {
+    employees: Vec<Employee>,
+    deleted: Mutex<Vec<String>>,
+}
+
+impl StubEmployees {
+    fn existing(&self, id: &str) -> Result<Employee, ApiError> {
+        self.find_by_id(id)?
+            .ok_or_else(|| ApiError::NotFound("Employee not found".to_string()))
+    }
+}
+
+impl EmployeeRepository for StubEmployees {
+    fn list_page(
+        &self,
+        _filter: &EmployeeFilter,
+        _sort: &[SortKey<EmployeeSortField>],
+        page: PageRequest,
+    ) -> Result<Page<Employee>, ApiError> {
+        Ok(page.slice(self.employees.clone()))
+    }
+
+    fn search(
+        &self,
+        _terms: &[String],
+        _include_inactive: bool,
+        page: PageRequest,
+    ) -> Result<Page<Employee>, ApiError> {
+        Ok(page.slice(vec![]))
+    }
+
+    fn org_chart(&self, _root_id: Option<&str>) -> Result<Vec<OrgChartRow>, ApiError> {
+        Ok(vec![])
+    }
+
+    fn management_chain(&self, _id: &str) -> Result<Vec<OrgChartRow>, ApiError> {
+        Ok(vec![])
+    }
+
+    fn list_by_department(
+        &self,
+        department_id: &str,
+        _recursive: bool,
+    ) -> Result<Vec<Employee>, ApiError> {
+        Ok(self
+            .employees
+            .iter()
+            .filter(|e| e.department_id.as_deref() == Some(department_id))
+            .cloned()
+            .collect())
+    }
+
+    fn find_by_id(&self, id: &str) -> Result<Option<Employee>, ApiError> {
+        Ok(self.employees.iter().find(|e| e.id == id).cloned())
+    }
+
+    fn create(
+        &self,
+        _employee: &CreateEmployeeRequest,
+        _role: Role,
+        _caller: &AuthenticatedUser,
+    ) -> Result<String, ApiError> {
+        Ok("new-id".to_string())
+    }
+
+    fn update(
+        &self,
+        _id: &str,
+        _changes: &UpdateEmployeeRequest,
+        _caller: &AuthenticatedUser,
+    ) -> Result<Employee, ApiError> {
+        Err(ApiError::BadRequest("No fields to update".to_string()))
+    }
+
+    // Checks the caller like the real repositories do before anything is changed
+    fn soft_delete(&self, id: &str, caller: &AuthenticatedUser) -> Result<Employee, ApiError> {
+        let employee = self.existing(id)?;
+        caller.ensure_can_change(&employee)?;
+        self.deleted.lock().unwrap().push(id.to_string());
+        Ok(employee)
+    }
+
+    fn restore(&self, id: &str, _caller: &AuthenticatedUser) -> Result<EmployeeRestore, ApiError> {
+        Ok(EmployeeRestore {
+            employee: self.existing(id)?,
+            nulled_references: vec![],
+        })
+    }
+
+    fn personal_data(&self, id: &str) -> Result<PersonalData, ApiError> {
+        Ok(PersonalData {
+            employee: self.existing(id)?,
+            department_name: None,
+            manager_name: None,
+            salary_grade_code: None,
+            headed_departments: vec![],
+            account: None,
+            api_keys: vec![],
+        })
+    }
+
+    fn anonymize(&self, id: &str) -> Result<Employee, ApiError> {
+        self.existing(id)
+    }
+
+    fn expired(&self, _deleted_before: &str, _mode: PurgeMode) -> Result<Vec<String>, ApiError> {
+        Ok(vec![])
+    }
+
+    fn purge(
+        &self,
+        _id: &str,
+        _mode: PurgeMode,
+        _deleted_before: &str,
+    ) -> Result<Option<PurgedReferences>, ApiError> {
+        Ok(None)
+    }
+
+    fn assign_manager(
+        &self,
+        id: &str,
+        _manager_id: &str,
+        _caller: &AuthenticatedUser,
+    ) -> Result<Employee, ApiError> {
+        self.existing(id)
+    }
+
+    fn assign_salary_grade(
+        &self,
+        id: &str,
+        _salary_grade_id: &str,
+        _caller: &AuthenticatedUser,
+    ) -> Result<Employee, ApiError> {
+        self.existing(id)
+    }
+}
+
+struct StubDepartments;
+
+impl DepartmentRepository for StubDepartments {
+    fn list(&self, _include_deleted: bool) -> Result<Vec<Department>, ApiError> {
+        Ok(vec![Department {
+            id: ENGINEERING.to_string(),
+            name: "Engineering".to_string(),
+            head_id: Some(BOB_ID.to_string()),
+            parent_id: None,
+            deleted_at: None,
+            created_at: None,
+            updated_at: None,
+        }])
+    }
+
+    fn find_by_id(&self, _id: &str) -> Result<Option<Department>, ApiError> {
+        Ok(None)
+    }
+
+    fn subdepartments(&self, _id: &str) -> Result<Vec<Department>, ApiError> {
+        Ok(vec![])
+    }
+
+    fn create(&self, _department: &CreateDepartmentRequest) -> Result<String, ApiError> {
+        Ok("new-id".to_string())
+    }
+
+    fn update(
+        &self,
+        _id: &str,
+        _changes: &UpdateDepartmentRequest,
+    ) -> Result<Department, ApiError> {
+        Err(ApiError::NotFound("Department not found".to_string()))
+    }
+
+    fn delete(
+        &self,
+        _id: &str,
+        _employees: DepartmentEmployees,
+    ) -> Result<DepartmentDeletion, ApiError> {
+        Err(ApiError::NotFound("Department not found".to_string()))
+    }
+
+    fn restore(&self, _id: &str) -> Result<Department, ApiError> {
+        Err(ApiError::NotFound("Department not found".to_string()))
+    }
+}
+
+struct EmptySalaryGrades;
+
+impl SalaryGradeRepository for EmptySalaryGrades {
+    fn list(&self, _include_deleted: bool) -> Result<Vec<SalaryGrade>, ApiError> {
+        Ok(vec![])
+    }
+
+    fn find_by_id(&self, _id: &str) -> Result<Option<SalaryGrade>, ApiError> {
+        Ok(None)
+    }
+
+    fn create(&self, _grade: &CreateSalaryGradeRequest) -> Result<String, ApiError> {
+        Ok("new-id".to_string())
+    }
+
+    fn update(
+        &self,
+        _id: &str,
+        _changes: &UpdateSalaryGradeRequest,
+    ) -> Result<SalaryGrade, ApiError> {
+        Err(ApiError::NotFound("Salary grade not found".to_string()))
+    }
+
+    fn delete(&self, _id: &str) -> Result<SalaryGrade, ApiError> {
+        Err(ApiError::NotFound("Salary grade not found".to_string()))
+    }
+
+    fn restore(&self, _id: &str) -> Result<SalaryGrade, ApiError> {
+        Err(ApiError::NotFound("Salary grade not found".to_string()))
+    }
+}
+
+fn stub_employees() -> Arc<StubEmployees> {
+    Arc::new(StubEmployees {
+        employees: vec![
+            employee(common::ADMIN_ID, ENGINEERING, Role::Admin),
+            employee(IRIS_ID, ENGINEERING, Role::Employee),
+            employee(MIA_ID, HR, Role::Employee),
+        ],
+        ..Default::default()
+    })
+}
+
+fn employees_data(stub: &Arc<StubEmployees>) -> web::Data<dyn EmployeeRepository> {
+    let repository: Arc<dyn EmployeeRepository> = stub.clone();
+    web::Data::from(repository)
+}
+
+#[actix_web::test]
+async fn test_get_employee_by_id_from_repository() {
+    let app = actix_test::init_service(
+        App::new()
+            .app_data(employees_data(&stub_employees()))
+            .service(get_employee_by_id),
+    )
+    .await;
+
+    let found = as_admin(
+        actix_test::TestRequest::get()
+            .uri(&format!("/api/employees/{}", IRIS_ID))
+            .to_request(),
+    );
+    let resp = actix_test::call_service(&app, found).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body: serde_json::Value = actix_test::read_body_json(resp).await;
+    assert_eq!(body["id"], IRIS_ID);
+
+    let missing = as_admin(
+        actix_test::TestRequest::get()
+            .uri("/api/employees/unknown")
+            .to_request(),
+    );
+    let resp = actix_test::call_service(&app, missing).await;
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+}
+
+#[actix_web::test]
+async fn test_head_deletes_employee_of_own_department() {
+    let stub = stub_employees();
+    let app = actix_test::init_service(
+        App::new()
+            .app_data(employees_data(&stub))
+            .service(delete_employee),
+    )
+    .await;
+
+    let req = as_user(
+        actix_test::TestRequest::delete()
+            .uri(&format!("/api/employees/{}", IRIS_ID))
+            .to_request(),
+        BOB_ID,
+        Role::DepartmentHead,
+        Some(ENGINEERING),
+    );
+    let resp = actix_test::call_service(&app, req).await;
+
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(*stub.deleted.lock().unwrap(), vec![IRIS_ID.to_string()]);
+}
+
+#[actix_web::test]
+async fn test_head_cannot_delete_outside_department_or_admins() {
+    let stub = stub_employees();
+    let app = actix_test::init_service(
+        App::new()
+            .app_data(employees_data(&stub))
+            .service(delete_employee),
+    )
+    .await;
+
+    for target in [MIA_ID, common::ADMIN_ID] {
+        let req = as_user(
+            actix_test::TestRequest::delete()
+                .uri(&format!("/api/employees/{}", target))
+                .to_request(),
+            BOB_ID,
+            Role::DepartmentHead,
+            Some(ENGINEERING),
+        );
+        let resp = actix_test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::FORBIDDEN, "{}", target);
+    }
+    assert!(stub.deleted.lock().unwrap().is_empty());
+}
+
+#[actix_web::test]
+async fn test_repository_errors_become_problem_responses() {
+    let app = actix_test::init_service(
+        App::new()
+            .app_data(employees_data(&stub_employees()))
+            .service(update_employee),
+    )
+    .await;
+
+    let req = as_admin(
+        actix_test::TestRequest::put()
+            .uri(&format!("/api/employees/{}", IRIS_ID))
+            .set_json(serde_json::json!({}))
+            .to_request(),
+    );
+    let resp = actix_test::call_service(&app, req).await;
+
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    let body: serde_json::Value = actix_test::read_body_json(resp).await;
+    assert_eq!(body["detail"], "No fields to update");
+}
+
+#[actix_web::test]
+async fn test_department_and_salary_grade_handlers_use_repositories() {
+    let departments: Arc<dyn DepartmentRepository> = Arc::new(StubDepartments);
+    let grades: Arc<dyn SalaryGradeRepository> = Arc::new(EmptySalaryGrades);
+    let app = actix_test::init_service(
+        App::new()
+            .app_data(web::Data::from(departments))
+            .app_data(web::Data::from(grades))
+            .service(get_departments)
+            .service(get_salary_grade_by_id),
+    )
+    .await;
+
+    let req = as_admin(
+        actix_test::TestRequest::get()
+            .uri("/api/departments")
+            .to_request(),
+    );
+    let body: serde_json::Value =
+        actix_test::read_body_json(actix_test::call_service(&app, req).await).await;
+    assert_eq!(body[0]["name"], "Engineering");
+
+    let req = as_admin(
+        actix_test::TestRequest::get()
+            .uri("/api/salary-grades/unknown")
+            .to_request(),
+    );
+    let resp = actix_test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+}
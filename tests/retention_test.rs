@@ -0,0 +1,158 @@
+// Tests for the retention purge of long deleted employees, run against the in-memory backend
+
+use backend::auth::AuthenticatedUser;
+use backend::models::*;
+use backend::repository::{PurgeMode, Repositories};
+use backend::retention::*;
+use chrono::{DateTime, Duration, Utc};
+
+fn admin() -> AuthenticatedUser {
+    AuthenticatedUser {
+        employee_id: "admin".to_string(),
+        role: Role::Admin,
+        department_id: None,
+    }
+}
+
+fn employee(storage: &Repositories, email: &str, manager_id: Option<&str>) -> String {
+    let request = CreateEmployeeRequest {
+        first_name: "Test".to_string(),
+        last_name: "Employee".to_string(),
+        email: email.to_string(),
+        department_id: None,
+        salary_grade_id: None,
+        manager_id: manager_id.map(str::to_string),
+        role: None,
+        hire_date: None,
+    };
+    storage
+        .employees
+        .create(&request, Role::Employee, &admin())
+        .unwrap()
+}
+
+// A point in time from which every employee deleted so far is past its retention period
+fn years_later(years: i64) -> DateTime<Utc> {
+    Utc::now() + Duration::days(years * 366 + 1)
+}
+
+#[test]
+fn test_cutoff_and_mode_parsing() {
+    let policy = RetentionPolicy {
+        retention_years: 10,
+        mode: PurgeMode::Anonymize,
+    };
+    let now = DateTime::parse_from_rfc3339("2026-03-01T12:00:00Z")
+        .unwrap()
+        .with_timezone(&Utc);
+    assert_eq!(policy.cutoff(now), "2016-03-01T12:00:00Z");
+    assert_eq!(
+        RetentionPolicy::default().retention_years,
+        DEFAULT_RETENTION_YEARS
+    );
+
+    assert_eq!("delete".parse(), Ok(PurgeMode::Delete));
+    assert_eq!(" Anonymize ".parse(), Ok(PurgeMode::Anonymize));
+    assert!("shred".parse::<PurgeMode>().is_err());
+}
+
+#[test]
+fn test_purge_anonymizes_expired_employees_and_moves_their_references() {
+    let storage = Repositories::memory();
+    let boss = employee(&storage, "boss@company.com", None);
+    let mid = employee(&storage, "mid@company.com", Some(&boss));
+    let report = employee(&storage, "report@company.com", Some(&mid));
+    let sales = storage
+        .departments
+        .create(&CreateDepartmentRequest {
+            name: "Sales".to_string(),
+            head_id: Some(mid.clone()),
+            parent_id: None,
+            promote_head: Some(true),
+        })
+        .unwrap();
+    storage.employees.soft_delete(&mid, &admin()).unwrap();
+    // A deleted employee heads nothing anymore
+    let sales_after = storage.departments.find_by_id(&sales).unwrap().unwrap();
+    assert_eq!(sales_after.head_id, None);
+
+    let policy = RetentionPolicy {
+        retention_years: 10,
+        mode: PurgeMode::Anonymize,
+    };
+
+    // Nothing is due within the retention period
+    let summary = purge_expired(storage.employees.get_ref(), &policy, Utc::now()).unwrap();
+    assert_eq!(summary.purged, 0);
+
+    let summary = purge_expired(storage.employees.get_ref(), &policy, years_later(10)).unwrap();
+    assert_eq!(summary.purged, 1);
+    assert_eq!(summary.failed, 0);
+    assert_eq!(summary.reassigned_reports, 1);
+    assert_eq!(summary.cleared_heads, 0);
+    assert!(summary.to_string().contains("1 employees"));
+
+    let mid_after = storage.employees.find_by_id(&mid).unwrap().unwrap();
+    assert!(mid_after.anonymized_at.is_some());
+    assert!(!mid_after.email.contains("mid"));
+    let report_after = storage.employees.find_by_id(&report).unwrap().unwrap();
+    assert_eq!(report_after.manager_id, Some(boss));
+
+    // Anonymized employees are not picked up again
+    let summary = purge_expired(storage.employees.get_ref(), &policy, years_later(10)).unwrap();
+    assert_eq!(summary.purged, 0);
+}
+
+#[test]
+fn test_purge_deletes_expired_employees() {
+    let storage = Repositories::memory();
+    let boss = employee(&storage, "boss@company.com", None);
+    let report = employee(&storage, "report@company.com", Some(&boss));
+    storage.employees.soft_delete(&boss, &admin()).unwrap();
+
+    let policy = RetentionPolicy {
+        retention_years: 1,
+        mode: PurgeMode::Delete,
+    };
+    let summary = purge_expired(storage.employees.get_ref(), &policy, years_later(1)).unwrap();
+    assert_eq!(summary.purged, 1);
+    assert_eq!(summary.unassigned_reports, 1);
+
+    assert!(storage.employees.find_by_id(&boss).unwrap().is_none());
+    let report_after = storage.employees.find_by_id(&report).unwrap().unwrap();
+    assert_eq!(report_after.manager_id, None);
+}
+
+#[test]
+fn test_purge_skips_employees_restored_since_they_were_listed() {
+    let storage = Repositories::memory();
+    let jane = employee(&storage, "jane@company.com", None);
+    storage.employees.soft_delete(&jane, &admin()).unwrap();
+
+    let policy = RetentionPolicy {
+        retention_years: 1,
+        mode: PurgeMode::Delete,
+    };
+    let cutoff = policy.cutoff(years_later(1));
+    assert_eq!(
+        storage.employees.expired(&cutoff, policy.mode).unwrap(),
+        vec![jane.clone()]
+    );
+
+    // Restored between listing and purging, so the purge leaves it alone
+    storage.employees.restore(&jane, &admin()).unwrap();
+    assert_eq!(
+        storage
+            .employees
+            .purge(&jane, policy.mode, &cutoff)
+            .unwrap(),
+        None
+    );
+    assert!(storage.employees.find_by_id(&jane).unwrap().unwrap().active);
+
+    // Deleted again, but within the retention period
+    storage.employees.soft_delete(&jane, &admin()).unwrap();
+    let summary = purge_expired(storage.employees.get_ref(), &policy, Utc::now()).unwrap();
+    assert_eq!(summary.purged, 0);
+    assert!(storage.employees.find_by_id(&jane).unwrap().is_some());
+}
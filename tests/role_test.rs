@@ -0,0 +1,61 @@
+// Tests for the Role enum and its serde / MySQL conversions
+
+use backend::models::*;
+use mysql::Value;
+use mysql::prelude::FromValue;
+
+#[test]
+fn test_role_serializes_as_enum_name() {
+    for role in Role::ALL {
+        let json = serde_json::to_string(&role).unwrap();
+        assert_eq!(json, format!("\"{}\"", role.as_str()));
+
+        let back: Role = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, role);
+    }
+}
+
+#[test]
+fn test_default_role_is_employee() {
+    assert_eq!(Role::default(), Role::Employee);
+}
+
+#[test]
+fn test_unknown_role_rejected_at_deserialization() {
+    let json = r#"{
+        "first_name":"Jane",
+        "last_name":"Smith",
+        "email":"jane.smith@example.com",
+        "role":"Boss"
+    }"#;
+
+    let err = serde_json::from_str::<CreateEmployeeRequest>(json)
+        .err()
+        .expect("role Boss must be rejected");
+
+    assert!(err.to_string().contains("unknown variant `Boss`"));
+}
+
+#[test]
+fn test_role_names_are_case_sensitive() {
+    assert!("admin".parse::<Role>().is_err());
+    assert_eq!("DeputyHead".parse::<Role>().unwrap(), Role::DeputyHead);
+}
+
+#[test]
+fn test_role_to_mysql_value() {
+    let value: Value = Role::DepartmentHead.into();
+
+    assert_eq!(value, Value::Bytes(b"DepartmentHead".to_vec()));
+}
+
+#[test]
+fn test_role_from_mysql_value() {
+    let role = Role::from_value_opt(Value::Bytes(b"Admin".to_vec())).unwrap();
+    let nullable: Option<Role> = Option::from_value(Value::NULL);
+
+    assert_eq!(role, Role::Admin);
+    assert_eq!(nullable, None);
+    assert!(Role::from_value_opt(Value::Bytes(b"Intern".to_vec())).is_err());
+    assert!(Role::from_value_opt(Value::Int(1)).is_err());
+}
@@ -12,10 +12,11 @@ fn test_employee_model_serialization() {
         department_id: Some("dept-456".to_string()),
         salary_grade_id: Some("grade-789".to_string()),
         manager_id: Some("mgr-101".to_string()),
-        role: "Employee".to_string(),
+        role: Role::Employee,
         hire_date: Some("2024-06-15".to_string()),
         active: true,
         deleted_at: None,
+        anonymized_at: None,
         created_at: Some("2024-06-15 10:00:00".to_string()),
         updated_at: Some("2024-06-15 10:00:00".to_string()),
     };
@@ -47,15 +48,16 @@ fn test_employee_roles() {
             department_id: None,
             salary_grade_id: None,
             manager_id: None,
-            role: role.to_string(),
+            role: role.parse().unwrap(),
             hire_date: None,
             active: true,
             deleted_at: None,
+            anonymized_at: None,
             created_at: None,
             updated_at: None,
         };
 
-        assert_eq!(employee.role, role);
+        assert_eq!(employee.role.as_str(), role);
     }
 }
 
@@ -69,10 +71,11 @@ fn test_employee_active_inactive_states() {
         department_id: None,
         salary_grade_id: None,
         manager_id: None,
-        role: "Employee".to_string(),
+        role: Role::Employee,
         hire_date: None,
         active: true,
         deleted_at: None,
+        anonymized_at: None,
         created_at: None,
         updated_at: None,
     };
@@ -85,10 +88,11 @@ fn test_employee_active_inactive_states() {
         department_id: None,
         salary_grade_id: None,
         manager_id: None,
-        role: "Employee".to_string(),
+        role: Role::Employee,
         hire_date: None,
         active: false,
         deleted_at: Some("2024-12-31 23:59:59".to_string()),
+        anonymized_at: None,
         created_at: None,
         updated_at: None,
     };
@@ -104,6 +108,8 @@ fn test_department_model_serialization() {
         id: "dept-123".to_string(),
         name: "Human Resources".to_string(),
         head_id: Some("mgr-456".to_string()),
+        parent_id: None,
+        deleted_at: None,
         created_at: Some("2024-01-01 00:00:00".to_string()),
         updated_at: Some("2024-01-01 00:00:00".to_string()),
     };
@@ -123,6 +129,8 @@ fn test_department_with_manager() {
         id: "dept-001".to_string(),
         name: "Sales".to_string(),
         head_id: Some("manager-001".to_string()),
+        parent_id: None,
+        deleted_at: None,
         created_at: None,
         updated_at: None,
     };
@@ -137,6 +145,8 @@ fn test_department_without_manager() {
         id: "dept-002".to_string(),
         name: "Marketing".to_string(),
         head_id: None,
+        parent_id: None,
+        deleted_at: None,
         created_at: None,
         updated_at: None,
     };
@@ -149,6 +159,8 @@ fn test_create_department_minimal_fields() {
     let minimal_dept = CreateDepartmentRequest {
         name: "New Department".to_string(),
         head_id: None,
+        parent_id: None,
+        promote_head: None,
     };
 
     assert_eq!(minimal_dept.name, "New Department");
@@ -160,6 +172,8 @@ fn test_update_department_all_fields() {
     let full_update = UpdateDepartmentRequest {
         name: Some("Updated Name".to_string()),
         head_id: Some("new-manager".to_string()),
+        parent_id: None,
+        promote_head: None,
     };
 
     assert!(full_update.name.is_some());
@@ -173,6 +187,7 @@ fn test_salary_grade_model_serialization() {
         code: "MGR".to_string(),
         base_salary: 125000.0,
         description: Some("Manager grade".to_string()),
+        deleted_at: None,
         created_at: Some("2024-01-01 00:00:00".to_string()),
     };
 
@@ -194,6 +209,7 @@ fn test_salary_grade_different_levels() {
         code: "ENTRY".to_string(),
         base_salary: 50000.0,
         description: Some("Entry level".to_string()),
+        deleted_at: None,
         created_at: None,
     };
 
@@ -202,6 +218,7 @@ fn test_salary_grade_different_levels() {
         code: "SENIOR".to_string(),
         base_salary: 120000.0,
         description: Some("Senior level".to_string()),
+        deleted_at: None,
         created_at: None,
     };
 
@@ -216,6 +233,7 @@ fn test_salary_grade_with_description() {
         code: "SPECIALIST".to_string(),
         base_salary: 95000.0,
         description: Some("Technical specialist position".to_string()),
+        deleted_at: None,
         created_at: None,
     };
 
@@ -230,6 +248,7 @@ fn test_salary_grade_without_description() {
         code: "BASIC".to_string(),
         base_salary: 45000.0,
         description: None,
+        deleted_at: None,
         created_at: None,
     };
 
@@ -293,6 +312,7 @@ fn test_salary_grade_codes_uniqueness() {
         code: "CODE1".to_string(),
         base_salary: 60000.0,
         description: None,
+        deleted_at: None,
         created_at: None,
     };
 
@@ -301,6 +321,7 @@ fn test_salary_grade_codes_uniqueness() {
         code: "CODE2".to_string(),
         base_salary: 60000.0,
         description: None,
+        deleted_at: None,
         created_at: None,
     };
 
@@ -325,6 +346,7 @@ fn test_user_model_complete() {
         id: 123,
         name: "John Doe".to_string(),
         email: "john.doe@example.com".to_string(),
+        ..Default::default()
     };
 
     assert_eq!(user.id, 123);
@@ -337,6 +359,8 @@ fn test_create_user_request_validation() {
     let valid_request = CreateUserRequest {
         name: "Jane Smith".to_string(),
         email: "jane.smith@example.com".to_string(),
+        employee_id: None,
+        password: None,
     };
 
     assert!(!valid_request.name.is_empty());
@@ -349,6 +373,8 @@ fn test_create_user_request_serialization() {
     let request = CreateUserRequest {
         name: "Test User".to_string(),
         email: "test@example.com".to_string(),
+        employee_id: None,
+        password: None,
     };
 
     let json = serde_json::to_string(&request).unwrap();
@@ -366,6 +392,7 @@ fn test_user_serialization() {
         id: 789,
         name: "Alice Brown".to_string(),
         email: "alice@example.com".to_string(),
+        ..Default::default()
     };
 
     let json = serde_json::to_string(&user).unwrap();
@@ -399,16 +426,19 @@ fn test_multiple_users() {
             id: 1,
             name: "User One".to_string(),
             email: "user1@example.com".to_string(),
+            ..Default::default()
         },
         User {
             id: 2,
             name: "User Two".to_string(),
             email: "user2@example.com".to_string(),
+            ..Default::default()
         },
         User {
             id: 3,
             name: "User Three".to_string(),
             email: "user3@example.com".to_string(),
+            ..Default::default()
         },
     ];
 
@@ -425,12 +455,14 @@ fn test_user_email_formats() {
         id: 1,
         name: "User 1".to_string(),
         email: "user@company.com".to_string(),
+        ..Default::default()
     };
 
     let user2 = User {
         id: 2,
         name: "User 2".to_string(),
         email: "user.name@subdomain.company.com".to_string(),
+        ..Default::default()
     };
 
     assert!(user1.email.contains("@"));
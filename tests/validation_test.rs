@@ -0,0 +1,178 @@
+// Tests for request payload validation (runs before any SQL)
+
+use backend::error::ApiError;
+use backend::models::*;
+use validator::Validate;
+
+fn valid_employee() -> CreateEmployeeRequest {
+    CreateEmployeeRequest {
+        first_name: "Anna".to_string(),
+        last_name: "Schmidt".to_string(),
+        email: "anna.schmidt@company.com".to_string(),
+        department_id: Some("650e8400-e29b-41d4-a716-446655440001".to_string()),
+        salary_grade_id: Some("550e8400-e29b-41d4-a716-446655440002".to_string()),
+        manager_id: None,
+        role: Some(Role::Employee),
+        hire_date: Some("2024-10-31".to_string()),
+    }
+}
+
+fn field_errors(result: Result<(), validator::ValidationErrors>) -> Vec<FieldError> {
+    match ApiError::from(result.unwrap_err()) {
+        ApiError::Validation(errors) => errors,
+        other => panic!("expected validation error, got {:?}", other),
+    }
+}
+
+fn fields(errors: &[FieldError]) -> Vec<&str> {
+    errors.iter().map(|e| e.field.as_str()).collect()
+}
+
+#[test]
+fn test_valid_employee_passes() {
+    assert!(valid_employee().validate().is_ok());
+}
+
+#[test]
+fn test_employee_reports_all_errors_at_once() {
+    let req = CreateEmployeeRequest {
+        first_name: "".to_string(),
+        last_name: "x".repeat(101),
+        email: "not-an-email".to_string(),
+        department_id: Some("dept-1".to_string()),
+        salary_grade_id: Some("grade-1".to_string()),
+        manager_id: Some("mgr-1".to_string()),
+        role: Some(Role::Employee),
+        hire_date: Some("31.10.2024".to_string()),
+    };
+
+    let errors = field_errors(req.validate());
+
+    assert_eq!(
+        fields(&errors),
+        vec![
+            "department_id",
+            "email",
+            "first_name",
+            "hire_date",
+            "last_name",
+            "manager_id",
+            "salary_grade_id"
+        ]
+    );
+}
+
+#[test]
+fn test_blank_name_rejected() {
+    let mut req = valid_employee();
+    req.first_name = "   ".to_string();
+
+    let errors = field_errors(req.validate());
+
+    assert_eq!(errors[0].field, "first_name");
+    assert_eq!(errors[0].message, "must not be blank");
+}
+
+#[test]
+fn test_invalid_calendar_date_rejected() {
+    let mut req = valid_employee();
+    req.hire_date = Some("2024-02-30".to_string());
+
+    let errors = field_errors(req.validate());
+
+    assert_eq!(fields(&errors), vec!["hire_date"]);
+}
+
+#[test]
+fn test_email_length_matches_column() {
+    let mut req = valid_employee();
+    req.email = format!("{}@company.com", "a".repeat(250));
+
+    let errors = field_errors(req.validate());
+
+    assert!(errors.iter().all(|e| e.field == "email"));
+    assert!(
+        errors
+            .iter()
+            .any(|e| e.message == "must be at most 255 characters")
+    );
+}
+
+#[test]
+fn test_update_employee_only_checks_present_fields() {
+    let empty = UpdateEmployeeRequest {
+        first_name: None,
+        last_name: None,
+        email: None,
+        department_id: None,
+        salary_grade_id: None,
+        manager_id: None,
+        role: None,
+        hire_date: None,
+        active: None,
+    };
+    assert!(empty.validate().is_ok());
+
+    let invalid = UpdateEmployeeRequest {
+        email: Some("nope".to_string()),
+        hire_date: Some("yesterday".to_string()),
+        ..empty
+    };
+    let errors = field_errors(invalid.validate());
+    assert_eq!(fields(&errors), vec!["email", "hire_date"]);
+}
+
+#[test]
+fn test_department_name_and_head_rules() {
+    let req = CreateDepartmentRequest {
+        name: "x".repeat(256),
+        head_id: Some("head-1".to_string()),
+        parent_id: None,
+        promote_head: None,
+    };
+
+    let errors = field_errors(req.validate());
+
+    assert_eq!(fields(&errors), vec!["head_id", "name"]);
+}
+
+#[test]
+fn test_salary_grade_rules() {
+    let valid = CreateSalaryGradeRequest {
+        code: "E5".to_string(),
+        base_salary: 95000.0,
+        description: None,
+    };
+    assert!(valid.validate().is_ok());
+
+    let invalid = CreateSalaryGradeRequest {
+        code: "x".repeat(51),
+        base_salary: -1.0,
+        description: None,
+    };
+    let errors = field_errors(invalid.validate());
+    assert_eq!(fields(&errors), vec!["base_salary", "code"]);
+
+    let zero = UpdateSalaryGradeRequest {
+        code: None,
+        base_salary: Some(0.0),
+        description: None,
+    };
+    assert_eq!(fields(&field_errors(zero.validate())), vec!["base_salary"]);
+}
+
+#[test]
+fn test_assign_requests_require_uuids() {
+    let manager = AssignManagerRequest {
+        manager_id: "mgr-123".to_string(),
+    };
+    let grade = AssignSalaryGradeRequest {
+        salary_grade_id: "550e8400-e29b-41d4-a716-446655440002".to_string(),
+    };
+
+    assert_eq!(
+        fields(&field_errors(manager.validate())),
+        vec!["manager_id"]
+    );
+    assert!(grade.validate().is_ok());
+}